@@ -0,0 +1,80 @@
+use crate::args::{ApplyArgs, ConnectionArgs};
+use crate::error::CliError;
+use crate::migrate::{
+    connect_and_bootstrap, connection_manager, discover, lock_listener, map_executor_error,
+    print_applied_steps, reconcile_listener, select_database_name,
+};
+use crate::output;
+use crate::signal;
+use crate::style::Style;
+use crate::ui::Ui;
+use std::path::Path;
+use vellum_executor::Runner;
+use vellum_migration::SavedPlan;
+
+pub async fn run(
+    args: &ApplyArgs,
+    connection: &ConnectionArgs,
+    vellum_version: &str,
+) -> Result<(), CliError> {
+    let connection = connection_manager(connection)?;
+
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    let saved = read_saved_plan(&args.plan)?;
+    let discovered = discover(&args.discovery)?;
+
+    let pool = connect_and_bootstrap(&connection).await?;
+    let pool_for_queries = pool.clone();
+
+    let database_name = select_database_name(&pool).await?;
+
+    for line in ui.header("Vellum Apply") {
+        output::line(line);
+    }
+    output::line(ui.kv("Database", &database_name));
+    output::line(ui.kv("Plan", &args.plan.display().to_string()));
+    if saved.database != database_name {
+        output::line(ui.kv("Planned against", &saved.database));
+    }
+    output::line("");
+    output::line(ui.ok_line("Connected to database"));
+
+    let runner = Runner::new(pool, vellum_version)
+        .with_connection_settings(connection.settings().clone())
+        .with_lock_settings(args.lock.settings())
+        .with_lock_listener(lock_listener(&ui))
+        .with_reconcile_listener(reconcile_listener(&ui))
+        .with_cancellation(signal::cancellation(&ui));
+    let report = runner
+        .apply_saved(&saved, &discovered.versioned, &discovered.repeatable)
+        .await
+        .map_err(map_executor_error)?;
+
+    output::line(ui.ok_line("Advisory lock acquired"));
+    output::line(ui.ok_line("Plan is up to date with the database and migration files"));
+    output::line(ui.info_line(&format!(
+        "Applying {} migrations",
+        report.plan.to_apply().count()
+    )));
+    output::line("");
+
+    print_applied_steps(&ui, &pool_for_queries, &report).await;
+    output::line("");
+    output::line(ui.ok_line("Plan applied successfully"));
+    output::line(ui.footer());
+
+    Ok(())
+}
+
+fn read_saved_plan(path: &Path) -> Result<SavedPlan, CliError> {
+    let read_error = |message: String| {
+        CliError::user_error(format!("Failed to read plan file {}", path.display()))
+            .with_reason(message)
+            .with_action("Pass a plan file written by `vellum plan --out <FILE>`.")
+    };
+
+    let contents = std::fs::read_to_string(path).map_err(|e| read_error(e.to_string()))?;
+    serde_json::from_str(&contents).map_err(|e| read_error(e.to_string()))
+}
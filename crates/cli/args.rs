@@ -1,4 +1,13 @@
-use clap::{Parser, Subcommand};
+use crate::config;
+use crate::error::CliError;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use std::time::Duration;
+use sqlx::postgres::PgSslMode;
+use vellum_executor::{ConnectionSettings, LockScope, LockSettings, LockWait};
+use vellum_migration::{
+    ChecksumAlgorithm, DiscoveryOptions, FilenameScheme, Version, VersionScheme,
+};
 
 #[derive(Parser)]
 #[command(
@@ -8,8 +17,8 @@ use clap::{Parser, Subcommand};
     propagate_version = true
 )]
 pub struct Cli {
-    #[arg(long, env = "VELLUM_DATABASE_URL", value_name = "URL", global = true)]
-    pub database_url: Option<String>,
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
 
     #[command(subcommand)]
     pub command: Command,
@@ -19,13 +28,398 @@ pub struct Cli {
 pub enum Command {
     Migrate(MigrateArgs),
     Status(StatusArgs),
+    Plan(PlanArgs),
+    Apply(ApplyArgs),
+    Check(CheckArgs),
+    Verify(VerifyArgs),
+    Repair(RepairArgs),
+    Baseline(BaselineArgs),
+    Lock(LockArgs),
+    History(HistoryArgs),
+    New(NewArgs),
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct MigrateArgs {
     #[arg(long)]
     pub dry_run: bool,
+
+    #[arg(long, requires = "dry_run")]
+    pub explain_analyze: bool,
+
+    #[arg(long, requires = "dry_run", conflicts_with = "explain_analyze")]
+    pub clone: bool,
+
+    #[arg(long, value_name = "DATABASE", requires = "clone")]
+    pub clone_from: Option<String>,
+
+    #[command(flatten)]
+    pub lock: LockOptions,
+
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct StatusArgs {
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct PlanArgs {
+    #[arg(long, value_enum, default_value = "human")]
+    pub format: OutputFormat,
+
+    #[arg(long, value_name = "FILE")]
+    pub out: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct ApplyArgs {
+    #[arg(value_name = "PLAN")]
+    pub plan: PathBuf,
+
+    #[command(flatten)]
+    pub lock: LockOptions,
+
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct VerifyArgs {
+    #[arg(long)]
+    pub shadow: bool,
+
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
 }
 
 #[derive(Parser, Debug, Clone)]
-pub struct StatusArgs {}
+pub struct RepairArgs {
+    #[arg(value_name = "VERSION", required = true)]
+    pub versions: Vec<Version>,
+
+    #[arg(long)]
+    pub reason: String,
+
+    #[command(flatten)]
+    pub lock: LockOptions,
+
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[command(disable_version_flag = true)]
+pub struct BaselineArgs {
+    #[arg(long, value_name = "N")]
+    pub version: Version,
+
+    #[command(flatten)]
+    pub lock: LockOptions,
+
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct LockArgs {
+    #[command(flatten)]
+    pub scope: LockScopeArgs,
+
+    #[command(subcommand)]
+    pub command: LockCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum LockCommand {
+    Status,
+    Release(LockReleaseArgs),
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct LockReleaseArgs {
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct HistoryArgs {
+    #[command(subcommand)]
+    pub command: HistoryCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum HistoryCommand {
+    Reconcile(ReconcileArgs),
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct ReconcileArgs {
+    #[command(flatten)]
+    pub lock: LockOptions,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct NewArgs {
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    #[arg(
+        long,
+        env = "VELLUM_VERSION_SCHEME",
+        value_name = "SCHEME",
+        default_value = "timestamp",
+        value_parser = parse_version_scheme
+    )]
+    pub scheme: VersionScheme,
+
+    #[arg(
+        long,
+        env = "VELLUM_FILENAME_SCHEME",
+        value_name = "SCHEME",
+        default_value = "vellum",
+        value_parser = parse_filename_scheme
+    )]
+    pub filename_scheme: FilenameScheme,
+
+    #[arg(long)]
+    pub down: bool,
+
+    #[arg(long)]
+    pub header: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ConnectionArgs {
+    #[arg(long, env = "VELLUM_DATABASE_URL", value_name = "URL", global = true)]
+    pub database_url: Option<String>,
+
+    #[arg(
+        long,
+        env = "VELLUM_CONNECT_TIMEOUT",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        global = true
+    )]
+    pub connect_timeout: Option<Duration>,
+
+    #[arg(
+        long,
+        env = "VELLUM_POOL_SIZE",
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        global = true
+    )]
+    pub pool_size: Option<u32>,
+
+    #[arg(
+        long,
+        env = "VELLUM_SSLMODE",
+        value_name = "MODE",
+        value_parser = parse_ssl_mode,
+        global = true
+    )]
+    pub sslmode: Option<PgSslMode>,
+
+    #[arg(long, env = "VELLUM_SSLROOTCERT", value_name = "PATH", global = true)]
+    pub sslrootcert: Option<PathBuf>,
+
+    #[arg(long, env = "VELLUM_PASSWORD_FILE", value_name = "PATH", global = true)]
+    pub password_file: Option<PathBuf>,
+}
+
+impl ConnectionArgs {
+    pub fn settings(&self) -> ConnectionSettings {
+        let defaults = ConnectionSettings::default();
+        ConnectionSettings {
+            pool_size: self.pool_size.unwrap_or(defaults.pool_size),
+            connect_timeout: self.connect_timeout.unwrap_or(defaults.connect_timeout),
+            ssl_mode: self.sslmode,
+            ssl_root_cert: self.sslrootcert.clone(),
+            password_file: self.password_file.clone(),
+            ..defaults
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct LockScopeArgs {
+    #[arg(long, env = "VELLUM_LOCK_NAMESPACE", value_name = "NAME", global = true)]
+    pub lock_namespace: Option<String>,
+
+    #[arg(long, env = "VELLUM_LOCK_DATABASE", value_name = "DATABASE", global = true)]
+    pub lock_database: Option<String>,
+}
+
+impl LockScopeArgs {
+    pub fn scope(&self) -> LockScope {
+        LockScope {
+            namespace: self.lock_namespace.clone(),
+            database: self.lock_database.clone(),
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct LockOptions {
+    #[command(flatten)]
+    pub scope: LockScopeArgs,
+
+    #[arg(
+        long,
+        env = "VELLUM_LOCK_TIMEOUT",
+        value_name = "DURATION",
+        value_parser = parse_lock_timeout
+    )]
+    pub lock_timeout: Option<LockWait>,
+
+    #[arg(long, conflicts_with = "lock_timeout")]
+    pub no_wait: bool,
+}
+
+impl LockOptions {
+    pub fn settings(&self) -> LockSettings {
+        let wait = if self.no_wait {
+            LockWait::NoWait
+        } else {
+            self.lock_timeout.unwrap_or_default()
+        };
+
+        LockSettings {
+            scope: self.scope.scope(),
+            wait,
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DiscoveryArgs {
+    #[arg(
+        long,
+        env = "VELLUM_MIGRATIONS",
+        value_name = "PATH",
+        default_value = "migrations"
+    )]
+    pub migrations: PathBuf,
+
+    #[arg(
+        long,
+        env = "VELLUM_CHECKSUM_ALGORITHM",
+        value_name = "ALGORITHM",
+        default_value = "sha256",
+        value_parser = parse_checksum_algorithm
+    )]
+    pub checksum_algorithm: ChecksumAlgorithm,
+
+    #[arg(
+        long,
+        env = "VELLUM_FILENAME_SCHEME",
+        value_name = "SCHEME",
+        default_value = "vellum",
+        value_parser = parse_filename_scheme
+    )]
+    pub filename_scheme: FilenameScheme,
+
+    #[arg(long, env = "VELLUM_CONFIG", value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[arg(
+        long = "placeholder",
+        value_name = "KEY=VALUE",
+        value_parser = parse_placeholder
+    )]
+    pub placeholders: Vec<(String, String)>,
+}
+
+impl DiscoveryArgs {
+    pub fn options(&self) -> Result<DiscoveryOptions, CliError> {
+        let placeholders =
+            config::resolve_placeholders(self.config.as_deref(), &self.placeholders)?;
+
+        // Without any configured value, files are used exactly as written.
+        Ok(DiscoveryOptions {
+            checksum_algorithm: self.checksum_algorithm,
+            filename_scheme: self.filename_scheme,
+            placeholders: (!placeholders.is_empty()).then_some(placeholders),
+        })
+    }
+}
+
+fn parse_checksum_algorithm(value: &str) -> Result<ChecksumAlgorithm, String> {
+    ChecksumAlgorithm::from_id(value)
+        .ok_or_else(|| "expected one of: sha256, normalized-sha256".to_string())
+}
+
+fn parse_filename_scheme(value: &str) -> Result<FilenameScheme, String> {
+    FilenameScheme::from_id(value)
+        .ok_or_else(|| "expected one of: vellum, flyway, golang-migrate".to_string())
+}
+
+fn parse_placeholder(value: &str) -> Result<(String, String), String> {
+    let (name, value) = value
+        .split_once('=')
+        .ok_or_else(|| "expected KEY=VALUE".to_string())?;
+
+    if !vellum_migration::placeholder::is_valid_name(name) {
+        return Err(format!(
+            "invalid placeholder name '{name}': use ASCII letters, digits, '_', '.' and '-'"
+        ));
+    }
+
+    Ok((name.to_string(), value.to_string()))
+}
+
+fn parse_version_scheme(value: &str) -> Result<VersionScheme, String> {
+    VersionScheme::from_id(value).ok_or_else(|| "expected one of: integer, timestamp".to_string())
+}
+
+fn parse_lock_timeout(value: &str) -> Result<LockWait, String> {
+    if value == "forever" {
+        return Ok(LockWait::Forever);
+    }
+
+    parse_duration(value)
+        .map(LockWait::Timeout)
+        .map_err(|e| format!("{e}, or 'forever'"))
+}
+
+fn parse_ssl_mode(value: &str) -> Result<PgSslMode, String> {
+    value.parse().map_err(|_| {
+        "expected one of: disable, allow, prefer, require, verify-ca, verify-full".to_string()
+    })
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (digits, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => value.split_at(at),
+        None => (value, "s"),
+    };
+    let amount: u64 = digits
+        .parse()
+        .map_err(|_| "expected a duration such as 30s, 500ms or 5m".to_string())?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "s" => Ok(Duration::from_secs(amount)),
+        "m" => Ok(Duration::from_secs(amount.saturating_mul(60))),
+        _ => Err(format!("unknown unit '{unit}': use ms, s or m")),
+    }
+}
@@ -0,0 +1,65 @@
+use crate::args::{BaselineArgs, ConnectionArgs};
+use crate::error::CliError;
+use crate::migrate::{
+    bootstrap, connect, connection_manager, discover, lock_listener, map_executor_error,
+    migration_label, reconcile_listener, select_database_name,
+};
+use crate::output;
+use crate::signal;
+use crate::style::Style;
+use crate::ui::Ui;
+use vellum_executor::Runner;
+
+pub async fn run(
+    args: &BaselineArgs,
+    connection: &ConnectionArgs,
+    vellum_version: &str,
+) -> Result<(), CliError> {
+    let connection = connection_manager(connection)?;
+
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    let migrations = discover(&args.discovery)?.versioned;
+
+    let pool = connect(&connection).await?;
+    bootstrap(&pool, "vellum baseline").await?;
+
+    let database_name = select_database_name(&pool).await?;
+
+    for line in ui.header("Vellum Baseline") {
+        output::line(line);
+    }
+    output::line(ui.kv("Database", &database_name));
+    output::line(ui.kv("Baseline version", &args.version.to_string()));
+    output::line("");
+    output::line(ui.ok_line("Connected to database"));
+
+    let runner = Runner::new(pool, vellum_version)
+        .with_connection_settings(connection.settings().clone())
+        .with_lock_settings(args.lock.settings())
+        .with_lock_listener(lock_listener(&ui))
+        .with_reconcile_listener(reconcile_listener(&ui))
+        .with_cancellation(signal::cancellation(&ui));
+    let report = runner
+        .baseline(&migrations, &args.version)
+        .await
+        .map_err(map_executor_error)?;
+
+    output::line(ui.ok_line("Advisory lock acquired"));
+    output::line(ui.info_line(&format!(
+        "Recording {} migrations as applied",
+        report.recorded.len()
+    )));
+    output::line("");
+
+    for m in migrations.iter().filter(|m| report.recorded.contains(&m.version)) {
+        output::line(ui.list_item_with_suffix(&migration_label(m), "OK", Some("(baseline)")));
+    }
+    output::line("");
+    output::line(ui.ok_line("Baseline recorded successfully"));
+    output::line(ui.ok_line("No migrations were executed"));
+    output::line(ui.footer());
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use crate::args::CheckArgs;
+use crate::error::CliError;
+use crate::migrate::map_discovery_error;
+use crate::output;
+use crate::style::{Color, Style};
+use crate::ui::Ui;
+use vellum_migration::{discover_from, open_source, ChecksumAlgorithm, SourceMap};
+use vellum_sql_engine::{check_sql, locate, Diagnostic, SourceLocation};
+
+pub fn run(args: &CheckArgs) -> Result<(), CliError> {
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    // Checksums are irrelevant here, and normalized checksums would reject
+    // unparsable files during discovery before they could be reported.
+    let mut options = args.discovery.options()?;
+    options.checksum_algorithm = ChecksumAlgorithm::Sha256;
+
+    let source = open_source(&args.discovery.migrations).map_err(map_discovery_error)?;
+    let discovered = discover_from(source.as_ref(), &options).map_err(map_discovery_error)?;
+
+    // Rendered SQL is checked, but problems are reported against the file.
+    let files: Vec<(&str, &str, &str, &SourceMap)> = discovered
+        .versioned
+        .iter()
+        .map(|m| (m.filename.as_str(), m.sql.as_str(), m.raw_sql.as_str(), &m.source_map))
+        .chain(
+            discovered
+                .repeatable
+                .iter()
+                .map(|m| (m.filename.as_str(), m.sql.as_str(), m.raw_sql.as_str(), &m.source_map)),
+        )
+        .collect();
+
+    for line in ui.header("Vellum Check") {
+        output::line(line);
+    }
+    output::line(ui.kv("Migrations", &source.location()));
+    output::line(ui.kv("Files", &files.len().to_string()));
+    output::line("");
+
+    let mut problems = 0usize;
+    let mut failed_files = 0usize;
+    for (filename, sql, raw_sql, source_map) in &files {
+        let diagnostics = check_sql(sql);
+        if diagnostics.is_empty() {
+            continue;
+        }
+
+        failed_files += 1;
+        problems += diagnostics.len();
+        let path = args.discovery.migrations.join(filename);
+        for diagnostic in &diagnostics {
+            let location = locate(raw_sql, source_map.raw_offset(diagnostic.offset));
+            print_diagnostic(&style, &path.display().to_string(), &location, diagnostic);
+        }
+    }
+
+    if problems > 0 {
+        output::line(ui.footer());
+        return Err(CliError::user_error("Migration check failed")
+            .with_reason(format!(
+                "{problems} problem(s) found in {failed_files} of {} file(s).",
+                files.len()
+            ))
+            .with_action("Fix the reported problems and re-run `vellum check`."));
+    }
+
+    output::line(ui.ok_line(&format!("All {} migrations are valid", files.len())));
+    output::line(ui.footer());
+
+    Ok(())
+}
+
+fn print_diagnostic(style: &Style, path: &str, location: &SourceLocation, diagnostic: &Diagnostic) {
+    output::line(format!(
+        "{path}:{}:{}: {}: {}",
+        location.line,
+        location.column,
+        style.paint_stdout(Color::Red, "error"),
+        diagnostic.message
+    ));
+
+    let gutter = location.line.to_string();
+    let indent: String = location
+        .line_text
+        .chars()
+        .take(location.column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    output::line(format!(" {gutter} | {}", location.line_text));
+    output::line(format!(
+        " {} | {indent}{}",
+        " ".repeat(gutter.len()),
+        style.paint_stdout(Color::Red, "^")
+    ));
+    output::line("");
+}
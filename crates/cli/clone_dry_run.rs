@@ -0,0 +1,146 @@
+use crate::args::MigrateArgs;
+use crate::error::CliError;
+use crate::migrate::{
+    connect_and_bootstrap, discover, map_executor_error, print_applied_steps,
+};
+use crate::output;
+use crate::style::{Color, Style};
+use crate::ui::Ui;
+use std::collections::BTreeMap;
+use vellum_executor::{introspect, ConnectionManager, RunReport, Runner, ShadowDatabase};
+use vellum_migration::{Migration, RepeatableMigration};
+use vellum_schema::{diff_schemas, DifferenceKind, Schema, SchemaDifference};
+
+pub async fn run(
+    args: &MigrateArgs,
+    connection: &ConnectionManager,
+    vellum_version: &str,
+) -> Result<(), CliError> {
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    let discovered = discover(&args.discovery)?;
+
+    let target = connection.database().map(str::to_string).ok_or_else(|| {
+        CliError::user_error("Database name is required for a clone dry-run")
+            .with_action("Pass a postgres:// database URL that names the target database.")
+    })?;
+    let template = args.clone_from.clone().unwrap_or_else(|| target.clone());
+
+    // The template must not have any sessions while it is copied, so the
+    // clone is created from a maintenance database instead of the target.
+    let maintenance = if template == "postgres" { "template1" } else { "postgres" };
+    let admin = connection
+        .for_database(maintenance)
+        .pool()
+        .await
+        .map_err(|e| {
+            CliError::user_error("Failed to connect to database")
+                .with_reason(format!("Could not connect to the {maintenance} maintenance database: {e}."))
+                .with_action("A clone dry-run needs access to the maintenance database of the same server.")
+        })?;
+
+    for line in ui.header("Vellum Migration (dry-run)") {
+        output::line(line);
+    }
+    output::line(ui.kv("Database", &target));
+    output::line(ui.kv("Mode", "clone"));
+    if template != target {
+        output::line(ui.kv("Cloned from", &template));
+    }
+    output::line("");
+
+    let clone = ShadowDatabase::clone_of(&admin, &template)
+        .await
+        .map_err(map_executor_error)?;
+    output::line(ui.ok_line(&format!("Created clone {}", clone.name())));
+
+    let applied = apply(&ui, connection, &clone, vellum_version, &discovered.versioned, &discovered.repeatable).await;
+    let dropped = clone.drop(&admin).await;
+    admin.close().await;
+    let (report, differences) = applied?;
+    dropped.map_err(map_executor_error)?;
+
+    output::line("");
+    output::line(ui.ok_line("Dropped clone"));
+    output::line("");
+
+    if differences.is_empty() {
+        output::line(ui.info_line("Migrations do not change the schema"));
+    } else {
+        output::line(ui.info_line("Schema changes"));
+        for difference in &differences {
+            print_change(ui.style(), difference);
+        }
+    }
+
+    output::line("");
+    output::line(ui.ok_line(&format!(
+        "{} migrations applied to the clone",
+        report.plan.to_apply().count()
+    )));
+    output::line(ui.ok_line(&format!("No changes were applied to {target}")));
+    output::line(ui.footer());
+
+    Ok(())
+}
+
+async fn apply(
+    ui: &Ui,
+    connection: &ConnectionManager,
+    clone: &ShadowDatabase,
+    vellum_version: &str,
+    migrations: &[Migration],
+    repeatables: &[RepeatableMigration],
+) -> Result<(RunReport, Vec<SchemaDifference>), CliError> {
+    let pool = connect_and_bootstrap(&connection.for_database(clone.name())).await?;
+    let result = apply_and_compare(ui, &pool, vellum_version, migrations, repeatables).await;
+    pool.close().await;
+    result
+}
+
+async fn apply_and_compare(
+    ui: &Ui,
+    pool: &sqlx::PgPool,
+    vellum_version: &str,
+    migrations: &[Migration],
+    repeatables: &[RepeatableMigration],
+) -> Result<(RunReport, Vec<SchemaDifference>), CliError> {
+    let before: BTreeMap<String, Schema> = introspect(pool).await.map_err(map_executor_error)?;
+
+    let report = Runner::new(pool.clone(), vellum_version)
+        .run(migrations, repeatables)
+        .await
+        .map_err(map_executor_error)?;
+
+    output::line(ui.info_line(&format!(
+        "Applied {} migrations",
+        report.plan.to_apply().count()
+    )));
+    output::line("");
+    print_applied_steps(ui, pool, &report).await;
+
+    let after = introspect(pool).await.map_err(map_executor_error)?;
+    Ok((report, diff_schemas(&before, &after)))
+}
+
+fn print_change(style: &Style, difference: &SchemaDifference) {
+    let (label, color) = match difference.kind {
+        DifferenceKind::Unexpected => ("added  ", Color::Green),
+        DifferenceKind::Missing => ("removed", Color::Red),
+        DifferenceKind::Changed => ("changed", Color::Yellow),
+    };
+
+    output::line(format!(
+        "  {} {}",
+        style.paint_stdout(color, label),
+        difference.object
+    ));
+    match (&difference.expected, &difference.actual) {
+        (Some(before), Some(after)) => output::line(format!("      {before} -> {after}")),
+        (Some(description), None) | (None, Some(description)) => {
+            output::line(format!("      {description}"))
+        }
+        (None, None) => {}
+    }
+}
@@ -0,0 +1,200 @@
+use crate::error::CliError;
+use std::path::Path;
+use vellum_migration::placeholder::is_valid_name;
+use vellum_migration::Placeholders;
+
+pub const DEFAULT_CONFIG_FILE: &str = "vellum.toml";
+pub const PLACEHOLDER_ENV_PREFIX: &str = "VELLUM_PLACEHOLDER_";
+
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub placeholders: Placeholders,
+}
+
+pub fn load(path: Option<&Path>) -> Result<Config, CliError> {
+    let (path, required) = match path {
+        Some(path) => (path, true),
+        None => (Path::new(DEFAULT_CONFIG_FILE), false),
+    };
+
+    if !required && !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        CliError::user_error("Failed to read config file")
+            .with_reason(format!("{}: {e}", path.display()))
+            .with_action("Check the --config path (or VELLUM_CONFIG) and file permissions.")
+    })?;
+
+    parse(&contents).map_err(|reason| {
+        CliError::user_error("Invalid config file")
+            .with_reason(format!("{}: {reason}", path.display()))
+            .with_action("Fix the config file and try again.")
+    })
+}
+
+fn parse(contents: &str) -> Result<Config, String> {
+    let table: toml::Table = contents.parse().map_err(|e: toml::de::Error| e.to_string())?;
+
+    let mut config = Config::default();
+    if let Some(value) = table.get("placeholders") {
+        let placeholders = value
+            .as_table()
+            .ok_or_else(|| "'placeholders' must be a table".to_string())?;
+
+        for (name, value) in placeholders {
+            if !is_valid_name(name) {
+                return Err(format!("invalid placeholder name '{name}'"));
+            }
+
+            let value = match value {
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                toml::Value::Boolean(b) => b.to_string(),
+                _ => return Err(format!("placeholder '{name}' must be a string, number or boolean")),
+            };
+            config.placeholders.insert(name.clone(), value);
+        }
+    }
+
+    Ok(config)
+}
+
+pub fn resolve_placeholders(
+    config_path: Option<&Path>,
+    cli_values: &[(String, String)],
+) -> Result<Placeholders, CliError> {
+    let config = load(config_path)?;
+    Ok(resolve(config, std::env::vars(), cli_values))
+}
+
+// Environment variable names cannot carry `.` or `-` and are conventionally
+// upper-case, so `VELLUM_PLACEHOLDER_<NAME>` sets every placeholder from the
+// config file whose name maps to <NAME> (upper-cased, `.` and `-` as `_`).
+// Any other <NAME> is taken lower-cased; names that fit neither rule can
+// only be set in the config file or with `--placeholder`.
+fn resolve(
+    config: Config,
+    env: impl IntoIterator<Item = (String, String)>,
+    cli_values: &[(String, String)],
+) -> Placeholders {
+    let mut placeholders = config.placeholders;
+
+    let mut env_values: Vec<(String, String)> = env
+        .into_iter()
+        .filter_map(|(key, value)| {
+            key.strip_prefix(PLACEHOLDER_ENV_PREFIX)
+                .map(|suffix| (suffix.to_string(), value))
+        })
+        .collect();
+    env_values.sort();
+
+    for (suffix, value) in env_values {
+        let configured: Vec<String> = placeholders
+            .iter()
+            .map(|(name, _)| name)
+            .filter(|name| env_suffix(name) == suffix)
+            .map(str::to_string)
+            .collect();
+
+        if configured.is_empty() {
+            placeholders.insert(suffix.to_ascii_lowercase(), value);
+        } else {
+            for name in configured {
+                placeholders.insert(name, value.clone());
+            }
+        }
+    }
+
+    for (name, value) in cli_values.iter().cloned() {
+        placeholders.insert(name, value);
+    }
+
+    placeholders
+}
+
+fn env_suffix(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '.' | '-' => '_',
+            c => c.to_ascii_uppercase(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, resolve};
+
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn config(contents: &str) -> super::Config {
+        parse(contents).unwrap()
+    }
+
+    #[test]
+    fn cli_overrides_env_overrides_config() {
+        let config = config("[placeholders]\nschema = \"file\"\nrole = \"file\"\nowner = \"file\"\n");
+        let env = env(&[
+            ("VELLUM_PLACEHOLDER_ROLE", "env"),
+            ("VELLUM_PLACEHOLDER_OWNER", "env"),
+        ]);
+        let cli = vec![("owner".to_string(), "cli".to_string())];
+
+        let placeholders = resolve(config, env, &cli);
+
+        assert_eq!(placeholders.get("schema"), Some("file"));
+        assert_eq!(placeholders.get("role"), Some("env"));
+        assert_eq!(placeholders.get("owner"), Some("cli"));
+    }
+
+    #[test]
+    fn env_sets_configured_names_with_dots_dashes_and_capitals() {
+        let config = config(
+            "[placeholders]\n\"role.read\" = \"file\"\n\"table-space\" = \"file\"\nSchema = \"file\"\n",
+        );
+        let env = env(&[
+            ("VELLUM_PLACEHOLDER_ROLE_READ", "reader"),
+            ("VELLUM_PLACEHOLDER_TABLE_SPACE", "fast"),
+            ("VELLUM_PLACEHOLDER_SCHEMA", "app"),
+        ]);
+
+        let placeholders = resolve(config, env, &[]);
+
+        assert_eq!(placeholders.get("role.read"), Some("reader"));
+        assert_eq!(placeholders.get("table-space"), Some("fast"));
+        assert_eq!(placeholders.get("Schema"), Some("app"));
+        assert_eq!(placeholders.get("role_read"), None);
+        assert_eq!(placeholders.get("schema"), None);
+    }
+
+    #[test]
+    fn unconfigured_env_names_are_lower_cased() {
+        let env = env(&[("VELLUM_PLACEHOLDER_SCHEMA", "app"), ("OTHER", "x")]);
+
+        let placeholders = resolve(super::Config::default(), env, &[]);
+
+        assert_eq!(placeholders.iter().collect::<Vec<_>>(), vec![("schema", "app")]);
+    }
+
+    #[test]
+    fn invalid_config_names_and_values_are_rejected() {
+        assert!(parse("[placeholders]\n\"a b\" = \"x\"\n").is_err());
+        assert!(parse("[placeholders]\nschema = [1]\n").is_err());
+        assert!(parse("placeholders = 1\n").is_err());
+        assert_eq!(
+            parse("[placeholders]\nport = 5432\nlive = true\n")
+                .unwrap()
+                .placeholders
+                .get("port"),
+            Some("5432")
+        );
+    }
+}
@@ -6,6 +6,7 @@ pub enum ExitCode {
     UserError = 1,
     MigrationFailed = 2,
     LockUnavailable = 3,
+    Cancelled = 130,
 }
 
 impl ExitCode {
@@ -54,6 +55,16 @@ impl CliError {
         }
     }
 
+    pub fn cancelled(title: impl Into<String>) -> Self {
+        Self {
+            code: ExitCode::Cancelled,
+            title: title.into(),
+            reason: None,
+            meaning: None,
+            action: None,
+        }
+    }
+
     pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
         self.reason = Some(reason.into());
         self
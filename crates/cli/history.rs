@@ -0,0 +1,84 @@
+use crate::args::{ConnectionArgs, HistoryArgs, HistoryCommand, ReconcileArgs};
+use crate::error::CliError;
+use crate::migrate::{
+    connect_and_bootstrap, connection_manager, lock_listener, map_executor_error,
+    select_database_name,
+};
+use crate::output;
+use crate::signal;
+use crate::style::Style;
+use crate::ui::Ui;
+use vellum_executor::{AbandonedRun, Runner};
+
+pub async fn run(
+    args: &HistoryArgs,
+    connection: &ConnectionArgs,
+    vellum_version: &str,
+) -> Result<(), CliError> {
+    match &args.command {
+        HistoryCommand::Reconcile(args) => run_reconcile(args, connection, vellum_version).await,
+    }
+}
+
+async fn run_reconcile(
+    args: &ReconcileArgs,
+    connection: &ConnectionArgs,
+    vellum_version: &str,
+) -> Result<(), CliError> {
+    let connection = connection_manager(connection)?;
+
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    let pool = connect_and_bootstrap(&connection).await?;
+    let database_name = select_database_name(&pool).await?;
+
+    for line in ui.header("Vellum History Reconcile") {
+        output::line(line);
+    }
+    output::line(ui.kv("Database", &database_name));
+    output::line("");
+    output::line(ui.ok_line("Connected to database"));
+
+    let runner = Runner::new(pool.clone(), vellum_version)
+        .with_connection_settings(connection.settings().clone())
+        .with_lock_settings(args.lock.settings())
+        .with_lock_listener(lock_listener(&ui))
+        .with_cancellation(signal::cancellation(&ui));
+    let abandoned = runner.reconcile().await.map_err(map_executor_error);
+    pool.close().await;
+    let abandoned = abandoned?;
+    output::line(ui.ok_line("Advisory lock acquired"));
+
+    if abandoned.is_empty() {
+        output::line(ui.ok_line("No abandoned runs"));
+    } else {
+        print_abandoned_runs(&ui, &abandoned);
+    }
+    output::line(ui.footer());
+
+    Ok(())
+}
+
+pub(crate) fn print_abandoned_runs(ui: &Ui, abandoned: &[AbandonedRun]) {
+    output::line(ui.info_line(&format!(
+        "Marked {} run(s) left running by a process that no longer exists as abandoned",
+        abandoned.len()
+    )));
+
+    for run in abandoned {
+        let owner = match (&run.client_host, run.owner_pid) {
+            (Some(host), Some(pid)) => format!(", {host}, backend pid {pid}"),
+            (Some(host), None) => format!(", {host}"),
+            (None, Some(pid)) => format!(", backend pid {pid}"),
+            (None, None) => String::new(),
+        };
+        output::line(format!(
+            "  {} {} ({}, started {}{owner})",
+            ui.style().bullet(),
+            run.run_id,
+            run.mode,
+            run.started_at
+        ));
+    }
+}
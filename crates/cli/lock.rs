@@ -0,0 +1,175 @@
+use crate::args::{ConnectionArgs, LockArgs, LockCommand, LockReleaseArgs};
+use crate::error::CliError;
+use crate::migrate::{connection_manager, map_executor_error};
+use crate::output;
+use crate::style::Style;
+use crate::ui::Ui;
+use sqlx::{Connection, PgConnection};
+use vellum_lock::{advisory, holder, LockError, LockHolder, LockRecord};
+
+pub async fn run(args: &LockArgs, connection: &ConnectionArgs) -> Result<(), CliError> {
+    let mut connection = connection_manager(connection)?;
+    let scope = args.scope.scope();
+    if let Some(database) = &scope.database {
+        connection = connection.for_database(database);
+    }
+
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    let mut conn = connection.connection().await.map_err(map_executor_error)?;
+
+    let database_name = advisory::current_database(&mut conn)
+        .await
+        .map_err(map_lock_error)?;
+    let key = scope.key(&database_name);
+
+    let title = match &args.command {
+        LockCommand::Status => "Vellum Lock Status",
+        LockCommand::Release(_) => "Vellum Lock Release",
+    };
+    for line in ui.header(title) {
+        output::line(line);
+    }
+    output::line(ui.kv("Database", &database_name));
+    if let Some(namespace) = &scope.namespace {
+        output::line(ui.kv("Lock namespace", namespace));
+    }
+    output::line(ui.kv("Lock key", &key.to_string()));
+    output::line("");
+
+    let result = match &args.command {
+        LockCommand::Status => status(&ui, &mut conn, key).await,
+        LockCommand::Release(release_args) => release(&ui, &mut conn, key, release_args).await,
+    };
+    let _ = conn.close().await;
+    result?;
+
+    output::line(ui.footer());
+    Ok(())
+}
+
+async fn status(ui: &Ui, conn: &mut PgConnection, key: i64) -> Result<(), CliError> {
+    match holder::holder(conn, key).await.map_err(map_lock_error)? {
+        Some(holder) => {
+            output::line(ui.info_line("Migration lock is held"));
+            output::line("");
+            print_holder(ui, &holder);
+        }
+        None => {
+            output::line(ui.ok_line("Migration lock is free"));
+            if let Some(record) = holder::recorded_owner(conn, key).await.map_err(map_lock_error)? {
+                output::line(ui.info_line(&format!(
+                    "Stale lock record left by run {} (acquired {})",
+                    record.run_id, record.acquired_at
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn release(
+    ui: &Ui,
+    conn: &mut PgConnection,
+    key: i64,
+    args: &LockReleaseArgs,
+) -> Result<(), CliError> {
+    let Some(current) = holder::holder(conn, key).await.map_err(map_lock_error)? else {
+        if holder::clear_record(conn, key).await.map_err(map_lock_error)? {
+            output::line(ui.ok_line("Removed stale lock record"));
+        }
+        output::line(ui.ok_line("Migration lock is free"));
+        return Ok(());
+    };
+
+    print_holder(ui, &current);
+    output::line("");
+
+    if !args.force {
+        return Err(CliError::user_error("Migration lock is held")
+            .with_reason(format!("The migration lock is held by {current}."))
+            .with_meaning("Releasing it terminates the session holding the lock. A run that is still alive notices within seconds, rolls back its in-flight migration and fails.")
+            .with_action("Make sure the run is stuck, then re-run with `vellum lock release --force`."));
+    }
+
+    let terminated = holder::terminate(conn, current.pid)
+        .await
+        .map_err(map_lock_error)?;
+    if !terminated {
+        return Err(CliError::lock_unavailable("Failed to release migration lock")
+            .with_reason(format!("Backend pid {} could not be terminated.", current.pid))
+            .with_action("Terminating another session requires superuser or the pg_signal_backend role."));
+    }
+
+    // pg_terminate_backend waits for the session to exit, which releases its
+    // advisory lock, so the lock must be gone by now.
+    if holder::holder(conn, key)
+        .await
+        .map_err(map_lock_error)?
+        .is_some_and(|h| h.pid == current.pid)
+    {
+        return Err(CliError::lock_unavailable("Failed to release migration lock")
+            .with_reason(format!("Backend pid {} is still holding the lock.", current.pid))
+            .with_action("Check the session in pg_stat_activity and try again."));
+    }
+
+    holder::clear_record(conn, key).await.map_err(map_lock_error)?;
+    output::line(ui.ok_line(&format!(
+        "Terminated backend pid {} and released the migration lock",
+        current.pid
+    )));
+
+    Ok(())
+}
+
+fn print_holder(ui: &Ui, holder: &LockHolder) {
+    if let Some(LockRecord {
+        run_id,
+        run_mode,
+        client_host,
+        client_pid,
+        acquired_at,
+        held_seconds,
+        ..
+    }) = &holder.record
+    {
+        output::line(ui.kv("Run", &run_id.to_string()));
+        if let Some(mode) = run_mode {
+            output::line(ui.kv("Mode", mode));
+        }
+        let client = match (client_host, client_pid) {
+            (Some(host), Some(pid)) => format!("{host} (pid {pid})"),
+            (Some(host), None) => host.clone(),
+            (None, Some(pid)) => format!("pid {pid}"),
+            (None, None) => "unknown".to_string(),
+        };
+        output::line(ui.kv("Client", &client));
+        output::line(ui.kv("Acquired at", acquired_at));
+        output::line(ui.kv("Held for", &format!("{held_seconds}s")));
+    }
+
+    output::line(ui.kv("Backend pid", &holder.pid.to_string()));
+    if let Some(user) = &holder.user {
+        output::line(ui.kv("User", user));
+    }
+    if let Some(addr) = &holder.client_addr {
+        output::line(ui.kv("Client address", addr));
+    }
+    if let Some(name) = holder.application_name.as_deref().filter(|n| !n.is_empty()) {
+        output::line(ui.kv("Application", name));
+    }
+    if let Some(state) = &holder.state {
+        output::line(ui.kv("Session state", state));
+    }
+    if let Some(started) = &holder.backend_start {
+        output::line(ui.kv("Session started", started));
+    }
+}
+
+fn map_lock_error(err: LockError) -> CliError {
+    CliError::user_error("Failed to inspect migration lock")
+        .with_reason(err.to_string())
+        .with_action("Verify database connectivity and permissions, then try again.")
+}
@@ -1,62 +1,45 @@
-use crate::args::MigrateArgs;
+use crate::args::{ConnectionArgs, DiscoveryArgs, MigrateArgs};
+use crate::clone_dry_run;
 use crate::error::CliError;
+use crate::history;
 use crate::output;
-use crate::style::Style;
+use crate::signal;
+use crate::style::{Color, Style};
 use crate::ui::Ui;
-use std::collections::{HashMap, HashSet};
-use vellum_executor::{ExecutionMode, ExecutorError, Runner};
-use vellum_migration::{discover_migrations, MigrationDiscoveryError};
+use std::collections::HashMap;
+use vellum_executor::{
+    AbandonedRun, ConnectionManager, ExecutionMode, ExecutorError, LockImpact, LockWaiting,
+    RowEstimate, RunReport, Runner,
+};
+use vellum_migration::{
+    discover_from, open_source, DiscoveredMigrations, MigrationDiscoveryError, StaleReason, StepKind,
+    Version,
+};
+
+const LARGE_ROW_COUNT: i64 = 1_000_000;
 
 pub async fn run(
     args: &MigrateArgs,
-    database_url_override: Option<&str>,
+    connection: &ConnectionArgs,
     vellum_version: &str,
 ) -> Result<(), CliError> {
-    let database_url = resolve_database_url(database_url_override)?;
+    let connection = connection_manager(connection)?;
+    if args.clone {
+        return clone_dry_run::run(args, &connection, vellum_version).await;
+    }
 
     let style = Style::detect();
     let ui = Ui::new(style);
 
-    let migrations_dir = std::path::Path::new("migrations");
-    let migrations = discover_migrations(migrations_dir).map_err(map_discovery_error)?;
-
-    let migrator = vellum_db::SqlxDatabaseMigrator::connect(&database_url)
-        .await
-        .map_err(|_| {
-            CliError::user_error("Failed to connect to database")
-                .with_reason("Database connection failed.")
-                .with_action(
-                    "Check DATABASE_URL (or pass --database-url) and verify the database is reachable.",
-                )
-        })?;
-
-    vellum_core::bootstrap::apply_baseline(&migrator)
-        .await
-        .map_err(|_| {
-            CliError::migration_failed("Failed to initialize vellum schema")
-                .with_reason("Schema initialization failed.")
-                .with_action(
-                    "Run `vellum migrate` again, and check database permissions if the problem persists.",
-                )
-        })?;
+    let discovered = discover(&args.discovery)?;
+    let migrations = discovered.versioned;
+    let repeatables = discovered.repeatable;
 
-    let pool = sqlx::PgPool::connect(&database_url)
-        .await
-        .map_err(|_| {
-            CliError::user_error("Failed to connect to database")
-                .with_reason("Database connection failed.")
-                .with_action("Check DATABASE_URL (or pass --database-url) and verify the database is reachable.")
-        })?;
+    let pool = connect_and_bootstrap(&connection).await?;
     let pool_for_queries = pool.clone();
 
     let database_name = select_database_name(&pool).await?;
 
-    let applied_versions = select_applied_versions(&pool).await?;
-    let pending_migrations: Vec<_> = migrations
-        .iter()
-        .filter(|m| !applied_versions.contains(&m.version.to_string()))
-        .collect();
-
     if args.dry_run {
         for line in ui.header("Vellum Migration (dry-run)") {
             output::line(line);
@@ -74,7 +57,13 @@ pub async fn run(
         output::line(ui.ok_line("Connected to database"));
     }
 
-    let runner = Runner::new(pool, database_url, vellum_version);
+    let runner = Runner::new(pool, vellum_version)
+        .with_connection_settings(connection.settings().clone())
+        .with_explain_analyze(args.explain_analyze)
+        .with_lock_settings(args.lock.settings())
+        .with_lock_listener(lock_listener(&ui))
+        .with_reconcile_listener(reconcile_listener(&ui))
+        .with_cancellation(signal::cancellation(&ui));
 
     let mode = if args.dry_run {
         ExecutionMode::DryRun
@@ -83,17 +72,19 @@ pub async fn run(
     };
 
     let report = runner
-        .run_with_mode(mode, &migrations)
+        .run_with_mode(mode, &migrations, &repeatables)
         .await
         .map_err(map_executor_error)?;
+    let pending_count = report.plan.to_apply().count();
 
     if args.dry_run {
         output::line(ui.ok_line("Advisory lock acquired"));
-        output::line(ui.info_line(&format!(
-            "Validating {} migrations",
-            pending_migrations.len()
-        )));
+        output::line(ui.info_line(&format!("Validating {pending_count} migrations")));
         output::line("");
+        if let Some(impact) = report.lock_impact.as_ref().filter(|i| !i.statements.is_empty()) {
+            print_lock_impact(&ui, impact);
+            output::line("");
+        }
         output::line(ui.ok_line("All migrations are valid"));
         output::line(ui.ok_line("No changes were applied"));
         output::line(ui.footer());
@@ -101,38 +92,145 @@ pub async fn run(
     }
 
     output::line(ui.ok_line("Advisory lock acquired"));
+    output::line(ui.info_line(&format!("Applying {pending_count} migrations")));
+    output::line("");
+
+    print_applied_steps(&ui, &pool_for_queries, &report).await;
+    output::line("");
+    output::line(ui.ok_line("Migration completed successfully"));
+    output::line(ui.footer());
+
+    Ok(())
+}
+
+fn print_lock_impact(ui: &Ui, impact: &LockImpact) {
+    let style = ui.style();
+
+    for stmt in &impact.statements {
+        let label = format!("{} #{} {}", file_label(&stmt.filename), stmt.ordinal, stmt.kind);
+        let suffix = format!("({}ms)", stmt.execution_time_ms);
+        output::line(ui.list_item_with_suffix(&label, "OK", Some(&suffix)));
+
+        for lock in &stmt.locks {
+            let text = format!("{} on {}", lock.level, lock.relation);
+            let text = if lock.level.blocks_reads() {
+                style.paint_stdout(Color::Red, &text)
+            } else if lock.level.blocks_writes() {
+                style.paint_stdout(Color::Yellow, &text)
+            } else {
+                text
+            };
+            output::line(format!("      {text}, held ~{}ms", lock.held_ms));
+        }
+
+        if let Some(estimate) = &stmt.rows {
+            print_row_estimate(ui, estimate);
+        }
+    }
+
+    output::line("");
     output::line(ui.info_line(&format!(
-        "Applying {} migrations",
-        pending_migrations.len()
+        "Statements took {}ms in total; locks are held until each migration commits",
+        impact.total_time_ms()
     )));
-    output::line("");
+}
 
-    let run_id = report.run_id.to_string();
-    let execution_times = match select_run_migration_times(&pool_for_queries, &run_id).await {
-        Ok(m) => m,
-        Err(_) => HashMap::new(),
+fn print_row_estimate(ui: &Ui, estimate: &RowEstimate) {
+    let style = ui.style();
+    let rows = if estimate.analyzed {
+        format!("{} rows affected", estimate.rows)
+    } else {
+        format!("~{} rows affected (estimated)", estimate.rows)
+    };
+    let rows = if estimate.rows >= LARGE_ROW_COUNT {
+        style.paint_stdout(Color::Yellow, &rows)
+    } else {
+        rows
     };
+    output::line(format!("      {rows}"));
+
+    // The root is the modifying node itself; its row count is the rows it
+    // returns, which is zero without RETURNING.
+    for node in &estimate.plan {
+        let indent = "  ".repeat(node.depth);
+        if node.depth == 0 {
+            output::line(format!("        {indent}{}", node.description));
+        } else {
+            output::line(format!("        {indent}{} (rows={})", node.description, node.rows));
+        }
+    }
+}
 
-    for m in pending_migrations {
-        let suffix_string;
-        let suffix = match execution_times.get(&m.version) {
-            Some(ms) => {
-                suffix_string = format!("({}ms)", ms);
-                Some(suffix_string.as_str())
+pub(crate) fn lock_listener(ui: &Ui) -> impl Fn(&LockWaiting) + Send + Sync + 'static {
+    let ui = Ui::new(*ui.style());
+    move |waiting| {
+        let waited = waiting.waited.as_secs();
+        let message = match &waiting.holder {
+            Some(holder) => format!("Waiting for migration lock ({waited}s), held by {holder}"),
+            None => format!("Waiting for migration lock ({waited}s)"),
+        };
+        output::line(ui.info_line(&message));
+    }
+}
+
+pub(crate) fn reconcile_listener(ui: &Ui) -> impl Fn(&[AbandonedRun]) + Send + Sync + 'static {
+    let ui = Ui::new(*ui.style());
+    move |abandoned| history::print_abandoned_runs(&ui, abandoned)
+}
+
+pub(crate) fn connection_manager(connection: &ConnectionArgs) -> Result<ConnectionManager, CliError> {
+    let database_url = resolve_database_url(connection.database_url.as_deref())?;
+    ConnectionManager::new(&database_url, connection.settings()).map_err(map_executor_error)
+}
+
+pub(crate) async fn connect(connection: &ConnectionManager) -> Result<sqlx::PgPool, CliError> {
+    connection.pool().await.map_err(map_executor_error)
+}
+
+pub(crate) async fn connect_and_bootstrap(
+    connection: &ConnectionManager,
+) -> Result<sqlx::PgPool, CliError> {
+    let pool = connect(connection).await?;
+    bootstrap(&pool, "vellum migrate").await?;
+    Ok(pool)
+}
+
+pub(crate) async fn bootstrap(pool: &sqlx::PgPool, command: &str) -> Result<(), CliError> {
+    let migrator = vellum_db::SqlxDatabaseMigrator::from_pool(pool.clone());
+    vellum_core::bootstrap::apply_baseline(&migrator)
+        .await
+        .map_err(|_| {
+            CliError::migration_failed("Failed to initialize vellum schema")
+                .with_reason("Schema initialization failed.")
+                .with_action(format!(
+                    "Run `{command}` again, and check database permissions if the problem persists."
+                ))
+        })
+}
+
+pub(crate) async fn print_applied_steps(ui: &Ui, pool: &sqlx::PgPool, report: &RunReport) {
+    let run_id = report.run_id.to_string();
+    let (execution_times, repeatable_times) =
+        select_run_migration_times(pool, &run_id)
+            .await
+            .unwrap_or_default();
+
+    for step in report.plan.to_apply() {
+        let suffix = match (step.kind, &step.version) {
+            (StepKind::Versioned, Some(version)) => {
+                execution_times.get(version).map(|ms| format!("({ms}ms)"))
             }
-            None => None,
+            _ => Some(match repeatable_times.get(&step.name) {
+                Some(ms) => format!("(repeatable, {ms}ms)"),
+                None => "(repeatable)".to_string(),
+            }),
         };
 
-        output::line(ui.list_item_with_suffix(&migration_label(m), "OK", suffix));
+        output::line(ui.list_item_with_suffix(&file_label(&step.filename), "OK", suffix.as_deref()));
     }
-    output::line("");
-    output::line(ui.ok_line("Migration completed successfully"));
-    output::line(ui.footer());
-
-    Ok(())
 }
 
-fn resolve_database_url(database_url_override: Option<&str>) -> Result<String, CliError> {
+pub(crate) fn resolve_database_url(database_url_override: Option<&str>) -> Result<String, CliError> {
     match database_url_override {
         Some(v) if !v.trim().is_empty() => Ok(v.to_string()),
         _ => match std::env::var("VELLUM_DATABASE_URL") {
@@ -147,24 +245,91 @@ fn resolve_database_url(database_url_override: Option<&str>) -> Result<String, C
     }
 }
 
-fn map_discovery_error(err: MigrationDiscoveryError) -> CliError {
-    CliError::user_error("Migration discovery failed")
-        .with_reason(err.to_string())
-        .with_action("Ensure the 'migrations' directory exists and contains valid .sql migration files.")
+pub(crate) fn discover(args: &DiscoveryArgs) -> Result<DiscoveredMigrations, CliError> {
+    let options = args.options()?;
+    let source = open_source(&args.migrations).map_err(map_discovery_error)?;
+    discover_from(source.as_ref(), &options).map_err(map_discovery_error)
 }
 
-fn map_executor_error(err: ExecutorError) -> CliError {
+pub(crate) fn map_discovery_error(err: MigrationDiscoveryError) -> CliError {
     match err {
-        ExecutorError::MigrationLockUnavailable { .. } => {
-            CliError::lock_unavailable("Another migration process is currently running")
-                .with_action("Wait for the other process to finish or investigate stuck locks.")
+        MigrationDiscoveryError::UnknownPlaceholder { ref name, .. } => {
+            let env_name = format!(
+                "VELLUM_PLACEHOLDER_{}",
+                name.to_ascii_uppercase().replace(['.', '-'], "_")
+            );
+            CliError::user_error("Migration discovery failed")
+                .with_reason(err.to_string())
+                .with_action(format!(
+                    "Define the placeholder with --placeholder {name}=<value>, {env_name}, or the [placeholders] table in vellum.toml."
+                ))
         }
-        ExecutorError::ChecksumMismatch { version, .. } => {
-            CliError::migration_failed(format!("Migration failed at version {version}"))
+        _ => CliError::user_error("Migration discovery failed")
+            .with_reason(err.to_string())
+            .with_action("Ensure the 'migrations' directory exists and contains valid .sql migration files."),
+    }
+}
+
+pub(crate) fn map_executor_error(err: ExecutorError) -> CliError {
+    match err {
+        ExecutorError::ConnectionConfigInvalid { message } => {
+            CliError::user_error("Invalid connection settings")
+                .with_reason(format!("{message}."))
+                .with_action("Check the database URL, --sslmode, --sslrootcert and --password-file.")
+        }
+        ExecutorError::ConnectionFailed { attempts, message } => {
+            let error = CliError::user_error("Failed to connect to database")
+                .with_reason(format!("{message}."))
+                .with_action(
+                    "Check DATABASE_URL (or pass --database-url) and verify the database is reachable.",
+                );
+            if attempts > 1 {
+                error.with_meaning(format!(
+                    "Gave up after {attempts} attempts within --connect-timeout."
+                ))
+            } else {
+                error
+            }
+        }
+        ExecutorError::MigrationLockUnavailable { timeout_ms, holder } => {
+            let error = CliError::lock_unavailable("Another migration process is currently running")
+                .with_meaning(if timeout_ms == 0 {
+                    "The lock was taken and --no-wait was given.".to_string()
+                } else {
+                    format!("Gave up after waiting {}s for the lock (--lock-timeout).", timeout_ms / 1000)
+                });
+            match holder {
+                Some(holder) => error
+                    .with_reason(format!("The migration lock is held by {holder}."))
+                    .with_action("Wait for that run to finish. If it is stuck, inspect it with `vellum lock status` and free it with `vellum lock release --force`."),
+                None => error.with_action("Wait for the other process to finish or investigate stuck locks with `vellum lock status`."),
+            }
+        }
+        ExecutorError::ChecksumMismatch {
+            version,
+            expected,
+            actual,
+        } => match (
+            vellum_migration::normalized_parser_version(&expected),
+            vellum_migration::normalized_parser_version(&actual),
+        ) {
+            (Some(recorded), Some(current)) if recorded != current => {
+                CliError::migration_failed(format!("Migration failed at version {version}"))
+                    .with_reason("Checksum mismatch detected." )
+                    .with_meaning(format!(
+                        "The normalized checksum was recorded with the PostgreSQL {recorded} parser and cannot be compared with the PostgreSQL {current} parser in this release."
+                    ))
+                    .with_action(format!(
+                        "If the file is unchanged, run `vellum repair {version} --reason <text> --checksum-algorithm normalized-sha256` to re-record it."
+                    ))
+            }
+            _ => CliError::migration_failed(format!("Migration failed at version {version}"))
                 .with_reason("Checksum mismatch detected." )
                 .with_meaning("The migration file was modified after being applied.")
-                .with_action("Restore the original migration file or reset the database.")
-        }
+                .with_action(format!(
+                    "Restore the original migration file, or run `vellum repair {version} --reason <text>` if the change is intentional."
+                )),
+        },
         ExecutorError::MigrationAlreadyApplied { version } => {
             CliError::migration_failed(format!("Migration failed at version {version}"))
                 .with_reason("Migration is already applied." )
@@ -204,11 +369,12 @@ fn map_executor_error(err: ExecutorError) -> CliError {
         .with_action("Investigate database state and re-run `vellum migrate`."),
         ExecutorError::DryRunValidationError {
             migration_version,
+            message,
             ..
         } => CliError::migration_failed(format!(
             "Migration failed at version {migration_version}"
         ))
-        .with_reason("Dry-run validation failed.")
+        .with_reason(format!("Dry-run validation failed: {message}"))
         .with_action("Fix the migration SQL and re-run `vellum migrate --dry-run`."),
         ExecutorError::DryRunFailed { message, .. } => CliError::migration_failed(
             "Dry-run validation failed",
@@ -234,9 +400,30 @@ fn map_executor_error(err: ExecutorError) -> CliError {
                 })
                 .with_action("Check database permissions and schema, then re-run `vellum migrate`.")
         }
-        ExecutorError::LockAcquireFailed { .. } => CliError::lock_unavailable(
+        ExecutorError::MigrationLockLost {
+            message,
+            terminated_sessions,
+        } => CliError::lock_unavailable("Migration lock was lost during the run")
+            .with_reason(format!("{message}."))
+            .with_meaning(format!(
+                "Another run could have taken the lock, so this run stopped. {terminated_sessions} in-flight session(s) were terminated and their open transaction rolled back; migrations committed before that stay applied."
+            ))
+            .with_action("Check the connection to the database (idle timeouts, poolers such as pgbouncer), inspect `vellum status`, then run the command again."),
+        ExecutorError::RunCancelled { cancelled_sessions } => CliError::cancelled("Run cancelled")
+            .with_reason(format!(
+                "Interrupted by a termination signal; {cancelled_sessions} in-flight query(ies) were cancelled."
+            ))
+            .with_meaning("The current migration was rolled back, the lock was released and the run was recorded as cancelled. Migrations committed before the signal stay applied.")
+            .with_action("Run `vellum status` to see what was applied, then run the command again."),
+        ExecutorError::RunReconciliationFailed { message } => {
+            CliError::migration_failed("Failed to reconcile abandoned runs")
+                .with_reason(message)
+                .with_action("Check that the database user can read pg_stat_activity and update vellum.vellum_runs, then try again.")
+        }
+        ExecutorError::LockAcquireFailed { message } => CliError::lock_unavailable(
             "Failed to acquire migration lock",
         )
+        .with_reason(message)
         .with_action("Wait for other migration processes to finish, then try again."),
         ExecutorError::LockReleaseFailed { .. } => CliError::migration_failed(
             "Migration failed",
@@ -252,42 +439,85 @@ fn map_executor_error(err: ExecutorError) -> CliError {
                 let _ = message;
                 "Fix the migration SQL and re-run `vellum migrate`."
             }),
+        ExecutorError::RepairReasonMissing => CliError::user_error("Repair reason is required")
+            .with_action("Pass a non-empty --reason describing why the repair is needed."),
+        ExecutorError::RepairTargetNotFound { version } => {
+            CliError::user_error(format!("Repair failed at version {version}"))
+                .with_reason("No migration with this version is recorded in the database.")
+                .with_action("Run `vellum status` and check the versions passed to `vellum repair`.")
+        }
+        ExecutorError::BaselineVersionNotFound { version } => {
+            CliError::user_error(format!("Baseline failed at version {version}"))
+                .with_reason("No migration file has this version.")
+                .with_action("Pass the version of the last migration already present in the database.")
+        }
+        ExecutorError::BaselineHistoryNotEmpty { recorded } => {
+            CliError::user_error("Baseline refused")
+                .with_reason(format!("Migration history already contains {recorded} records."))
+                .with_meaning("A baseline can only be recorded on a database Vellum has not migrated yet.")
+                .with_action("Use `vellum migrate` to apply pending migrations instead.")
+        }
+        ExecutorError::RepeatableMigrationFailed {
+            name,
+            statement_ordinal,
+            message,
+        } => {
+            CliError::migration_failed(format!("Repeatable migration '{name}' failed"))
+                .with_reason(match statement_ordinal {
+                    Some(_) => "Statement execution failed.".to_string(),
+                    None => message,
+                })
+                .with_meaning("All versioned migrations were applied; the repeatable migration was rolled back.")
+                .with_action("Fix the repeatable migration SQL and re-run `vellum migrate`.")
+        }
+        ExecutorError::PlanStale {
+            reason: reason @ StaleReason::DatabaseChanged { .. },
+        } => CliError::migration_failed("Saved plan is for another database")
+            .with_reason(reason.to_string())
+            .with_meaning("The plan was reviewed against a different database. Nothing was applied.")
+            .with_action("Check the connection settings, or run `vellum plan --out <FILE>` against this database and apply that plan."),
+        ExecutorError::PlanStale { reason } => CliError::migration_failed("Saved plan is stale")
+            .with_reason(reason.to_string())
+            .with_meaning("The database history or migration files changed since the plan was created. Nothing was applied.")
+            .with_action("Run `vellum plan --out <FILE>` again, review the new plan, and apply it."),
+        ExecutorError::IntrospectionFailed { message } => {
+            CliError::migration_failed("Schema introspection failed")
+                .with_reason(message)
+                .with_action("Check that the database role can read the system catalogs, then try again.")
+        }
+        ExecutorError::ShadowDatabaseFailed {
+            operation,
+            database,
+            message,
+        } => CliError::migration_failed(format!("Shadow database {operation} failed"))
+            .with_reason(message)
+            .with_action(match operation.as_str() {
+                "drop" => format!("Drop the leftover database manually: DROP DATABASE \"{database}\";"),
+                "clone" => "Disconnect other sessions from the database, or restore a copy of it and pass --clone-from <DATABASE>.".to_string(),
+                _ => "Check that the database role has the CREATEDB privilege and that DATABASE_URL is a postgres:// URL.".to_string(),
+            }),
     }
 }
 
-fn migration_label(m: &vellum_migration::Migration) -> String {
-    m.filename
-        .strip_suffix(".sql")
-        .unwrap_or(&m.filename)
-        .to_string()
+pub(crate) fn migration_label(m: &vellum_migration::Migration) -> String {
+    file_label(&m.filename)
 }
 
-async fn select_applied_versions(pool: &sqlx::PgPool) -> Result<HashSet<String>, CliError> {
-    let rows: Result<Vec<(String,)>, sqlx::Error> =
-        sqlx::query_as("SELECT version FROM vellum.vellum_migrations WHERE success = TRUE")
-            .fetch_all(pool)
-            .await;
-
-    let rows = match rows {
-        Ok(r) => r,
-        Err(e) => return Err(map_status_sql_error(e)),
-    };
-
-    let mut out = HashSet::with_capacity(rows.len());
-    for (v,) in rows {
-        out.insert(v);
-    }
-
-    Ok(out)
+pub(crate) fn file_label(filename: &str) -> String {
+    filename
+        .strip_suffix(".up.sql")
+        .or_else(|| filename.strip_suffix(".sql"))
+        .unwrap_or(filename)
+        .to_string()
 }
 
 fn map_status_sql_error(err: sqlx::Error) -> CliError {
     let msg = err.to_string();
-    if msg.contains("vellum.vellum_migrations") {
-        if msg.contains("does not exist") || msg.contains("undefined_table") {
-            return CliError::user_error("Vellum schema is not initialized")
-                .with_action("Run `vellum migrate` to initialize the schema.");
-        }
+    if msg.contains("vellum.vellum_migrations")
+        && (msg.contains("does not exist") || msg.contains("undefined_table"))
+    {
+        return CliError::user_error("Vellum schema is not initialized")
+            .with_action("Run `vellum migrate` to initialize the schema.");
     }
 
     CliError::migration_failed("Migration failed")
@@ -298,7 +528,7 @@ fn map_status_sql_error(err: sqlx::Error) -> CliError {
         .with_action("Check database connectivity and permissions, then re-run `vellum migrate`.")
 }
 
-async fn select_database_name(pool: &sqlx::PgPool) -> Result<String, CliError> {
+pub(crate) async fn select_database_name(pool: &sqlx::PgPool) -> Result<String, CliError> {
     let row: Result<(String,), sqlx::Error> =
         sqlx::query_as("SELECT current_database()::text").fetch_one(pool).await;
 
@@ -313,9 +543,9 @@ async fn select_database_name(pool: &sqlx::PgPool) -> Result<String, CliError> {
 async fn select_run_migration_times(
     pool: &sqlx::PgPool,
     run_id: &str,
-) -> Result<HashMap<i64, i32>, CliError> {
-    let rows: Result<Vec<(String, i32)>, sqlx::Error> = sqlx::query_as(
-        "SELECT version, execution_time_ms FROM vellum.vellum_migrations WHERE run_id = $1::uuid AND success = TRUE",
+) -> Result<(HashMap<Version, i32>, HashMap<String, i32>), CliError> {
+    let rows: Result<Vec<(String, String, i32)>, sqlx::Error> = sqlx::query_as(
+        "SELECT kind, version, execution_time_ms FROM vellum.vellum_migrations WHERE run_id = $1::uuid AND success = TRUE",
     )
     .bind(run_id)
     .fetch_all(pool)
@@ -326,12 +556,15 @@ async fn select_run_migration_times(
         Err(e) => return Err(map_status_sql_error(e)),
     };
 
-    let mut out = HashMap::with_capacity(rows.len());
-    for (version, ms) in rows {
-        if let Ok(v) = version.parse::<i64>() {
-            out.insert(v, ms);
+    let mut versioned = HashMap::with_capacity(rows.len());
+    let mut repeatable = HashMap::new();
+    for (kind, version, ms) in rows {
+        if kind == "repeatable" {
+            repeatable.insert(version, ms);
+        } else if let Ok(v) = version.parse::<Version>() {
+            versioned.insert(v, ms);
         }
     }
 
-    Ok(out)
+    Ok((versioned, repeatable))
 }
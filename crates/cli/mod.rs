@@ -1,11 +1,27 @@
 pub mod args;
+pub mod config;
 pub mod error;
 pub mod error_view;
 pub mod output;
 pub mod style;
 pub mod ui;
+pub mod apply;
+pub mod baseline;
+pub mod check;
+pub mod clone_dry_run;
+pub mod history;
+pub mod lock;
 pub mod migrate;
+pub mod new;
+pub mod plan;
+pub mod repair;
+pub mod signal;
 pub mod status;
+pub mod verify;
 
-pub use args::{Cli, Command, MigrateArgs, StatusArgs};
+pub use args::{
+    ApplyArgs, BaselineArgs, CheckArgs, Cli, Command, DiscoveryArgs, HistoryArgs, HistoryCommand,
+    LockArgs, LockCommand, LockReleaseArgs, MigrateArgs, NewArgs, OutputFormat, PlanArgs,
+    ReconcileArgs, RepairArgs, StatusArgs, VerifyArgs,
+};
 pub use error::CliError;
@@ -0,0 +1,62 @@
+use crate::args::NewArgs;
+use crate::error::CliError;
+use crate::output;
+use crate::style::Style;
+use crate::ui::Ui;
+use vellum_migration::{create_migration, MigrationScaffoldError, NewMigrationOptions};
+
+pub fn run(args: &NewArgs) -> Result<(), CliError> {
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    let migrations_dir = std::path::Path::new("migrations");
+    let options = NewMigrationOptions {
+        scheme: args.scheme,
+        filename_scheme: args.filename_scheme,
+        down: args.down,
+        header: args.header,
+    };
+
+    let created = create_migration(
+        migrations_dir,
+        &args.name,
+        options,
+        std::time::SystemTime::now(),
+    )
+    .map_err(map_scaffold_error)?;
+
+    for line in ui.header("Vellum New Migration") {
+        output::line(line);
+    }
+    output::line(ui.kv("Version", &created.version.to_string()));
+    output::line(ui.kv("Scheme", args.scheme.id()));
+    output::line("");
+    output::line(ui.ok_line(&format!("Created {}", created.path.display())));
+    if let Some(down_path) = &created.down_path {
+        output::line(ui.ok_line(&format!("Created {}", down_path.display())));
+    }
+    output::line(ui.footer());
+
+    Ok(())
+}
+
+fn map_scaffold_error(err: MigrationScaffoldError) -> CliError {
+    match err {
+        MigrationScaffoldError::InvalidName { .. } => CliError::user_error("Invalid migration name")
+            .with_reason(err.to_string())
+            .with_action("Use only ASCII letters, digits, '_' and '-', e.g. `vellum new add_orders_table`."),
+        MigrationScaffoldError::AlreadyExists { .. } => {
+            CliError::user_error("Migration file already exists")
+                .with_reason(err.to_string())
+                .with_action("Choose a different name or remove the existing file.")
+        }
+        MigrationScaffoldError::Discovery { .. } => {
+            CliError::user_error("Migration discovery failed")
+                .with_reason(err.to_string())
+                .with_action("Fix the invalid files in the 'migrations' directory, then try again.")
+        }
+        MigrationScaffoldError::Io { .. } => CliError::user_error("Failed to create migration file")
+            .with_reason(err.to_string())
+            .with_action("Check that the 'migrations' directory is writable."),
+    }
+}
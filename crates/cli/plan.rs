@@ -0,0 +1,259 @@
+use crate::args::{ConnectionArgs, OutputFormat, PlanArgs};
+use crate::error::CliError;
+use crate::migrate::{
+    connect, connection_manager, discover, file_label, map_executor_error,
+};
+use crate::output;
+use crate::style::{Color, Style};
+use crate::ui::Ui;
+use serde::Serialize;
+use vellum_executor::{audit, planner, statement};
+use std::path::Path;
+use vellum_migration::{
+    AppliedHistory, DatabaseIdentity, MigrationPlan, PlanStep, SavedPlan, StepAction, StepKind,
+};
+use vellum_sql_engine::{analyze_statement, StatementAnalysis};
+
+#[derive(Debug, Serialize)]
+struct PlanReport<'a> {
+    database: String,
+    blocked: bool,
+    steps: Vec<StepReport<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct StepReport<'a> {
+    #[serde(flatten)]
+    step: &'a PlanStep,
+    detail: String,
+    statements: Vec<StatementReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct StatementReport {
+    ordinal: i32,
+    #[serde(flatten)]
+    analysis: StatementAnalysis,
+    sql: String,
+}
+
+pub async fn run(
+    args: &PlanArgs,
+    connection: &ConnectionArgs,
+    vellum_version: &str,
+) -> Result<(), CliError> {
+    let connection = connection_manager(connection)?;
+
+    let discovered = discover(&args.discovery)?;
+
+    let pool = connect(&connection).await?;
+
+    let database = select_database_identity(&pool).await?;
+    let history = select_history(&pool).await?;
+    let plan = MigrationPlan::build(&history, &discovered.versioned, &discovered.repeatable);
+
+    let (versioned, repeatable) =
+        planner::resolve(&plan, &discovered.versioned, &discovered.repeatable);
+    let sources = versioned
+        .iter()
+        .map(|m| (m.filename.as_str(), m.sql.as_str()))
+        .chain(repeatable.iter().map(|m| (m.filename.as_str(), m.sql.as_str())));
+
+    let mut statements_by_file = std::collections::HashMap::new();
+    for (filename, sql) in sources {
+        statements_by_file.insert(filename, analyze(filename, sql)?);
+    }
+
+    let report = PlanReport {
+        database: database.name.clone(),
+        blocked: plan.is_blocked(),
+        steps: plan
+            .steps
+            .iter()
+            .filter(|step| step.action != StepAction::Skip)
+            .map(|step| StepReport {
+                step,
+                detail: step.reason.to_string(),
+                statements: statements_by_file
+                    .remove(step.filename.as_str())
+                    .unwrap_or_default(),
+            })
+            .collect(),
+    };
+
+    match args.format {
+        OutputFormat::Json => print_json(&report),
+        OutputFormat::Human => print_human(&report, &plan),
+    }
+
+    if let Some(out) = &args.out {
+        if plan.is_blocked() {
+            return Err(CliError::user_error("Plan was not saved")
+                .with_reason("The plan is blocked by a modified migration.")
+                .with_action("Resolve the blocked migrations, then run `vellum plan --out` again."));
+        }
+
+        let saved = SavedPlan::new(vellum_version, &database, &history, &plan);
+        write_saved_plan(out, &saved)?;
+
+        if args.format == OutputFormat::Human {
+            let ui = Ui::new(Style::detect());
+            output::line(ui.ok_line(&format!("Plan saved to {}", out.display())));
+            output::line(ui.info_line(&format!(
+                "Run `vellum apply {}` to apply exactly this plan",
+                out.display()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn write_saved_plan(path: &Path, saved: &SavedPlan) -> Result<(), CliError> {
+    let write_error = |message: String| {
+        CliError::user_error(format!("Failed to write plan file {}", path.display()))
+            .with_reason(message)
+            .with_action("Check that the output path is writable.")
+    };
+
+    let mut json = serde_json::to_string_pretty(saved).map_err(|e| write_error(e.to_string()))?;
+    json.push('\n');
+    std::fs::write(path, json).map_err(|e| write_error(e.to_string()))
+}
+
+async fn select_database_identity(pool: &sqlx::PgPool) -> Result<DatabaseIdentity, CliError> {
+    audit::select_database_identity(pool).await.map_err(|_| {
+        CliError::migration_failed("Plan query failed")
+            .with_reason("Database query failed.")
+            .with_action("Verify database connectivity and permissions, then try again.")
+    })
+}
+
+async fn select_history(pool: &sqlx::PgPool) -> Result<AppliedHistory, CliError> {
+    let initialized: Result<bool, sqlx::Error> =
+        sqlx::query_scalar("SELECT to_regclass('vellum.vellum_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await;
+
+    match initialized {
+        Ok(true) => audit::select_applied_history(pool)
+            .await
+            .map_err(map_executor_error),
+        Ok(false) => Ok(AppliedHistory::default()),
+        Err(_) => Err(CliError::migration_failed("Plan query failed")
+            .with_reason("Database query failed.")
+            .with_action("Verify database connectivity and read access to vellum.vellum_migrations.")),
+    }
+}
+
+fn analyze(filename: &str, sql: &str) -> Result<Vec<StatementReport>, CliError> {
+    let parse_error = |message: String| {
+        CliError::user_error(format!("Failed to parse {filename}"))
+            .with_reason(message)
+            .with_action("Fix the migration SQL and re-run `vellum plan`.")
+    };
+
+    let statements = statement::parse_statements(sql, Some(filename)).map_err(parse_error)?;
+
+    let mut out = Vec::with_capacity(statements.len());
+    for stmt in statements {
+        let analysis =
+            analyze_statement(stmt.sql(), Some(filename)).map_err(|e| parse_error(e.to_string()))?;
+        out.push(StatementReport {
+            ordinal: stmt.ordinal(),
+            analysis,
+            sql: stmt.sql().trim().to_string(),
+        });
+    }
+
+    Ok(out)
+}
+
+fn print_json(report: &PlanReport<'_>) {
+    match serde_json::to_string_pretty(report) {
+        Ok(json) => output::line(json),
+        Err(e) => output::error(format!("failed to serialize plan: {e}")),
+    }
+}
+
+fn print_human(report: &PlanReport<'_>, plan: &MigrationPlan) {
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    for line in ui.header("Vellum Plan") {
+        output::line(line);
+    }
+    output::line(ui.kv("Database", &report.database));
+    output::line("");
+    output::line(ui.kv(
+        "Pending migrations",
+        &plan.count(StepKind::Versioned, StepAction::Apply).to_string(),
+    ));
+    output::line(ui.kv(
+        "Pending repeatables",
+        &plan.count(StepKind::Repeatable, StepAction::Apply).to_string(),
+    ));
+    output::line(ui.kv(
+        "Already applied",
+        &(plan.count(StepKind::Versioned, StepAction::Skip)
+            + plan.count(StepKind::Repeatable, StepAction::Skip))
+        .to_string(),
+    ));
+
+    for step in &report.steps {
+        output::line("");
+        let label = file_label(&step.step.filename);
+        if step.step.action == StepAction::Blocked {
+            output::line(format!(
+                "{} {label} {}",
+                style.paint_stdout(Color::Red, "✖"),
+                style.paint_stdout(Color::Red, &format!("blocked: {}", step.detail))
+            ));
+            continue;
+        }
+
+        output::line(ui.info_line(&format!("{label} ({})", step.detail)));
+        for stmt in &step.statements {
+            output::line(format!("    {}. {}", stmt.ordinal, describe(&stmt.analysis, &style)));
+            output::line(format!("       {}", vellum_sql_engine::error::sql_snippet(&stmt.sql)));
+        }
+    }
+
+    output::line(ui.footer());
+
+    if report.blocked {
+        output::line(ui.info_line(
+            "Restore the modified files, or run `vellum repair <VERSION> --reason <text>` if the change is intentional",
+        ));
+    } else if plan.to_apply().next().is_none() {
+        output::line(ui.ok_line("Database is up to date"));
+    } else {
+        output::line(ui.info_line("Run `vellum migrate` to apply this plan"));
+    }
+}
+
+fn describe(analysis: &StatementAnalysis, style: &Style) -> String {
+    let mut parts = vec![analysis.kind.clone()];
+
+    if analysis.transaction_control {
+        parts.push(style.paint_stdout(Color::Red, "transaction control"));
+    } else if !analysis.transactional {
+        parts.push(style.paint_stdout(Color::Yellow, "non-transactional"));
+    }
+
+    if analysis.locks.is_empty() {
+        parts.push("no table locks".to_string());
+    }
+    for lock in &analysis.locks {
+        let text = format!("{} on {}", lock.level, lock.relation);
+        parts.push(if lock.level.blocks_reads() {
+            style.paint_stdout(Color::Red, &text)
+        } else if lock.level.blocks_writes() {
+            style.paint_stdout(Color::Yellow, &text)
+        } else {
+            text
+        });
+    }
+
+    parts.join(" · ")
+}
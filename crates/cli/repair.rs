@@ -0,0 +1,76 @@
+use crate::args::{RepairArgs, ConnectionArgs};
+use crate::error::CliError;
+use crate::migrate::{
+    bootstrap, connect, connection_manager, discover, lock_listener, map_executor_error,
+    migration_label, reconcile_listener, select_database_name,
+};
+use crate::output;
+use crate::signal;
+use crate::style::Style;
+use crate::ui::Ui;
+use vellum_executor::{RepairAction, Runner};
+
+pub async fn run(
+    args: &RepairArgs,
+    connection: &ConnectionArgs,
+    vellum_version: &str,
+) -> Result<(), CliError> {
+    let connection = connection_manager(connection)?;
+
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    let migrations = discover(&args.discovery)?.versioned;
+
+    let pool = connect(&connection).await?;
+    bootstrap(&pool, "vellum repair").await?;
+
+    let database_name = select_database_name(&pool).await?;
+
+    for line in ui.header("Vellum Repair") {
+        output::line(line);
+    }
+    output::line(ui.kv("Database", &database_name));
+    output::line(ui.kv("Reason", args.reason.trim()));
+    output::line("");
+    output::line(ui.ok_line("Connected to database"));
+
+    let runner = Runner::new(pool, vellum_version)
+        .with_connection_settings(connection.settings().clone())
+        .with_lock_settings(args.lock.settings())
+        .with_lock_listener(lock_listener(&ui))
+        .with_reconcile_listener(reconcile_listener(&ui))
+        .with_cancellation(signal::cancellation(&ui));
+    let report = runner
+        .repair(&migrations, &args.versions, &args.reason)
+        .await
+        .map_err(map_executor_error)?;
+
+    output::line(ui.ok_line("Advisory lock acquired"));
+    output::line(ui.info_line(&format!(
+        "Repairing {} migrations",
+        report.outcomes.len()
+    )));
+    output::line("");
+
+    for outcome in &report.outcomes {
+        let label = match migrations.iter().find(|m| m.version == outcome.version) {
+            Some(m) => migration_label(m),
+            None => outcome.version.to_string(),
+        };
+
+        let suffix = match outcome.action {
+            RepairAction::ChecksumUpdated => "(checksum updated)",
+            RepairAction::FailedRemoved => "(failed record removed)",
+            RepairAction::OrphanRemoved => "(orphaned record removed)",
+            RepairAction::Unchanged => "(already up to date)",
+        };
+
+        output::line(ui.list_item_with_suffix(&label, "OK", Some(suffix)));
+    }
+    output::line("");
+    output::line(ui.ok_line("Repair completed successfully"));
+    output::line(ui.footer());
+
+    Ok(())
+}
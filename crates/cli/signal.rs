@@ -0,0 +1,68 @@
+use crate::error::ExitCode;
+use crate::output;
+use crate::ui::Ui;
+use tokio::sync::watch;
+
+// The first SIGINT or SIGTERM asks the running command to stop cleanly; a
+// second one exits straight away.
+pub fn cancellation(ui: &Ui) -> watch::Receiver<bool> {
+    let (cancel, cancelled) = watch::channel(false);
+    let ui = Ui::new(*ui.style());
+
+    let Some(mut signals) = Signals::install() else {
+        return cancelled;
+    };
+
+    tokio::spawn(async move {
+        signals.recv().await;
+        output::line("");
+        output::line(ui.info_line(
+            "Cancelling: rolling back the current migration and releasing the lock (signal again to exit immediately)",
+        ));
+        let _ = cancel.send(true);
+
+        signals.recv().await;
+        std::process::exit(ExitCode::Cancelled.as_i32());
+    });
+
+    cancelled
+}
+
+#[cfg(unix)]
+struct Signals {
+    interrupt: tokio::signal::unix::Signal,
+    terminate: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn install() -> Option<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        Some(Self {
+            interrupt: signal(SignalKind::interrupt()).ok()?,
+            terminate: signal(SignalKind::terminate()).ok()?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.interrupt.recv() => {}
+            _ = self.terminate.recv() => {}
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    fn install() -> Option<Self> {
+        Some(Self)
+    }
+
+    async fn recv(&mut self) {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
@@ -1,44 +1,61 @@
-use crate::args::StatusArgs;
+use crate::args::{ConnectionArgs, StatusArgs};
 use crate::error::CliError;
 use crate::output;
 use crate::style::Style;
 use crate::ui::Ui;
-use std::collections::HashSet;
-use vellum_migration::{discover_migrations, MigrationDiscoveryError};
+use vellum_executor::audit;
+use vellum_migration::{MigrationPlan, StepAction, StepKind, StepReason};
 
-pub async fn run(_args: &StatusArgs, database_url_override: Option<&str>) -> Result<(), CliError> {
-    let database_url = resolve_database_url(database_url_override)?;
+pub async fn run(args: &StatusArgs, connection: &ConnectionArgs) -> Result<(), CliError> {
+    let connection = crate::migrate::connection_manager(connection)?;
 
     let style = Style::detect();
     let ui = Ui::new(style);
 
-    let pool = sqlx::PgPool::connect(&database_url)
-        .await
-        .map_err(|_| {
-            CliError::user_error("Failed to connect to database")
-                .with_reason("Database connection failed.")
-                .with_action(
-                    "Check DATABASE_URL (or pass --database-url) and verify the database is reachable.",
-                )
-        })?;
-
-    let migrations_dir = std::path::Path::new("migrations");
-    let local = discover_migrations(migrations_dir).map_err(map_discovery_error)?;
-
-    let applied_versions = select_applied_versions(&pool).await?;
-
-    let mut pending = 0usize;
-    for m in &local {
-        if !applied_versions.contains(&m.version.to_string()) {
-            pending += 1;
-        }
-    }
+    let pool = crate::migrate::connect(&connection).await?;
 
-    let applied_count = applied_versions.len();
-    let last_applied = select_last_applied(&pool).await?;
-    let last_run_status = select_last_run_status(&pool).await?;
+    let discovered = crate::migrate::discover(&args.discovery)?;
+    let local = discovered.versioned;
 
-    let database_name = select_database_name(&pool).await?;
+    // Everything reported is read from one REPEATABLE READ snapshot, so a
+    // concurrent migrate cannot make the counts disagree with each other.
+    let mut tx = pool.begin().await.map_err(map_status_sql_error)?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await
+        .map_err(map_status_sql_error)?;
+
+    let database_name = select_database_name(&mut tx).await?;
+    let history = audit::select_applied_history_with(&mut tx)
+        .await
+        .map_err(map_history_error)?;
+    let last_run_status = select_last_run_status(&mut tx).await?;
+    let _ = tx.commit().await;
+
+    let plan = MigrationPlan::build(&history, &local, &discovered.repeatable);
+    let pending = plan.count(StepKind::Versioned, StepAction::Apply);
+    let pending_repeatables = plan.count(StepKind::Repeatable, StepAction::Apply);
+    let modified: Vec<String> = plan
+        .steps
+        .iter()
+        .filter(|s| matches!(s.reason, StepReason::ChecksumMismatch { .. }))
+        .map(|s| crate::migrate::file_label(&s.filename))
+        .collect();
+
+    // Applied means recorded in the history; a modified file still counts.
+    let applied: Vec<_> = plan
+        .steps
+        .iter()
+        .filter(|s| s.kind == StepKind::Versioned)
+        .filter(|s| {
+            matches!(
+                s.reason,
+                StepReason::AlreadyApplied | StepReason::ChecksumMismatch { .. }
+            )
+        })
+        .collect();
+    let applied_count = applied.len();
+    let last_applied = applied.iter().max_by(|a, b| a.version.cmp(&b.version));
 
     for line in ui.header("Vellum Status") {
         output::line(line);
@@ -48,9 +65,15 @@ pub async fn run(_args: &StatusArgs, database_url_override: Option<&str>) -> Res
 
     output::line(ui.kv("Applied migrations", &applied_count.to_string()));
     output::line(ui.kv("Pending migrations", &pending.to_string()));
+    if !discovered.repeatable.is_empty() {
+        output::line(ui.kv("Pending repeatables", &pending_repeatables.to_string()));
+    }
+    if !modified.is_empty() {
+        output::line(ui.kv("Modified migrations", &modified.join(", ")));
+    }
 
-    let last_migration = match &last_applied {
-        Some((version, name)) => last_migration_label(&local, version, name),
+    let last_migration = match last_applied {
+        Some(step) => crate::migrate::file_label(&step.filename),
         None => "none".to_string(),
     };
     output::line(ui.kv("Last migration", &last_migration));
@@ -59,16 +82,20 @@ pub async fn run(_args: &StatusArgs, database_url_override: Option<&str>) -> Res
     output::line(ui.kv("Last run status", &last_status));
     output::line(ui.footer());
 
-    if pending > 0 {
+    if !modified.is_empty() {
+        output::line(ui.info_line(
+            "Restore the modified files, or run `vellum repair <VERSION> --reason <text>` if the change is intentional",
+        ));
+    } else if pending > 0 {
         output::line(ui.info_line("Run `vellum migrate` to apply pending migrations"));
     }
 
     Ok(())
 }
 
-async fn select_database_name(pool: &sqlx::PgPool) -> Result<String, CliError> {
+async fn select_database_name(conn: &mut sqlx::PgConnection) -> Result<String, CliError> {
     let row: Result<(String,), sqlx::Error> =
-        sqlx::query_as("SELECT current_database()::text").fetch_one(pool).await;
+        sqlx::query_as("SELECT current_database()::text").fetch_one(conn).await;
 
     match row {
         Ok(r) => Ok(r.0),
@@ -78,65 +105,11 @@ async fn select_database_name(pool: &sqlx::PgPool) -> Result<String, CliError> {
     }
 }
 
-fn resolve_database_url(database_url_override: Option<&str>) -> Result<String, CliError> {
-    match database_url_override {
-        Some(v) if !v.trim().is_empty() => Ok(v.to_string()),
-        _ => match std::env::var("VELLUM_DATABASE_URL") {
-            Ok(v) if !v.trim().is_empty() => Ok(v),
-            _ => match std::env::var("DATABASE_URL") {
-                Ok(v) if !v.trim().is_empty() => Ok(v),
-                _ => Err(CliError::user_error("Database URL is required").with_action(
-                    "Set VELLUM_DATABASE_URL (or DATABASE_URL) or pass --database-url to the CLI.",
-                )),
-            },
-        },
-    }
-}
-
-fn map_discovery_error(err: MigrationDiscoveryError) -> CliError {
-    CliError::user_error("Migration discovery failed")
-        .with_reason(err.to_string())
-        .with_action("Ensure the 'migrations' directory exists and contains valid .sql migration files.")
-}
-
-async fn select_applied_versions(pool: &sqlx::PgPool) -> Result<HashSet<String>, CliError> {
-    let rows: Result<Vec<(String,)>, sqlx::Error> = sqlx::query_as(
-        "SELECT version FROM vellum.vellum_migrations WHERE success = TRUE",
-    )
-    .fetch_all(pool)
-    .await;
-
-    let rows = match rows {
-        Ok(r) => r,
-        Err(e) => return Err(map_status_sql_error(e)),
-    };
-
-    let mut out = HashSet::with_capacity(rows.len());
-    for (v,) in rows {
-        out.insert(v);
-    }
-
-    Ok(out)
-}
-
-async fn select_last_applied(pool: &sqlx::PgPool) -> Result<Option<(String, String)>, CliError> {
-    let row: Result<Option<(String, String)>, sqlx::Error> = sqlx::query_as(
-        "SELECT version, name FROM vellum.vellum_migrations WHERE success = TRUE ORDER BY version::bigint DESC LIMIT 1",
-    )
-    .fetch_optional(pool)
-    .await;
-
-    match row {
-        Ok(r) => Ok(r),
-        Err(e) => Err(map_status_sql_error(e)),
-    }
-}
-
-async fn select_last_run_status(pool: &sqlx::PgPool) -> Result<Option<String>, CliError> {
+async fn select_last_run_status(conn: &mut sqlx::PgConnection) -> Result<Option<String>, CliError> {
     let row: Result<Option<(String,)>, sqlx::Error> = sqlx::query_as(
         "SELECT status FROM vellum.vellum_runs ORDER BY started_at DESC LIMIT 1",
     )
-    .fetch_optional(pool)
+    .fetch_optional(conn)
     .await;
 
     match row {
@@ -145,13 +118,24 @@ async fn select_last_run_status(pool: &sqlx::PgPool) -> Result<Option<String>, C
     }
 }
 
+// The history reader reports SQL failures as a message; an uninitialized
+// schema still gets the same advice as the other status queries.
+fn map_history_error(err: vellum_executor::ExecutorError) -> CliError {
+    let msg = err.to_string();
+    if msg.contains("vellum.vellum_migrations") && msg.contains("does not exist") {
+        return CliError::user_error("Vellum schema is not initialized")
+            .with_action("Run `vellum migrate` to initialize the schema.");
+    }
+    crate::migrate::map_executor_error(err)
+}
+
 fn map_status_sql_error(err: sqlx::Error) -> CliError {
     let msg = err.to_string();
-    if msg.contains("vellum.vellum_migrations") || msg.contains("vellum.vellum_runs") {
-        if msg.contains("does not exist") || msg.contains("undefined_table") {
-            return CliError::user_error("Vellum schema is not initialized")
-                .with_action("Run `vellum migrate` to initialize the schema.");
-        }
+    if (msg.contains("vellum.vellum_migrations") || msg.contains("vellum.vellum_runs"))
+        && (msg.contains("does not exist") || msg.contains("undefined_table"))
+    {
+        return CliError::user_error("Vellum schema is not initialized")
+            .with_action("Run `vellum migrate` to initialize the schema.");
     }
 
     CliError::migration_failed("Status query failed")
@@ -161,27 +145,3 @@ fn map_status_sql_error(err: sqlx::Error) -> CliError {
         })
         .with_action("Verify database connectivity and permissions, then try again.")
 }
-
-fn last_migration_label(
-    local: &[vellum_migration::Migration],
-    version: &str,
-    name: &str,
-) -> String {
-    if let Ok(v) = version.parse::<i64>() {
-        for m in local {
-            if m.version == v {
-                return m
-                    .filename
-                    .strip_suffix(".sql")
-                    .unwrap_or(&m.filename)
-                    .to_string();
-            }
-        }
-    }
-
-    if name.trim().is_empty() {
-        version.to_string()
-    } else {
-        format!("{version}_{name}")
-    }
-}
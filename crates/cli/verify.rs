@@ -0,0 +1,175 @@
+use crate::args::{ConnectionArgs, VerifyArgs};
+use crate::error::CliError;
+use crate::migrate::{
+    connect_and_bootstrap, connection_manager, discover, map_executor_error, select_database_name,
+};
+use crate::output;
+use crate::style::{Color, Style};
+use crate::ui::Ui;
+use std::collections::BTreeMap;
+use vellum_executor::{introspect, ConnectionManager, ExecutorError, Runner, ShadowDatabase};
+use vellum_migration::{Migration, RepeatableMigration, StepKind, StepReason};
+use vellum_schema::{diff_schemas, DifferenceKind, Schema, SchemaDifference};
+
+pub async fn run(
+    args: &VerifyArgs,
+    connection: &ConnectionArgs,
+    vellum_version: &str,
+) -> Result<(), CliError> {
+    if !args.shadow {
+        return Err(CliError::user_error("No verification selected")
+            .with_action("Run `vellum verify --shadow` to replay the migration history into a scratch database."));
+    }
+
+    let connection = connection_manager(connection)?;
+
+    let style = Style::detect();
+    let ui = Ui::new(style);
+
+    let discovered = discover(&args.discovery)?;
+
+    let pool = connect_and_bootstrap(&connection).await?;
+    let database_name = select_database_name(&pool).await?;
+
+    for line in ui.header("Vellum Verify") {
+        output::line(line);
+    }
+    output::line(ui.kv("Database", &database_name));
+    output::line(ui.kv("Mode", "shadow"));
+    output::line("");
+    output::line(ui.ok_line("Connected to database"));
+
+    // Only the history the target has actually applied is replayed, so
+    // pending migrations do not show up as drift.
+    let plan = Runner::new(pool.clone(), vellum_version)
+        .plan(&discovered.versioned, &discovered.repeatable)
+        .await
+        .map_err(map_executor_error)?;
+
+    let applied = |kind: StepKind, reason: &StepReason| match kind {
+        StepKind::Versioned => matches!(
+            reason,
+            StepReason::AlreadyApplied | StepReason::ChecksumMismatch { .. }
+        ),
+        StepKind::Repeatable => matches!(
+            reason,
+            StepReason::RepeatableUnchanged | StepReason::RepeatableChanged
+        ),
+    };
+    let replayed: Vec<&str> = plan
+        .steps
+        .iter()
+        .filter(|step| applied(step.kind, &step.reason))
+        .map(|step| step.filename.as_str())
+        .collect();
+
+    let migrations: Vec<Migration> = discovered
+        .versioned
+        .iter()
+        .filter(|m| replayed.contains(&m.filename.as_str()))
+        .cloned()
+        .collect();
+    let repeatables: Vec<RepeatableMigration> = discovered
+        .repeatable
+        .iter()
+        .filter(|m| replayed.contains(&m.filename.as_str()))
+        .cloned()
+        .collect();
+
+    let skipped = plan.steps.len() - replayed.len();
+    if skipped > 0 {
+        output::line(ui.info_line(&format!(
+            "Skipping {skipped} migrations not yet applied to {database_name}"
+        )));
+    }
+
+    let actual = introspect(&pool).await.map_err(map_executor_error)?;
+    output::line(ui.ok_line("Introspected database schema"));
+
+    let shadow = ShadowDatabase::create(&pool)
+        .await
+        .map_err(map_executor_error)?;
+    output::line(ui.ok_line(&format!("Created shadow database {}", shadow.name())));
+    output::line(ui.info_line(&format!(
+        "Replaying {} migrations",
+        migrations.len() + repeatables.len()
+    )));
+
+    let replay = replay(&connection, &shadow, vellum_version, &migrations, &repeatables).await;
+    let dropped = shadow.drop(&pool).await;
+    let expected = replay?;
+    dropped.map_err(map_executor_error)?;
+    output::line(ui.ok_line("Dropped shadow database"));
+    output::line("");
+
+    let differences = diff_schemas(&expected, &actual);
+    if differences.is_empty() {
+        output::line(ui.ok_line("Schema matches a fresh replay of the migration history"));
+        output::line(ui.footer());
+        return Ok(());
+    }
+
+    for difference in &differences {
+        print_difference(ui.style(), difference);
+    }
+    output::line("");
+    output::line(ui.footer());
+
+    Err(CliError::migration_failed("Schema drift detected")
+        .with_reason(format!(
+            "{} difference(s) between {database_name} and a fresh replay of its migration history.",
+            differences.len()
+        ))
+        .with_meaning("The database was changed outside of migrations, or applied migrations were edited since they ran.")
+        .with_action("Capture the differences in a new migration, or restore the edited migration files."))
+}
+
+async fn replay(
+    connection: &ConnectionManager,
+    shadow: &ShadowDatabase,
+    vellum_version: &str,
+    migrations: &[Migration],
+    repeatables: &[RepeatableMigration],
+) -> Result<BTreeMap<String, Schema>, CliError> {
+    let pool = connect_and_bootstrap(&connection.for_database(shadow.name())).await?;
+
+    let result = Runner::new(pool.clone(), vellum_version)
+        .with_connection_settings(connection.settings().clone())
+        .run(migrations, repeatables)
+        .await;
+    if let Err(err) = result {
+        pool.close().await;
+        return Err(map_replay_error(err));
+    }
+
+    let schema = introspect(&pool).await.map_err(map_executor_error);
+    pool.close().await;
+    schema
+}
+
+fn map_replay_error(err: ExecutorError) -> CliError {
+    CliError::migration_failed("Migration history does not replay on a fresh database")
+        .with_reason(err.to_string())
+        .with_meaning("Applied migrations can no longer rebuild the schema from scratch.")
+        .with_action("Fix the failing migration so the full history replays, then run `vellum verify --shadow` again.")
+}
+
+fn print_difference(style: &Style, difference: &SchemaDifference) {
+    let (label, color) = match difference.kind {
+        DifferenceKind::Missing => ("missing   ", Color::Red),
+        DifferenceKind::Unexpected => ("unexpected", Color::Yellow),
+        DifferenceKind::Changed => ("changed   ", Color::Yellow),
+    };
+
+    output::line(format!(
+        "  {} {}",
+        style.paint_stdout(color, label),
+        difference.object
+    ));
+    if let Some(expected) = &difference.expected {
+        output::line(format!("      migrations: {expected}"));
+    }
+    if let Some(actual) = &difference.actual {
+        output::line(format!("      database:   {actual}"));
+    }
+}
@@ -24,8 +24,7 @@ pub mod error {
 pub use error::Error;
 
 pub mod migration {
-    #[derive(Clone, Debug)]
-    pub struct MigrationPlan;
+    pub use vellum_migration::plan::MigrationPlan;
 
     #[derive(Clone, Debug)]
     pub struct MigrationReport;
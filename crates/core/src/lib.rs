@@ -5,54 +5,6 @@ pub use vellum_contracts::schema;
 pub use vellum_contracts::sql;
 pub use vellum_contracts::Error;
 
-pub mod orchestrator {
-    use vellum_contracts::db::DbConnection;
-    use vellum_contracts::migration::{MigrationOrchestrator, MigrationPlan, MigrationReport};
-    use vellum_contracts::schema::SchemaIntrospector;
-    use vellum_contracts::sql::SqlEngine;
-    use vellum_contracts::Error;
-
-    #[allow(dead_code)]
-    pub struct Orchestrator<DB, SCH, SQL>
-    where
-        DB: DbConnection<Error = Error>,
-        SCH: SchemaIntrospector<Error = Error>,
-        SQL: SqlEngine<Error = Error>,
-    {
-        db: DB,
-        schema: SCH,
-        sql: SQL,
-    }
-
-    impl<DB, SCH, SQL> Orchestrator<DB, SCH, SQL>
-    where
-        DB: DbConnection<Error = Error>,
-        SCH: SchemaIntrospector<Error = Error>,
-        SQL: SqlEngine<Error = Error>,
-    {
-        pub fn new(db: DB, schema: SCH, sql: SQL) -> Self {
-            Self { db, schema, sql }
-        }
-    }
-
-    impl<DB, SCH, SQL> MigrationOrchestrator for Orchestrator<DB, SCH, SQL>
-    where
-        DB: DbConnection<Error = Error>,
-        SCH: SchemaIntrospector<Error = Error>,
-        SQL: SqlEngine<Error = Error>,
-    {
-        type Error = Error;
-
-        fn run(&self, _plan: MigrationPlan) -> Result<MigrationReport, Self::Error> {
-            Ok(MigrationReport)
-        }
-    }
-}
-
-pub use orchestrator::Orchestrator;
-
-pub use vellum_contracts::migration::{MigrationOrchestrator, MigrationPlan, MigrationReport};
-
 pub mod bootstrap {
     use core::future::Future;
     use core::pin::Pin;
@@ -69,53 +21,24 @@ pub mod bootstrap {
     }
 }
 
-pub mod commands {
-    use vellum_contracts::migration::{MigrationOrchestrator, MigrationPlan, MigrationReport};
-
-    pub fn migrate<O>(orchestrator: &O) -> Result<MigrationReport, O::Error>
-    where
-        O: MigrationOrchestrator,
-    {
-        orchestrator.run(MigrationPlan)
-    }
-
-    pub fn dry_run<O>(orchestrator: &O) -> Result<MigrationReport, O::Error>
-    where
-        O: MigrationOrchestrator,
-    {
-        orchestrator.run(MigrationPlan)
-    }
-
-    pub fn status<O>(orchestrator: &O) -> Result<MigrationReport, O::Error>
-    where
-        O: MigrationOrchestrator,
-    {
-        orchestrator.run(MigrationPlan)
-    }
-}
-
 #[cfg(feature = "runtime")]
 pub mod runtime {
-    use crate::orchestrator::Orchestrator;
     use core::future::Future;
     use core::pin::Pin;
     use crate::Error;
 
-    pub fn build_orchestrator(
-    ) -> Orchestrator<vellum_db::DefaultDbConnection, vellum_schema_introspector::DefaultSchemaIntrospector, vellum_sql_engine::DefaultSqlEngine>
-    {
-        Orchestrator::new(
-            vellum_db::DefaultDbConnection::new(),
-            vellum_schema_introspector::DefaultSchemaIntrospector::new(),
-            vellum_sql_engine::DefaultSqlEngine::new(),
-        )
-    }
-
     pub fn build_migrator<'a>(
         database_url: &'a str,
     ) -> Pin<Box<dyn Future<Output = Result<vellum_db::SqlxDatabaseMigrator, Error>> + Send + 'a>>
     {
-        Box::pin(async move { vellum_db::SqlxDatabaseMigrator::connect(database_url).await })
+        Box::pin(async move {
+            let pool = vellum_executor::ConnectionManager::new(database_url, Default::default())
+                .map_err(|e| Error::message(e.to_string()))?
+                .pool()
+                .await
+                .map_err(|e| Error::message(e.to_string()))?;
+            Ok(vellum_db::SqlxDatabaseMigrator::from_pool(pool))
+        })
     }
 
     pub fn run_migrations<'a>(
@@ -124,15 +47,41 @@ pub mod runtime {
         vellum_version: &'a str,
     ) -> Pin<Box<dyn Future<Output = Result<vellum_executor::RunReport, Error>> + Send + 'a>> {
         Box::pin(async move {
-            let migrations = vellum_migration::discover_migrations(migrations_dir)
-                .map_err(|e| Error::message(e.to_string()))?;
-
-            let pool = sqlx::PgPool::connect(database_url)
+            let discovered = vellum_migration::discover_all_with(
+                migrations_dir,
+                &vellum_migration::DiscoveryOptions::default(),
+            )
+            .map_err(|e| Error::message(e.to_string()))?;
+
+            let pool = vellum_executor::ConnectionManager::new(database_url, Default::default())
+                .map_err(|e| Error::message(e.to_string()))?
+                .pool()
                 .await
                 .map_err(|e| Error::message(e.to_string()))?;
 
-            let runner = vellum_executor::Runner::new(pool, database_url, vellum_version);
-            runner.run(&migrations).await.map_err(|e| Error::message(e.to_string()))
+            let runner = vellum_executor::Runner::new(pool, vellum_version);
+            runner
+                .run(&discovered.versioned, &discovered.repeatable)
+                .await
+                .map_err(|e| Error::message(e.to_string()))
         })
     }
 }
+
+#[cfg(feature = "runtime")]
+pub mod vellum;
+
+#[cfg(feature = "runtime")]
+pub use vellum::{
+    AppliedMigration, AppliedRepeatable, ConnectionSettings, DryRunReport, LockScope, LockWait,
+    MigrateReport, PendingMigration, StatusReport, Vellum, VellumBuilder, VellumError,
+};
+
+#[cfg(feature = "macros")]
+pub use vellum_macros::embed_migrations;
+
+// Paths used by the `embed_migrations!` expansion, so callers do not need
+// their own dependency on `vellum-migration`.
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub use vellum_migration as __private;
@@ -0,0 +1,558 @@
+use core::fmt;
+use core::time::Duration;
+use uuid::Uuid;
+use vellum_contracts::migrations::DatabaseMigrator;
+use vellum_executor::{
+    ConnectionManager, ExecutionMode, ExecutorError, LockImpact, LockSettings, Runner,
+};
+pub use vellum_executor::{ConnectionSettings, LockScope, LockWait};
+use vellum_migration::{
+    discover_from, AppliedHistory, DiscoveredMigrations, DiscoveryOptions, FilesystemSource,
+    MigrationDiscoveryError, MigrationPlan, MigrationSource, StepKind, StepReason, Version,
+};
+
+pub const DEFAULT_MIGRATIONS_DIR: &str = "migrations";
+
+const SQL_DATABASE_NAME: &str = "SELECT current_database()::text";
+
+const SQL_HISTORY_EXISTS: &str = "SELECT to_regclass('vellum.vellum_migrations') IS NOT NULL";
+
+const SQL_SELECT_LAST_RUN_STATUS: &str =
+    "SELECT status FROM vellum.vellum_runs ORDER BY started_at DESC LIMIT 1";
+
+const SQL_SELECT_RUN_MIGRATIONS: &str = "
+SELECT kind, version, name, execution_time_ms
+FROM vellum.vellum_migrations
+WHERE run_id = $1 AND success = TRUE
+ORDER BY id
+";
+
+#[derive(Debug)]
+pub enum VellumError {
+    Config { message: String },
+    Discovery(MigrationDiscoveryError),
+    Connection { message: String },
+    Bootstrap { message: String },
+    Query { message: String },
+    Executor(ExecutorError),
+}
+
+impl fmt::Display for VellumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VellumError::Config { message } => write!(f, "invalid configuration: {message}"),
+            VellumError::Discovery(err) => write!(f, "migration discovery failed: {err}"),
+            VellumError::Connection { message } => {
+                write!(f, "database connection failed: {message}")
+            }
+            VellumError::Bootstrap { message } => {
+                write!(f, "vellum schema initialization failed: {message}")
+            }
+            VellumError::Query { message } => write!(f, "database query failed: {message}"),
+            VellumError::Executor(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for VellumError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VellumError::Discovery(err) => Some(err),
+            VellumError::Executor(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<MigrationDiscoveryError> for VellumError {
+    fn from(err: MigrationDiscoveryError) -> Self {
+        VellumError::Discovery(err)
+    }
+}
+
+impl From<ExecutorError> for VellumError {
+    fn from(err: ExecutorError) -> Self {
+        VellumError::Executor(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingMigration {
+    pub version: Version,
+    pub name: String,
+    pub filename: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: Version,
+    pub name: String,
+    pub execution_time_ms: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedRepeatable {
+    pub name: String,
+    pub execution_time_ms: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrateReport {
+    pub run_id: Uuid,
+    pub plan: MigrationPlan,
+    pub applied: Vec<AppliedMigration>,
+    pub skipped: usize,
+    pub repeatables_applied: Vec<AppliedRepeatable>,
+    pub repeatables_skipped: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRunReport {
+    pub run_id: Uuid,
+    pub plan: MigrationPlan,
+    pub validated: Vec<PendingMigration>,
+    pub skipped: usize,
+    pub repeatables_validated: Vec<String>,
+    pub repeatables_skipped: usize,
+    pub lock_impact: LockImpact,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusReport {
+    pub database: String,
+    pub applied: usize,
+    pub pending: Vec<PendingMigration>,
+    pub pending_repeatables: Vec<String>,
+    pub modified: Vec<Version>,
+    pub last_applied: Option<Version>,
+    pub last_run_status: Option<String>,
+    pub plan: MigrationPlan,
+}
+
+impl StatusReport {
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending.is_empty() && self.pending_repeatables.is_empty() && self.modified.is_empty()
+    }
+}
+
+pub struct VellumBuilder {
+    database_url: Option<String>,
+    source: Box<dyn MigrationSource + Send + Sync>,
+    options: DiscoveryOptions,
+    connection_settings: ConnectionSettings,
+    lock_settings: LockSettings,
+    explain_analyze: bool,
+    vellum_version: String,
+}
+
+impl VellumBuilder {
+    fn new() -> Self {
+        Self {
+            database_url: None,
+            source: Box::new(FilesystemSource::new(DEFAULT_MIGRATIONS_DIR)),
+            options: DiscoveryOptions::default(),
+            connection_settings: ConnectionSettings::default(),
+            lock_settings: LockSettings::default(),
+            explain_analyze: false,
+            vellum_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    pub fn database_url(mut self, database_url: impl Into<String>) -> Self {
+        self.database_url = Some(database_url.into());
+        self
+    }
+
+    pub fn migrations(mut self, source: impl MigrationSource + Send + Sync + 'static) -> Self {
+        self.source = Box::new(source);
+        self
+    }
+
+    pub fn discovery_options(mut self, options: DiscoveryOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn connection_settings(mut self, connection_settings: ConnectionSettings) -> Self {
+        self.connection_settings = connection_settings;
+        self
+    }
+
+    pub fn lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_settings.wait = LockWait::Timeout(lock_timeout);
+        self
+    }
+
+    pub fn lock_wait(mut self, lock_wait: LockWait) -> Self {
+        self.lock_settings.wait = lock_wait;
+        self
+    }
+
+    pub fn lock_scope(mut self, lock_scope: LockScope) -> Self {
+        self.lock_settings.scope = lock_scope;
+        self
+    }
+
+    pub fn explain_analyze(mut self, explain_analyze: bool) -> Self {
+        self.explain_analyze = explain_analyze;
+        self
+    }
+
+    pub fn vellum_version(mut self, vellum_version: impl Into<String>) -> Self {
+        self.vellum_version = vellum_version.into();
+        self
+    }
+
+    pub fn build(self) -> Result<Vellum, VellumError> {
+        let database_url = match self.database_url {
+            Some(url) if !url.trim().is_empty() => url,
+            _ => {
+                return Err(VellumError::Config {
+                    message: "database_url is required".to_string(),
+                })
+            }
+        };
+
+        let connection = ConnectionManager::new(&database_url, self.connection_settings)
+            .map_err(|e| VellumError::Config {
+                message: e.to_string(),
+            })?;
+        let pool = connection.pool_lazy();
+
+        let runner = Runner::new(pool.clone(), self.vellum_version)
+            .with_connection_settings(connection.settings().clone())
+            .with_lock_settings(self.lock_settings)
+            .with_explain_analyze(self.explain_analyze);
+
+        Ok(Vellum {
+            connection,
+            pool,
+            runner,
+            source: self.source,
+            options: self.options,
+        })
+    }
+}
+
+pub struct Vellum {
+    connection: ConnectionManager,
+    pool: sqlx::PgPool,
+    runner: Runner,
+    source: Box<dyn MigrationSource + Send + Sync>,
+    options: DiscoveryOptions,
+}
+
+impl Vellum {
+    pub fn builder() -> VellumBuilder {
+        VellumBuilder::new()
+    }
+
+    pub fn discover(&self) -> Result<DiscoveredMigrations, VellumError> {
+        Ok(discover_from(self.source.as_ref(), &self.options)?)
+    }
+
+    pub async fn migrate(&self) -> Result<MigrateReport, VellumError> {
+        let discovered = self.discover()?;
+        self.bootstrap().await?;
+
+        let report = self
+            .runner
+            .run(&discovered.versioned, &discovered.repeatable)
+            .await?;
+
+        let (applied, repeatables_applied) =
+            applied_from_rows(self.run_migrations(report.run_id).await?);
+
+        Ok(MigrateReport {
+            run_id: report.run_id,
+            plan: report.plan,
+            applied,
+            skipped: report.skipped,
+            repeatables_applied,
+            repeatables_skipped: report.repeatable_skipped,
+        })
+    }
+
+    pub async fn dry_run(&self) -> Result<DryRunReport, VellumError> {
+        let discovered = self.discover()?;
+        self.bootstrap().await?;
+
+        let report = self
+            .runner
+            .run_with_mode(
+                ExecutionMode::DryRun,
+                &discovered.versioned,
+                &discovered.repeatable,
+            )
+            .await?;
+
+        Ok(DryRunReport {
+            run_id: report.run_id,
+            validated: pending_migrations(&report.plan),
+            skipped: report.skipped,
+            repeatables_validated: pending_repeatables(&report.plan),
+            repeatables_skipped: report.repeatable_skipped,
+            lock_impact: report.lock_impact.unwrap_or_default(),
+            plan: report.plan,
+        })
+    }
+
+    pub async fn status(&self) -> Result<StatusReport, VellumError> {
+        let discovered = self.discover()?;
+        self.wait_for_database().await?;
+
+        let database: String = sqlx::query_scalar(SQL_DATABASE_NAME)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(connection_error)?;
+
+        let initialized: bool = sqlx::query_scalar(SQL_HISTORY_EXISTS)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(query_error)?;
+
+        let (history, last_run_status) = if initialized {
+            let history = vellum_executor::audit::select_applied_history(&self.pool).await?;
+            let last_run_status = sqlx::query_scalar(SQL_SELECT_LAST_RUN_STATUS)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(query_error)?;
+            (history, last_run_status)
+        } else {
+            (AppliedHistory::default(), None)
+        };
+
+        let plan = MigrationPlan::build(&history, &discovered.versioned, &discovered.repeatable);
+
+        Ok(StatusReport {
+            database,
+            applied: history.versioned.len(),
+            pending: pending_migrations(&plan),
+            pending_repeatables: pending_repeatables(&plan),
+            modified: plan
+                .steps
+                .iter()
+                .filter(|s| matches!(s.reason, StepReason::ChecksumMismatch { .. }))
+                .filter_map(|s| s.version.clone())
+                .collect(),
+            last_applied: history.versioned.keys().max().cloned(),
+            last_run_status,
+            plan,
+        })
+    }
+
+    // The pool connects lazily and gives up on the first refused connection,
+    // so with a connect timeout the database is probed up front until it
+    // accepts connections.
+    async fn wait_for_database(&self) -> Result<(), VellumError> {
+        if self.connection.settings().connect_timeout.is_zero() {
+            return Ok(());
+        }
+
+        let conn = self
+            .connection
+            .connection()
+            .await
+            .map_err(|e| VellumError::Connection {
+                message: e.to_string(),
+            })?;
+        let _ = sqlx::Connection::close(conn).await;
+        Ok(())
+    }
+
+    async fn bootstrap(&self) -> Result<(), VellumError> {
+        self.wait_for_database().await?;
+        vellum_db::SqlxDatabaseMigrator::from_pool(self.pool.clone())
+            .apply_baseline()
+            .await
+            .map_err(|e| VellumError::Bootstrap {
+                message: e.to_string(),
+            })
+    }
+
+    async fn run_migrations(
+        &self,
+        run_id: Uuid,
+    ) -> Result<Vec<(String, String, String, i32)>, VellumError> {
+        sqlx::query_as(SQL_SELECT_RUN_MIGRATIONS)
+            .bind(run_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(query_error)
+    }
+}
+
+// Repeatable rows carry the migration name in both `version` and `name`;
+// the name column is the one read back.
+fn applied_from_rows(
+    rows: Vec<(String, String, String, i32)>,
+) -> (Vec<AppliedMigration>, Vec<AppliedRepeatable>) {
+    let mut applied = Vec::new();
+    let mut repeatables_applied = Vec::new();
+    for (kind, version, name, execution_time_ms) in rows {
+        if kind == "repeatable" {
+            repeatables_applied.push(AppliedRepeatable {
+                name,
+                execution_time_ms,
+            });
+        } else if let Ok(version) = version.parse::<Version>() {
+            applied.push(AppliedMigration {
+                version,
+                name,
+                execution_time_ms,
+            });
+        }
+    }
+    (applied, repeatables_applied)
+}
+
+fn pending_migrations(plan: &MigrationPlan) -> Vec<PendingMigration> {
+    plan.to_apply()
+        .filter_map(|step| match (step.kind, &step.version) {
+            (StepKind::Versioned, Some(version)) => Some(PendingMigration {
+                version: version.clone(),
+                name: step.name.clone(),
+                filename: step.filename.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn pending_repeatables(plan: &MigrationPlan) -> Vec<String> {
+    plan.to_apply()
+        .filter(|step| step.kind == StepKind::Repeatable)
+        .map(|step| step.name.clone())
+        .collect()
+}
+
+fn connection_error(err: sqlx::Error) -> VellumError {
+    VellumError::Connection {
+        message: err.to_string(),
+    }
+}
+
+fn query_error(err: sqlx::Error) -> VellumError {
+    VellumError::Query {
+        message: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        applied_from_rows, pending_migrations, pending_repeatables, AppliedMigration,
+        AppliedRepeatable, PendingMigration, Vellum, VellumError,
+    };
+    use vellum_migration::{
+        AppliedHistory, Migration, MigrationPlan, RepeatableMigration, Version,
+    };
+
+    fn migration(version: u64, name: &str) -> Migration {
+        Migration::new(
+            Version::from(version),
+            name.to_string(),
+            format!("{version:03}_{name}.sql"),
+            format!("checksum-{version}"),
+            "select 1;".to_string(),
+        )
+    }
+
+    fn build_error(builder: super::VellumBuilder) -> VellumError {
+        match builder.build() {
+            Ok(_) => panic!("build() should fail"),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn build_requires_a_database_url() {
+        for builder in [Vellum::builder(), Vellum::builder().database_url("  ")] {
+            let err = build_error(builder);
+            assert!(
+                matches!(&err, VellumError::Config { message } if message == "database_url is required"),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn build_rejects_an_invalid_database_url() {
+        let err = build_error(Vellum::builder().database_url("postgres://localhost/app?sslmode=sometimes"));
+        assert!(matches!(err, VellumError::Config { .. }), "{err}");
+    }
+
+    #[tokio::test]
+    async fn build_does_not_connect() {
+        let built = Vellum::builder()
+            .database_url("postgres://vellum@127.0.0.1:1/app")
+            .build();
+        assert!(built.is_ok());
+    }
+
+    #[test]
+    fn run_rows_map_to_applied_migrations_and_repeatables() {
+        let rows = vec![
+            ("versioned".to_string(), "1".to_string(), "init".to_string(), 12),
+            (
+                "repeatable".to_string(),
+                "refresh_views".to_string(),
+                "refresh_views".to_string(),
+                3,
+            ),
+            ("versioned".to_string(), "2.1".to_string(), "users".to_string(), 7),
+        ];
+
+        let (applied, repeatables) = applied_from_rows(rows);
+
+        assert_eq!(
+            applied,
+            vec![
+                AppliedMigration {
+                    version: Version::from(1),
+                    name: "init".to_string(),
+                    execution_time_ms: 12,
+                },
+                AppliedMigration {
+                    version: "2.1".parse().unwrap(),
+                    name: "users".to_string(),
+                    execution_time_ms: 7,
+                },
+            ]
+        );
+        assert_eq!(
+            repeatables,
+            vec![AppliedRepeatable {
+                name: "refresh_views".to_string(),
+                execution_time_ms: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn pending_lists_only_steps_to_apply() {
+        let applied = migration(1, "init");
+        let mut history = AppliedHistory::default();
+        history
+            .versioned
+            .insert(applied.version.clone(), applied.checksum.clone());
+        let repeatable = RepeatableMigration::new(
+            "views".to_string(),
+            "R__views.sql".to_string(),
+            "checksum-views".to_string(),
+            "select 1;".to_string(),
+        );
+
+        let plan = MigrationPlan::build(&history, &[applied, migration(2, "users")], &[repeatable]);
+
+        assert_eq!(
+            pending_migrations(&plan),
+            vec![PendingMigration {
+                version: Version::from(2),
+                name: "users".to_string(),
+                filename: "002_users.sql".to_string(),
+            }]
+        );
+        assert_eq!(pending_repeatables(&plan), vec!["views".to_string()]);
+    }
+}
@@ -0,0 +1,26 @@
+BEGIN;
+
+ALTER TABLE vellum.vellum_runs
+    DROP CONSTRAINT IF EXISTS vellum_runs_mode_check;
+
+ALTER TABLE vellum.vellum_runs
+    ADD CONSTRAINT vellum_runs_mode_check
+    CHECK (mode IN ('dry-run', 'apply', 'repair'));
+
+CREATE TABLE IF NOT EXISTS vellum.vellum_repairs (
+    id BIGSERIAL PRIMARY KEY,
+    run_id UUID NOT NULL
+        REFERENCES vellum.vellum_runs(id)
+        ON DELETE CASCADE,
+    version TEXT NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('checksum_updated', 'failed_removed', 'orphan_removed')),
+    old_checksum TEXT,
+    new_checksum TEXT,
+    reason TEXT NOT NULL,
+    repaired_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_repairs_run_id
+    ON vellum.vellum_repairs(run_id);
+
+COMMIT;
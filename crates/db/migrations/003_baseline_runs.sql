@@ -0,0 +1,10 @@
+BEGIN;
+
+ALTER TABLE vellum.vellum_runs
+    DROP CONSTRAINT IF EXISTS vellum_runs_mode_check;
+
+ALTER TABLE vellum.vellum_runs
+    ADD CONSTRAINT vellum_runs_mode_check
+    CHECK (mode IN ('dry-run', 'apply', 'repair', 'baseline'));
+
+COMMIT;
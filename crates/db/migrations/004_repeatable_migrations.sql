@@ -0,0 +1,21 @@
+BEGIN;
+
+ALTER TABLE vellum.vellum_migrations
+    ADD COLUMN IF NOT EXISTS kind TEXT NOT NULL DEFAULT 'versioned'
+    CHECK (kind IN ('versioned', 'repeatable'));
+
+ALTER TABLE vellum.vellum_migrations
+    DROP CONSTRAINT IF EXISTS vellum_migrations_version_key;
+
+ALTER TABLE vellum.vellum_migrations
+    DROP CONSTRAINT IF EXISTS vellum_migrations_checksum_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_migrations_versioned_version
+    ON vellum.vellum_migrations(version)
+    WHERE kind = 'versioned';
+
+CREATE INDEX IF NOT EXISTS idx_migrations_repeatable_name
+    ON vellum.vellum_migrations(name, applied_at)
+    WHERE kind = 'repeatable';
+
+COMMIT;
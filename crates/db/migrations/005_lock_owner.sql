@@ -0,0 +1,8 @@
+BEGIN;
+
+ALTER TABLE vellum.vellum_locks
+    ADD COLUMN IF NOT EXISTS backend_pid INTEGER,
+    ADD COLUMN IF NOT EXISTS client_host TEXT,
+    ADD COLUMN IF NOT EXISTS client_pid INTEGER;
+
+COMMIT;
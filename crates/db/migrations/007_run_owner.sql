@@ -0,0 +1,13 @@
+BEGIN;
+
+ALTER TABLE vellum.vellum_runs
+    ADD COLUMN IF NOT EXISTS owner_pid INTEGER;
+
+ALTER TABLE vellum.vellum_runs
+    DROP CONSTRAINT IF EXISTS vellum_runs_status_check;
+
+ALTER TABLE vellum.vellum_runs
+    ADD CONSTRAINT vellum_runs_status_check
+    CHECK (status IN ('running', 'success', 'failed', 'cancelled', 'abandoned'));
+
+COMMIT;
@@ -26,6 +26,12 @@ impl DefaultDbConnection {
     }
 }
 
+impl Default for DefaultDbConnection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct DefaultDbTransaction;
 
 impl vellum_contracts::db::DbTransaction for DefaultDbTransaction {
@@ -49,17 +55,38 @@ impl vellum_contracts::db::DbConnection for DefaultDbConnection {
     }
 }
 
+// 001 is idempotent and runs on every bootstrap; later files are applied once,
+// in order, and tracked by the `schema_version` key in `vellum_metadata`.
+const INTERNAL_MIGRATIONS: &[(i64, &str)] = &[
+    (1, include_str!("../migrations/001_init_schema.sql")),
+    (2, include_str!("../migrations/002_repair_runs.sql")),
+    (3, include_str!("../migrations/003_baseline_runs.sql")),
+    (4, include_str!("../migrations/004_repeatable_migrations.sql")),
+    (5, include_str!("../migrations/005_lock_owner.sql")),
+    (6, include_str!("../migrations/006_cancelled_runs.sql")),
+    (7, include_str!("../migrations/007_run_owner.sql")),
+];
+
+const SQL_LOCK_METADATA: &str = "LOCK TABLE vellum.vellum_metadata IN SHARE ROW EXCLUSIVE MODE";
+
+const SQL_SELECT_SCHEMA_VERSION: &str =
+    "SELECT (value #>> '{}')::bigint FROM vellum.vellum_metadata WHERE key = 'schema_version'";
+
+const SQL_UPSERT_SCHEMA_VERSION: &str = "
+INSERT INTO vellum.vellum_metadata (key, value, updated_at)
+VALUES ('schema_version', to_jsonb($1::bigint), now())
+ON CONFLICT (key) DO UPDATE
+SET value = EXCLUDED.value,
+    updated_at = now()
+";
+
 pub struct SqlxDatabaseMigrator {
     pool: sqlx::PgPool,
 }
 
 impl SqlxDatabaseMigrator {
-    pub async fn connect(database_url: &str) -> Result<Self, vellum_contracts::Error> {
-        let pool = sqlx::PgPool::connect(database_url)
-            .await
-            .map_err(|e| vellum_contracts::Error::message(e.to_string()))?;
-
-        Ok(Self { pool })
+    pub fn from_pool(pool: sqlx::PgPool) -> Self {
+        Self { pool }
     }
 }
 
@@ -72,29 +99,21 @@ impl vellum_contracts::migrations::DatabaseMigrator for SqlxDatabaseMigrator {
         Box<dyn core::future::Future<Output = Result<(), Self::Error>> + Send + 'a>,
     > {
         Box::pin(async move {
-            let sql = include_str!("../migrations/001_init_schema.sql");
-
             let mut tx = self
                 .pool
                 .begin()
                 .await
                 .map_err(|e| vellum_contracts::Error::message(e.to_string()))?;
 
-            for chunk in sql.split(';') {
-                let stmt = chunk.trim();
-                if stmt.is_empty() {
-                    continue;
-                }
-
-                let stmt_upper = stmt.to_ascii_uppercase();
-                if stmt_upper == "BEGIN" || stmt_upper == "COMMIT" {
-                    continue;
-                }
-
-                if let Err(e) = sqlx::query(stmt).execute(&mut *tx).await {
-                    let _ = tx.rollback().await;
-                    return Err(vellum_contracts::Error::message(e.to_string()));
-                }
+            let (_, init_sql) = INTERNAL_MIGRATIONS[0];
+            if let Err(e) = execute_internal_sql(&mut tx, init_sql).await {
+                let _ = tx.rollback().await;
+                return Err(e);
+            }
+
+            if let Err(e) = upgrade_internal_schema(&mut tx).await {
+                let _ = tx.rollback().await;
+                return Err(e);
             }
 
             tx.commit()
@@ -105,3 +124,58 @@ impl vellum_contracts::migrations::DatabaseMigrator for SqlxDatabaseMigrator {
         })
     }
 }
+
+async fn upgrade_internal_schema(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+) -> Result<(), vellum_contracts::Error> {
+    sqlx::query(SQL_LOCK_METADATA)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| vellum_contracts::Error::message(e.to_string()))?;
+
+    let current: Option<i64> = sqlx::query_scalar(SQL_SELECT_SCHEMA_VERSION)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| vellum_contracts::Error::message(e.to_string()))?;
+    let current = current.unwrap_or(1);
+
+    for (version, sql) in INTERNAL_MIGRATIONS {
+        if *version <= current {
+            continue;
+        }
+
+        execute_internal_sql(tx, sql).await?;
+
+        sqlx::query(SQL_UPSERT_SCHEMA_VERSION)
+            .bind(*version)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| vellum_contracts::Error::message(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+async fn execute_internal_sql(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    sql: &str,
+) -> Result<(), vellum_contracts::Error> {
+    for chunk in sql.split(';') {
+        let stmt = chunk.trim();
+        if stmt.is_empty() {
+            continue;
+        }
+
+        let stmt_upper = stmt.to_ascii_uppercase();
+        if stmt_upper == "BEGIN" || stmt_upper == "COMMIT" {
+            continue;
+        }
+
+        sqlx::query(stmt)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| vellum_contracts::Error::message(e.to_string()))?;
+    }
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use crate::error::ExecutorError;
 use crate::statement::SqlStatement;
 use uuid::Uuid;
-use vellum_migration::{sha256_hex, Migration};
+use vellum_migration::{
+    sha256_hex, AppliedHistory, DatabaseIdentity, Migration, RepeatableMigration, Version,
+};
 
 const SQL_DB_INFO: &str = "SELECT current_database()::text, current_user::text, inet_client_addr()::text";
 
@@ -15,9 +17,10 @@ INSERT INTO vellum.vellum_runs (
     db_name,
     db_user,
     client_host,
-    vellum_version
+    vellum_version,
+    owner_pid
 )
-VALUES ($1, now(), NULL, $2, $3, $4, $5, $6, $7)
+VALUES ($1, now(), NULL, $2, $3, $4, $5, $6, $7, $8)
 ";
 
 const SQL_UPDATE_RUN_STATUS: &str = "
@@ -27,10 +30,90 @@ SET status = $2,
 WHERE id = $1
 ";
 
-const SQL_SELECT_MIGRATION_CHECKSUM: &str = "
-SELECT checksum
+// Only a run that is still running is cancelled; a run that managed to
+// record its own outcome before being interrupted keeps it.
+const SQL_CANCEL_RUN: &str = "
+UPDATE vellum.vellum_runs
+SET status = 'cancelled',
+    finished_at = now()
+WHERE id = $1 AND status = 'running'
+";
+
+const SQL_SELECT_APPLIED_CHECKSUMS: &str = "
+SELECT version, checksum
+FROM vellum.vellum_migrations
+WHERE kind = 'versioned'
+";
+
+// Histories written before internal schema version 4 have no `kind` column;
+// every row in them is a versioned migration. Read-only commands do not run
+// the internal upgrade, so they have to cope with both layouts.
+// pg_control_system() may be revoked from ordinary roles; the name alone is
+// then all that identifies the database.
+const SQL_SELECT_DATABASE_IDENTITY: &str = "
+SELECT
+    current_database()::text,
+    CASE WHEN has_function_privilege('pg_catalog.pg_control_system()', 'EXECUTE')
+        THEN (SELECT system_identifier::text FROM pg_catalog.pg_control_system())
+    END
+";
+
+const SQL_HAS_MIGRATION_KIND: &str = "
+SELECT EXISTS (
+    SELECT 1
+    FROM information_schema.columns
+    WHERE table_schema = 'vellum'
+      AND table_name = 'vellum_migrations'
+      AND column_name = 'kind'
+)
+";
+
+const SQL_SELECT_LEGACY_CHECKSUMS: &str = "
+SELECT version, checksum
+FROM vellum.vellum_migrations
+";
+
+const SQL_SELECT_REPEATABLE_CHECKSUMS: &str = "
+SELECT DISTINCT ON (name) name, checksum
+FROM vellum.vellum_migrations
+WHERE kind = 'repeatable'
+  AND success = TRUE
+ORDER BY name, applied_at DESC, id DESC
+";
+
+const SQL_COUNT_MIGRATIONS: &str = "
+SELECT count(*)
+FROM vellum.vellum_migrations
+";
+
+const SQL_SELECT_MIGRATION_RECORD: &str = "
+SELECT id, checksum, success
 FROM vellum.vellum_migrations
 WHERE version = $1
+  AND kind = 'versioned'
+";
+
+const SQL_UPDATE_MIGRATION_CHECKSUM: &str = "
+UPDATE vellum.vellum_migrations
+SET checksum = $2
+WHERE id = $1
+";
+
+const SQL_DELETE_MIGRATION: &str = "
+DELETE FROM vellum.vellum_migrations
+WHERE id = $1
+";
+
+const SQL_INSERT_REPAIR: &str = "
+INSERT INTO vellum.vellum_repairs (
+    run_id,
+    version,
+    action,
+    old_checksum,
+    new_checksum,
+    reason
+)
+VALUES ($1, $2, $3, $4, $5, $6)
 ";
 
 const SQL_INSERT_MIGRATION: &str = "
@@ -42,9 +125,10 @@ INSERT INTO vellum.vellum_migrations (
     success,
     error_code,
     error_message,
-    run_id
+    run_id,
+    kind
 )
-VALUES ($1, $2, $3, $4, $5, NULL, $6, $7)
+VALUES ($1, $2, $3, $4, $5, NULL, $6, $7, $8)
 RETURNING id
 ";
 
@@ -69,12 +153,26 @@ INSERT INTO vellum.vellum_statements (
 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
 ";
 
-pub async fn insert_run(pool: &sqlx::PgPool, vellum_version: &str) -> Result<Uuid, ExecutorError> {
-    insert_run_with_mode(pool, "apply", vellum_version).await
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationRecord {
+    pub id: i64,
+    pub checksum: String,
+    pub success: bool,
+}
+
+pub async fn insert_run(
+    pool: &sqlx::PgPool,
+    run_id: Uuid,
+    owner_pid: i32,
+    vellum_version: &str,
+) -> Result<Uuid, ExecutorError> {
+    insert_run_with_mode(pool, run_id, owner_pid, "apply", vellum_version).await
 }
 
 pub async fn insert_run_with_mode(
     pool: &sqlx::PgPool,
+    run_id: Uuid,
+    owner_pid: i32,
     mode: &str,
     vellum_version: &str,
 ) -> Result<Uuid, ExecutorError> {
@@ -89,8 +187,6 @@ pub async fn insert_run_with_mode(
                 original_error: None,
             })?;
 
-    let run_id = Uuid::new_v4();
-
     sqlx::query(SQL_INSERT_RUN)
         .bind(run_id)
         .bind(mode)
@@ -99,6 +195,7 @@ pub async fn insert_run_with_mode(
         .bind(db_user)
         .bind(client_host)
         .bind(vellum_version)
+        .bind(owner_pid)
         .execute(pool)
         .await
         .map_err(|e| ExecutorError::RunTrackingFailed {
@@ -147,22 +244,95 @@ pub async fn mark_run_failed(
     Ok(())
 }
 
-pub async fn get_applied_checksum(
-    pool: &sqlx::PgPool,
-    version: &str,
-) -> Result<Option<String>, ExecutorError> {
-    let row: Option<(String,)> = sqlx::query_as(SQL_SELECT_MIGRATION_CHECKSUM)
-        .bind(version)
-        .fetch_optional(pool)
+pub async fn mark_run_cancelled(pool: &sqlx::PgPool, run_id: Uuid) -> Result<(), ExecutorError> {
+    sqlx::query(SQL_CANCEL_RUN)
+        .bind(run_id)
+        .execute(pool)
         .await
         .map_err(|e| ExecutorError::RunTrackingFailed {
-            run_id: "<unknown>".to_string(),
-            operation: "select_migration_checksum".to_string(),
+            run_id: run_id.to_string(),
+            operation: "mark_run_cancelled".to_string(),
             message: e.to_string(),
             original_error: None,
         })?;
 
-    Ok(row.map(|r| r.0))
+    Ok(())
+}
+
+pub async fn has_migration_kind<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+) -> Result<bool, ExecutorError> {
+    sqlx::query_scalar(SQL_HAS_MIGRATION_KIND)
+        .fetch_one(executor)
+        .await
+        .map_err(|e| history_error("has_migration_kind", e))
+}
+
+pub async fn select_database_identity<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+) -> Result<DatabaseIdentity, ExecutorError> {
+    let (name, system_identifier) = sqlx::query_as(SQL_SELECT_DATABASE_IDENTITY)
+        .fetch_one(executor)
+        .await
+        .map_err(|e| history_error("select_database_identity", e))?;
+    Ok(DatabaseIdentity {
+        name,
+        system_identifier,
+    })
+}
+
+pub async fn select_applied_history(pool: &sqlx::PgPool) -> Result<AppliedHistory, ExecutorError> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| history_error("acquire_connection", e))?;
+    select_applied_history_with(&mut conn).await
+}
+
+// Reads the history on the caller's connection, so it can share a
+// transaction snapshot with other reads.
+pub async fn select_applied_history_with(
+    conn: &mut sqlx::PgConnection,
+) -> Result<AppliedHistory, ExecutorError> {
+    let has_kind = has_migration_kind(&mut *conn).await?;
+    let sql = if has_kind {
+        SQL_SELECT_APPLIED_CHECKSUMS
+    } else {
+        SQL_SELECT_LEGACY_CHECKSUMS
+    };
+    let versioned: Vec<(String, String)> = sqlx::query_as(sql)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| history_error("select_applied_checksums", e))?;
+
+    let repeatable: Vec<(String, String)> = if has_kind {
+        sqlx::query_as(SQL_SELECT_REPEATABLE_CHECKSUMS)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| history_error("select_repeatable_checksums", e))?
+    } else {
+        Vec::new()
+    };
+
+    let mut history = AppliedHistory::default();
+    for (version, checksum) in versioned {
+        let version = version
+            .parse::<Version>()
+            .map_err(|e| history_error("parse_applied_version", e))?;
+        history.versioned.insert(version, checksum);
+    }
+    history.repeatable.extend(repeatable);
+
+    Ok(history)
+}
+
+fn history_error(operation: &str, e: impl std::fmt::Display) -> ExecutorError {
+    ExecutorError::RunTrackingFailed {
+        run_id: "<unknown>".to_string(),
+        operation: operation.to_string(),
+        message: e.to_string(),
+        original_error: None,
+    }
 }
 
 pub async fn insert_migration(
@@ -171,15 +341,50 @@ pub async fn insert_migration(
     migration: &Migration,
 ) -> Result<i64, ExecutorError> {
     let version = migration.version.to_string();
+    insert_migration_row(
+        tx,
+        run_id,
+        "versioned",
+        &version,
+        &migration.name,
+        &migration.checksum,
+    )
+    .await
+}
 
+pub async fn insert_repeatable_migration(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    run_id: Uuid,
+    migration: &RepeatableMigration,
+) -> Result<i64, ExecutorError> {
+    insert_migration_row(
+        tx,
+        run_id,
+        "repeatable",
+        &migration.name,
+        &migration.name,
+        &migration.checksum,
+    )
+    .await
+}
+
+async fn insert_migration_row(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    run_id: Uuid,
+    kind: &str,
+    version: &str,
+    name: &str,
+    checksum: &str,
+) -> Result<i64, ExecutorError> {
     let migration_id: i64 = sqlx::query_scalar(SQL_INSERT_MIGRATION)
         .bind(version)
-        .bind(&migration.name)
-        .bind(&migration.checksum)
+        .bind(name)
+        .bind(checksum)
         .bind(0_i32)
         .bind(false)
         .bind(Option::<&str>::None)
         .bind(run_id)
+        .bind(kind)
         .fetch_one(&mut **tx)
         .await
         .map_err(|e| ExecutorError::RunTrackingFailed {
@@ -243,3 +448,105 @@ pub async fn insert_statement(
 
     Ok(())
 }
+
+pub async fn count_migrations(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+) -> Result<i64, ExecutorError> {
+    sqlx::query_scalar(SQL_COUNT_MIGRATIONS)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| ExecutorError::RunTrackingFailed {
+            run_id: "<unknown>".to_string(),
+            operation: "count_migrations".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })
+}
+
+pub async fn select_migration_record(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    version: &str,
+) -> Result<Option<MigrationRecord>, ExecutorError> {
+    let row: Option<(i64, String, bool)> = sqlx::query_as(SQL_SELECT_MIGRATION_RECORD)
+        .bind(version)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| ExecutorError::RunTrackingFailed {
+            run_id: "<unknown>".to_string(),
+            operation: "select_migration_record".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })?;
+
+    Ok(row.map(|(id, checksum, success)| MigrationRecord {
+        id,
+        checksum,
+        success,
+    }))
+}
+
+pub async fn update_migration_checksum(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    migration_id: i64,
+    checksum: &str,
+) -> Result<(), ExecutorError> {
+    sqlx::query(SQL_UPDATE_MIGRATION_CHECKSUM)
+        .bind(migration_id)
+        .bind(checksum)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| ExecutorError::RunTrackingFailed {
+            run_id: "<unknown>".to_string(),
+            operation: "update_migration_checksum".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })?;
+
+    Ok(())
+}
+
+pub async fn delete_migration(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    migration_id: i64,
+) -> Result<(), ExecutorError> {
+    sqlx::query(SQL_DELETE_MIGRATION)
+        .bind(migration_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| ExecutorError::RunTrackingFailed {
+            run_id: "<unknown>".to_string(),
+            operation: "delete_migration".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })?;
+
+    Ok(())
+}
+
+pub async fn insert_repair(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    run_id: Uuid,
+    version: &str,
+    action: &str,
+    old_checksum: Option<&str>,
+    new_checksum: Option<&str>,
+    reason: &str,
+) -> Result<(), ExecutorError> {
+    sqlx::query(SQL_INSERT_REPAIR)
+        .bind(run_id)
+        .bind(version)
+        .bind(action)
+        .bind(old_checksum)
+        .bind(new_checksum)
+        .bind(reason)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| ExecutorError::RunTrackingFailed {
+            run_id: run_id.to_string(),
+            operation: "insert_repair".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use crate::audit;
+use crate::error::ExecutorError;
+use uuid::Uuid;
+use vellum_migration::{Migration, Version};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaselineReport {
+    pub run_id: Uuid,
+    pub version: Version,
+    pub recorded: Vec<Version>,
+}
+
+pub async fn run(
+    pool: &sqlx::PgPool,
+    run_id: Uuid,
+    owner_pid: i32,
+    vellum_version: &str,
+    migrations: &[Migration],
+    version: &Version,
+) -> Result<BaselineReport, ExecutorError> {
+    if !migrations.iter().any(|m| &m.version == version) {
+        return Err(ExecutorError::BaselineVersionNotFound {
+            version: version.clone(),
+        });
+    }
+
+    audit::insert_run_with_mode(pool, run_id, owner_pid, "baseline", vellum_version).await?;
+
+    match record_baseline(pool, run_id, migrations, version).await {
+        Ok(recorded) => {
+            audit::mark_run_success(pool, run_id).await?;
+            Ok(BaselineReport {
+                run_id,
+                version: version.clone(),
+                recorded,
+            })
+        }
+        Err(err) => {
+            let _ = audit::mark_run_failed(pool, run_id, &err).await;
+            Err(err)
+        }
+    }
+}
+
+async fn record_baseline(
+    pool: &sqlx::PgPool,
+    run_id: Uuid,
+    migrations: &[Migration],
+    version: &Version,
+) -> Result<Vec<Version>, ExecutorError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| ExecutorError::RunTrackingFailed {
+            run_id: run_id.to_string(),
+            operation: "begin_baseline".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })?;
+
+    let existing = audit::count_migrations(&mut tx).await?;
+    if existing > 0 {
+        return Err(ExecutorError::BaselineHistoryNotEmpty { recorded: existing });
+    }
+
+    let mut recorded = Vec::new();
+    for m in migrations.iter().filter(|m| &m.version <= version) {
+        let migration_id = audit::insert_migration(&mut tx, run_id, m).await?;
+        audit::mark_migration_success(&mut tx, migration_id, 0).await?;
+        recorded.push(m.version.clone());
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| ExecutorError::RunTrackingFailed {
+            run_id: run_id.to_string(),
+            operation: "commit_baseline".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })?;
+
+    Ok(recorded)
+}
@@ -0,0 +1,348 @@
+use crate::error::ExecutorError;
+use core::str::FromStr;
+use core::time::Duration;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
+use sqlx::{Connection, PgConnection};
+use std::path::{Path, PathBuf};
+use tokio::time::{sleep, Instant};
+
+pub const DEFAULT_APPLICATION_NAME: &str = "vellum";
+pub const DEFAULT_POOL_SIZE: u32 = 10;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+// `connect_timeout` is how long to keep retrying while the server is
+// unreachable or still starting up; zero means a single attempt. Everything
+// else overrides what the database URL and the libpq environment variables
+// (PGPASSWORD, PGPASSFILE, PGSSLMODE, ...) already configure.
+#[derive(Debug, Clone)]
+pub struct ConnectionSettings {
+    pub application_name: String,
+    pub pool_size: u32,
+    pub connect_timeout: Duration,
+    pub ssl_mode: Option<PgSslMode>,
+    pub ssl_root_cert: Option<PathBuf>,
+    pub password_file: Option<PathBuf>,
+}
+
+impl Default for ConnectionSettings {
+    fn default() -> Self {
+        Self {
+            application_name: DEFAULT_APPLICATION_NAME.to_string(),
+            pool_size: DEFAULT_POOL_SIZE,
+            connect_timeout: Duration::ZERO,
+            ssl_mode: None,
+            ssl_root_cert: None,
+            password_file: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectionManager {
+    options: PgConnectOptions,
+    settings: ConnectionSettings,
+}
+
+impl ConnectionManager {
+    pub fn new(database_url: &str, settings: ConnectionSettings) -> Result<Self, ExecutorError> {
+        let mut options = PgConnectOptions::from_str(database_url).map_err(|e| {
+            ExecutorError::ConnectionConfigInvalid {
+                message: e.to_string(),
+            }
+        })?;
+
+        if options.get_application_name().is_none() {
+            options = options.application_name(&settings.application_name);
+        }
+        if let Some(mode) = settings.ssl_mode {
+            options = options.ssl_mode(mode);
+        }
+        if let Some(cert) = &settings.ssl_root_cert {
+            options = options.ssl_root_cert(cert);
+        }
+        // A password in the URL always wins; the file replaces PGPASSWORD and
+        // .pgpass, which sqlx has already consulted while parsing the URL.
+        if let Some(path) = &settings.password_file {
+            if !has_password(database_url) {
+                options = options.password(&read_password(path)?);
+            }
+        }
+
+        Ok(Self { options, settings })
+    }
+
+    // For options that already carry the settings, such as a pool's
+    // `connect_options()`.
+    pub fn from_options(options: PgConnectOptions, settings: ConnectionSettings) -> Self {
+        Self { options, settings }
+    }
+
+    pub fn for_database(&self, database: &str) -> Self {
+        Self {
+            options: self.options.clone().database(database),
+            settings: self.settings.clone(),
+        }
+    }
+
+    pub fn database(&self) -> Option<&str> {
+        self.options.get_database()
+    }
+
+    pub fn options(&self) -> &PgConnectOptions {
+        &self.options
+    }
+
+    pub fn settings(&self) -> &ConnectionSettings {
+        &self.settings
+    }
+
+    pub fn pool_lazy(&self) -> sqlx::PgPool {
+        self.pool_options().connect_lazy_with(self.options.clone())
+    }
+
+    // The pool retries refused connections on its own until the acquire
+    // timeout and then reports only that it timed out, so the server is
+    // probed with a plain connection first to get the real error promptly.
+    pub async fn pool(&self) -> Result<sqlx::PgPool, ExecutorError> {
+        let probe = self.connection().await?;
+        let _ = probe.close().await;
+
+        self.retry(|| self.pool_options().connect_with(self.options.clone()))
+            .await
+    }
+
+    pub async fn connection(&self) -> Result<PgConnection, ExecutorError> {
+        self.retry(|| PgConnection::connect_with(&self.options)).await
+    }
+
+    fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new().max_connections(self.settings.pool_size.max(1))
+    }
+
+    async fn retry<T, F, Fut>(&self, mut connect: F) -> Result<T, ExecutorError>
+    where
+        F: FnMut() -> Fut,
+        Fut: core::future::Future<Output = Result<T, sqlx::Error>>,
+    {
+        let deadline = Instant::now() + self.settings.connect_timeout;
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+            let err = match connect().await {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !is_transient(&err) || remaining.is_zero() {
+                return Err(ExecutorError::ConnectionFailed {
+                    attempts,
+                    message: err.to_string(),
+                });
+            }
+
+            sleep(backoff.min(remaining)).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+// Only failures that can go away on their own are retried: the server not
+// accepting connections yet, or being at its connection limit. Bad
+// credentials, a missing database or a TLS mismatch fail straight away.
+fn is_transient(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Io(_) => true,
+        sqlx::Error::Database(db) => matches!(
+            db.code().as_deref(),
+            Some("57P03") | Some("53300") | Some("08006") | Some("08001")
+        ),
+        _ => false,
+    }
+}
+
+fn has_password(database_url: &str) -> bool {
+    url::Url::parse(database_url)
+        .ok()
+        .is_some_and(|url| url.password().is_some())
+}
+
+fn read_password(path: &Path) -> Result<String, ExecutorError> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| ExecutorError::ConnectionConfigInvalid {
+            message: format!("failed to read password file {}: {e}", path.display()),
+        })?;
+    Ok(contents.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_password, is_transient, read_password, ConnectionManager, ConnectionSettings};
+    use crate::error::ExecutorError;
+    use sqlx::error::{DatabaseError, ErrorKind};
+    use sqlx::postgres::PgSslMode;
+    use std::borrow::Cow;
+    use std::path::PathBuf;
+
+    #[derive(Debug)]
+    struct ServerError(&'static str);
+
+    impl std::fmt::Display for ServerError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "server error {}", self.0)
+        }
+    }
+
+    impl std::error::Error for ServerError {}
+
+    impl DatabaseError for ServerError {
+        fn message(&self) -> &str {
+            "server error"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    fn server_error(code: &'static str) -> sqlx::Error {
+        sqlx::Error::Database(Box::new(ServerError(code)))
+    }
+
+    fn temp_file(contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("vellum-password-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn settings_with_password_file(path: PathBuf) -> ConnectionSettings {
+        ConnectionSettings {
+            password_file: Some(path),
+            ..ConnectionSettings::default()
+        }
+    }
+
+    #[test]
+    fn startup_and_capacity_failures_are_transient() {
+        let refused = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        assert!(is_transient(&sqlx::Error::Io(refused)));
+        for code in ["57P03", "53300", "08006", "08001"] {
+            assert!(is_transient(&server_error(code)), "{code}");
+        }
+    }
+
+    #[test]
+    fn configuration_failures_are_not_transient() {
+        // invalid_password, invalid_catalog_name
+        for code in ["28P01", "3D000"] {
+            assert!(!is_transient(&server_error(code)), "{code}");
+        }
+        assert!(!is_transient(&sqlx::Error::PoolTimedOut));
+        assert!(!is_transient(&sqlx::Error::Tls("handshake failed".into())));
+    }
+
+    #[test]
+    fn has_password_only_for_urls_with_one() {
+        assert!(has_password("postgres://app:secret@db/app"));
+        assert!(!has_password("postgres://app@db/app"));
+        assert!(!has_password("postgres://db/app"));
+        assert!(!has_password("not a url"));
+    }
+
+    #[test]
+    fn read_password_trims_only_the_line_ending() {
+        for (contents, expected) in [
+            ("secret\n", "secret"),
+            ("secret\r\n", "secret"),
+            ("secret", "secret"),
+            (" spaced secret \n\n", " spaced secret "),
+        ] {
+            let path = temp_file(contents);
+            let password = read_password(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(password, expected, "{contents:?}");
+        }
+    }
+
+    #[test]
+    fn read_password_reports_a_missing_file() {
+        let path = std::env::temp_dir().join("vellum-password-missing");
+        let err = read_password(&path).unwrap_err();
+        assert!(matches!(err, ExecutorError::ConnectionConfigInvalid { .. }), "{err}");
+    }
+
+    #[test]
+    fn url_password_wins_over_the_password_file() {
+        // The file is only read when the URL has no password, so a missing
+        // file shows which source was used.
+        let missing = std::env::temp_dir().join("vellum-password-missing");
+
+        let with_password = ConnectionManager::new(
+            "postgres://app:secret@db/app",
+            settings_with_password_file(missing.clone()),
+        );
+        assert!(with_password.is_ok());
+
+        let without_password =
+            ConnectionManager::new("postgres://app@db/app", settings_with_password_file(missing));
+        assert!(matches!(
+            without_password,
+            Err(ExecutorError::ConnectionConfigInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn url_application_name_wins_over_the_default() {
+        let from_url = ConnectionManager::new(
+            "postgres://db/app?application_name=deployer",
+            ConnectionSettings::default(),
+        )
+        .unwrap();
+        assert_eq!(from_url.options().get_application_name(), Some("deployer"));
+
+        let default = ConnectionManager::new("postgres://db/app", ConnectionSettings::default()).unwrap();
+        assert_eq!(default.options().get_application_name(), Some("vellum"));
+    }
+
+    #[test]
+    fn ssl_mode_setting_overrides_the_url() {
+        let settings = ConnectionSettings {
+            ssl_mode: Some(PgSslMode::Require),
+            ..ConnectionSettings::default()
+        };
+        let manager = ConnectionManager::new("postgres://db/app?sslmode=disable", settings).unwrap();
+        assert!(matches!(manager.options().get_ssl_mode(), PgSslMode::Require));
+
+        let manager =
+            ConnectionManager::new("postgres://db/app?sslmode=disable", ConnectionSettings::default())
+                .unwrap();
+        assert!(matches!(manager.options().get_ssl_mode(), PgSslMode::Disable));
+    }
+
+    #[test]
+    fn invalid_url_is_a_configuration_error() {
+        let err = ConnectionManager::new("postgres://db/app?sslmode=sometimes", ConnectionSettings::default())
+            .unwrap_err();
+        assert!(matches!(err, ExecutorError::ConnectionConfigInvalid { .. }), "{err}");
+    }
+}
@@ -1,29 +1,68 @@
 use crate::audit;
 use crate::error::ExecutorError;
+use crate::impact::ImpactTracer;
+use crate::planner;
+use crate::repeatable;
+use crate::runner::RunReport;
 use crate::statement;
-use vellum_migration::Migration;
+use crate::transaction;
+use uuid::Uuid;
+use vellum_migration::{Migration, RepeatableMigration, SourceMap, Version};
 
 pub async fn run(
     pool: &sqlx::PgPool,
+    run_id: Uuid,
+    owner_pid: i32,
     vellum_version: &str,
     migrations: &[Migration],
-) -> Result<crate::runner::RunReport, ExecutorError> {
-    let run_id = audit::insert_run_with_mode(pool, "dry-run", vellum_version)
+    repeatables: &[RepeatableMigration],
+    explain_analyze: bool,
+) -> Result<RunReport, ExecutorError> {
+    audit::insert_run_with_mode(pool, run_id, owner_pid, "dry-run", vellum_version)
         .await
         .map_err(|e| ExecutorError::DryRunFailed {
             message: "run tracking insert failed".to_string(),
             original_error: e.to_string(),
         })?;
 
-    let planned = match plan_migrations(pool, migrations).await {
-        Ok(planned) => planned,
-        Err(err) => {
+    let plan = match planner::compute(pool, migrations, repeatables).await {
+        Ok(plan) => plan,
+        Err(e) => {
+            let err = ExecutorError::DryRunFailed {
+                message: "applied checksum lookup failed".to_string(),
+                original_error: e.to_string(),
+            };
             let _ = audit::mark_run_failed(pool, run_id, &err).await;
             return Err(err);
         }
     };
 
-    let mut tx = match pool.begin().await {
+    if let Some(mismatch) = planner::checksum_mismatch(&plan) {
+        let err = ExecutorError::DryRunValidationError {
+            migration_version: mismatch.version.clone(),
+            statement_ordinal: None,
+            sql_snippet: None,
+            message: format!(
+                "checksum mismatch for version {} (db={}, fs={})",
+                mismatch.version, mismatch.expected, mismatch.actual
+            ),
+        };
+        let _ = audit::mark_run_failed(pool, run_id, &err).await;
+        return Err(err);
+    }
+
+    let (to_apply, changed_repeatables) = planner::resolve(&plan, migrations, repeatables);
+
+    if let Some(err) = validate_plpgsql(&to_apply, &changed_repeatables) {
+        let _ = audit::mark_run_failed(pool, run_id, &err).await;
+        return Err(err);
+    }
+
+    let begun = match pool.begin().await {
+        Ok(mut tx) => transaction::tag(&mut tx, run_id).await.map(|_| tx),
+        Err(e) => Err(e),
+    };
+    let mut tx = match begun {
         Ok(tx) => tx,
         Err(e) => {
             let err = ExecutorError::DryRunTransactionError {
@@ -36,34 +75,21 @@ pub async fn run(
         }
     };
 
-    for m in &planned.to_apply {
-        let statements = statement::split_statements(&m.sql, Some(&m.filename), m.version)?;
-
-        for stmt in &statements {
-            if let Err(err) = statement::execute_statement(&mut tx, m.version, stmt).await {
-                let mapped = map_validation_error(
-                    m.version,
-                    Some(stmt.ordinal()),
-                    Some(stmt.sql()),
-                    &err,
-                );
-
-                let rollback_res = tx.rollback().await;
-                if let Err(rollback_err) = rollback_res {
-                    let rollback_mapped = ExecutorError::DryRunTransactionError {
-                        operation: "rollback_after_failure".to_string(),
-                        message: rollback_err.to_string(),
-                        original_error: Some(mapped.to_string()),
-                    };
-
-                    let _ = audit::mark_run_failed(pool, run_id, &rollback_mapped).await;
-                    return Err(rollback_mapped);
-                }
+    let mut tracer = ImpactTracer::new(explain_analyze);
+    if let Err(err) = execute_traced(&mut tx, &to_apply, &changed_repeatables, &mut tracer).await {
+        if let Err(rollback_err) = tx.rollback().await {
+            let rollback_mapped = ExecutorError::DryRunTransactionError {
+                operation: "rollback_after_failure".to_string(),
+                message: rollback_err.to_string(),
+                original_error: Some(err.to_string()),
+            };
 
-                let _ = audit::mark_run_failed(pool, run_id, &mapped).await;
-                return Err(mapped);
-            }
+            let _ = audit::mark_run_failed(pool, run_id, &rollback_mapped).await;
+            return Err(rollback_mapped);
         }
+
+        let _ = audit::mark_run_failed(pool, run_id, &err).await;
+        return Err(err);
     }
 
     match tx.rollback().await {
@@ -86,65 +112,101 @@ pub async fn run(
             original_error: e.to_string(),
         })?;
 
-    Ok(crate::runner::RunReport {
-        run_id,
-        applied: planned.to_apply.len(),
-        skipped: planned.skipped,
-    })
+    let mut report = RunReport::from_plan(run_id, plan);
+    report.lock_impact = Some(tracer.into_impact());
+    Ok(report)
 }
 
-struct PlannedMigrations<'a> {
-    to_apply: Vec<&'a Migration>,
-    skipped: usize,
-}
+async fn execute_traced(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    migrations: &[&Migration],
+    repeatables: &[&RepeatableMigration],
+    tracer: &mut ImpactTracer,
+) -> Result<(), ExecutorError> {
+    for m in migrations {
+        let statements = statement::split_statements(&m.sql, Some(&m.filename), &m.version)?;
 
-async fn plan_migrations<'a>(
-    pool: &sqlx::PgPool,
-    migrations: &'a [Migration],
-) -> Result<PlannedMigrations<'a>, ExecutorError> {
-    let mut to_apply = Vec::new();
-    let mut skipped = 0usize;
+        for stmt in &statements {
+            let (execution_time_ms, rows) =
+                tracer.execute(tx, &m.filename, stmt).await.map_err(|failure| {
+                    let err = ExecutorError::StatementExecutionFailed {
+                        migration_version: m.version.clone(),
+                        statement_ordinal: stmt.ordinal(),
+                        execution_time_ms: failure.execution_time_ms,
+                        statement: stmt.sql().to_string(),
+                        message: failure.message,
+                    };
+                    map_validation_error(&m.version, Some(stmt.ordinal()), Some(stmt.sql()), &err)
+                })?;
+            tracer
+                .record(tx, &m.filename, stmt, execution_time_ms, rows)
+                .await?;
+        }
+        tracer.finish_migration();
+    }
 
-    for m in migrations {
-        let version_str = m.version.to_string();
-        let existing_checksum = audit::get_applied_checksum(pool, &version_str)
-            .await
-            .map_err(|e| ExecutorError::DryRunFailed {
-                message: "applied checksum lookup failed".to_string(),
-                original_error: e.to_string(),
-            })?;
+    for m in repeatables {
+        repeatable::validate(tx, m, tracer).await?;
+        tracer.finish_migration();
+    }
 
-        if let Some(db_checksum) = existing_checksum {
-            if db_checksum == m.checksum {
-                skipped += 1;
-                continue;
-            }
+    Ok(())
+}
 
-            return Err(ExecutorError::DryRunValidationError {
-                migration_version: m.version,
+fn validate_plpgsql(
+    migrations: &[&Migration],
+    repeatables: &[&RepeatableMigration],
+) -> Option<ExecutorError> {
+    for m in migrations {
+        if let Some((message, line)) = plpgsql_error(&m.filename, &m.sql, &m.raw_sql, &m.source_map) {
+            return Some(ExecutorError::DryRunValidationError {
+                migration_version: m.version.clone(),
                 statement_ordinal: None,
-                sql_snippet: None,
-                message: format!(
-                    "checksum mismatch for version {} (db={}, fs={})",
-                    m.version, db_checksum, m.checksum
-                ),
+                sql_snippet: Some(sql_snippet(&line)),
+                message,
             });
         }
+    }
 
-        to_apply.push(m);
+    for m in repeatables {
+        if let Some((message, _)) = plpgsql_error(&m.filename, &m.sql, &m.raw_sql, &m.source_map) {
+            return Some(ExecutorError::RepeatableMigrationFailed {
+                name: m.name.clone(),
+                statement_ordinal: None,
+                message,
+            });
+        }
     }
 
-    Ok(PlannedMigrations { to_apply, skipped })
+    None
+}
+
+// Validates the rendered SQL but reports the position in the file as written.
+fn plpgsql_error(
+    filename: &str,
+    sql: &str,
+    raw_sql: &str,
+    source_map: &SourceMap,
+) -> Option<(String, String)> {
+    let diagnostic = vellum_sql_engine::check_plpgsql(sql).into_iter().next()?;
+    let location = vellum_sql_engine::locate(raw_sql, source_map.raw_offset(diagnostic.offset));
+    Some((
+        format!(
+            "{filename}:{}:{}: {}",
+            location.line, location.column, diagnostic.message
+        ),
+        location.line_text,
+    ))
 }
 
 fn map_validation_error(
-    migration_version: i64,
+    migration_version: &Version,
     statement_ordinal: Option<i32>,
     sql: Option<&str>,
     err: &ExecutorError,
 ) -> ExecutorError {
     ExecutorError::DryRunValidationError {
-        migration_version,
+        migration_version: migration_version.clone(),
         statement_ordinal,
         sql_snippet: sql.map(sql_snippet),
         message: err.to_string(),
@@ -170,3 +232,39 @@ fn sql_snippet(sql: &str) -> String {
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::validate_plpgsql;
+    use crate::error::ExecutorError;
+    use vellum_migration::{Migration, Placeholders, Version};
+
+    #[test]
+    fn plpgsql_errors_are_located_in_the_file_as_written() {
+        let raw = "create schema ${schema};\n\
+                   create function ${schema}.f() returns int language plpgsql as $$\n\
+                   begin\n  perform 1 from ${schema}.t; retrun 1;\nend\n$$;";
+        let mut placeholders = Placeholders::new();
+        placeholders.insert("schema", "application_with_a_long_name");
+        let (rendered, map) = placeholders.render_mapped(raw, "1_f.sql").unwrap();
+        let migration = Migration::new(
+            Version::from(1),
+            "f".to_string(),
+            "1_f.sql".to_string(),
+            "checksum".to_string(),
+            raw.to_string(),
+        )
+        .with_rendered_sql(rendered, map);
+
+        let Some(ExecutorError::DryRunValidationError {
+            message,
+            sql_snippet,
+            ..
+        }) = validate_plpgsql(&[&migration], &[])
+        else {
+            panic!("expected a validation error");
+        };
+        assert!(message.starts_with("1_f.sql:4:31: "), "{message}");
+        assert_eq!(sql_snippet.as_deref(), Some("perform 1 from ${schema}.t; retrun 1;"));
+    }
+}
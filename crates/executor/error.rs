@@ -1,9 +1,19 @@
 use core::fmt;
+use vellum_lock::LockHolder;
+use vellum_migration::{StaleReason, Version};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExecutorError {
+    ConnectionConfigInvalid {
+        message: String,
+    },
+    ConnectionFailed {
+        attempts: u32,
+        message: String,
+    },
     MigrationLockUnavailable {
         timeout_ms: u64,
+        holder: Option<Box<LockHolder>>,
     },
     LockAcquireFailed {
         message: String,
@@ -12,23 +22,33 @@ pub enum ExecutorError {
         message: String,
         original_error: Option<String>,
     },
+    MigrationLockLost {
+        message: String,
+        terminated_sessions: u64,
+    },
+    RunCancelled {
+        cancelled_sessions: u64,
+    },
+    RunReconciliationFailed {
+        message: String,
+    },
     MigrationAlreadyApplied {
-        version: i64,
+        version: Version,
     },
     ChecksumMismatch {
-        version: i64,
+        version: Version,
         expected: String,
         actual: String,
     },
     StatementExecutionFailed {
-        migration_version: i64,
+        migration_version: Version,
         statement_ordinal: i32,
         execution_time_ms: i32,
         statement: String,
         message: String,
     },
     TransactionCommitFailed {
-        migration_version: i64,
+        migration_version: Version,
         message: String,
     },
     RunTrackingFailed {
@@ -38,11 +58,11 @@ pub enum ExecutorError {
         original_error: Option<String>,
     },
     TransactionBeginFailed {
-        migration_version: i64,
+        migration_version: Version,
         message: String,
     },
     TransactionRollbackFailed {
-        migration_version: i64,
+        migration_version: Version,
         message: String,
         original_error: String,
     },
@@ -56,13 +76,39 @@ pub enum ExecutorError {
         original_error: Option<String>,
     },
     DryRunValidationError {
-        migration_version: i64,
+        migration_version: Version,
         statement_ordinal: Option<i32>,
         sql_snippet: Option<String>,
         message: String,
     },
     StatementParsingFailed {
-        migration_version: i64,
+        migration_version: Version,
+        message: String,
+    },
+    RepairReasonMissing,
+    RepairTargetNotFound {
+        version: Version,
+    },
+    BaselineVersionNotFound {
+        version: Version,
+    },
+    BaselineHistoryNotEmpty {
+        recorded: i64,
+    },
+    RepeatableMigrationFailed {
+        name: String,
+        statement_ordinal: Option<i32>,
+        message: String,
+    },
+    PlanStale {
+        reason: StaleReason,
+    },
+    IntrospectionFailed {
+        message: String,
+    },
+    ShadowDatabaseFailed {
+        operation: String,
+        database: String,
         message: String,
     },
 }
@@ -70,8 +116,18 @@ pub enum ExecutorError {
 impl fmt::Display for ExecutorError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ExecutorError::MigrationLockUnavailable { timeout_ms } => {
-                write!(f, "migration lock unavailable (timeout_ms={timeout_ms})")
+            ExecutorError::ConnectionConfigInvalid { message } => {
+                write!(f, "invalid connection settings: {message}")
+            }
+            ExecutorError::ConnectionFailed { attempts, message } => {
+                write!(f, "database connection failed after {attempts} attempt(s): {message}")
+            }
+            ExecutorError::MigrationLockUnavailable { timeout_ms, holder } => {
+                write!(f, "migration lock unavailable (timeout_ms={timeout_ms})")?;
+                match holder {
+                    Some(holder) => write!(f, "; held by {holder}"),
+                    None => Ok(()),
+                }
             }
             ExecutorError::LockAcquireFailed { message } => {
                 write!(f, "lock acquire failed: {message}")
@@ -86,6 +142,21 @@ impl fmt::Display for ExecutorError {
                     write!(f, "lock release failed: {message}")
                 }
             }
+            ExecutorError::MigrationLockLost {
+                message,
+                terminated_sessions,
+            } => {
+                write!(
+                    f,
+                    "migration lock lost: {message}; terminated_sessions={terminated_sessions}"
+                )
+            }
+            ExecutorError::RunCancelled { cancelled_sessions } => {
+                write!(f, "run cancelled: cancelled_sessions={cancelled_sessions}")
+            }
+            ExecutorError::RunReconciliationFailed { message } => {
+                write!(f, "run reconciliation failed: {message}")
+            }
             ExecutorError::MigrationAlreadyApplied { version } => {
                 write!(f, "migration already applied: version={version}")
             }
@@ -192,6 +263,42 @@ impl fmt::Display for ExecutorError {
                 f,
                 "statement parsing failed (version={migration_version}): {message}"
             ),
+            ExecutorError::RepairReasonMissing => write!(f, "repair reason must not be empty"),
+            ExecutorError::RepairTargetNotFound { version } => write!(
+                f,
+                "repair target not found: version {version} has no recorded migration"
+            ),
+            ExecutorError::BaselineVersionNotFound { version } => write!(
+                f,
+                "baseline version not found: no migration file has version {version}"
+            ),
+            ExecutorError::BaselineHistoryNotEmpty { recorded } => write!(
+                f,
+                "baseline refused: migration history already contains {recorded} records"
+            ),
+            ExecutorError::RepeatableMigrationFailed {
+                name,
+                statement_ordinal,
+                message,
+            } => match statement_ordinal {
+                Some(ordinal) => write!(
+                    f,
+                    "repeatable migration failed (name={name}, ordinal={ordinal}): {message}"
+                ),
+                None => write!(f, "repeatable migration failed (name={name}): {message}"),
+            },
+            ExecutorError::PlanStale { reason } => write!(f, "saved plan is stale: {reason}"),
+            ExecutorError::IntrospectionFailed { message } => {
+                write!(f, "schema introspection failed: {message}")
+            }
+            ExecutorError::ShadowDatabaseFailed {
+                operation,
+                database,
+                message,
+            } => write!(
+                f,
+                "shadow database {operation} failed for {database}: {message}"
+            ),
         }
     }
 }
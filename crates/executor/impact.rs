@@ -0,0 +1,356 @@
+use crate::error::ExecutorError;
+use crate::statement::{self, SqlStatement, StatementFailure};
+use sqlx::types::JsonValue;
+use std::collections::HashSet;
+use vellum_sql_engine::{LockLevel, RelationLock};
+
+const EXPLAIN_SAVEPOINT: &str = "vellum_explain";
+
+// Relations are always named `nspname.relname`, whatever the search_path, so
+// they compare equal to the statically predicted locks resolved below.
+const SQL_SELECT_HELD_LOCKS: &str = "
+SELECT COALESCE(n.nspname || '.' || c.relname, 'oid ' || l.relation::text), l.mode
+FROM pg_locks l
+LEFT JOIN pg_class c ON c.oid = l.relation
+LEFT JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE l.pid = pg_backend_pid()
+  AND l.locktype = 'relation'
+  AND l.granted
+  AND (
+    c.oid IS NULL
+    OR (
+      c.relkind NOT IN ('i', 'I')
+      AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+      AND n.nspname NOT LIKE 'pg_toast%'
+    )
+  )
+ORDER BY 1, 2
+";
+
+const SQL_RESOLVE_RELATIONS: &str = "
+SELECT n.nspname || '.' || c.relname
+FROM unnest($1::text[]) WITH ORDINALITY AS r(name, ordinal)
+LEFT JOIN pg_class c ON c.oid = to_regclass(r.name)
+LEFT JOIN pg_namespace n ON n.oid = c.relnamespace
+ORDER BY r.ordinal
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeldLock {
+    pub relation: String,
+    pub level: LockLevel,
+    pub held_ms: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementImpact {
+    pub filename: String,
+    pub ordinal: i32,
+    pub kind: String,
+    pub execution_time_ms: i32,
+    pub locks: Vec<HeldLock>,
+    pub rows: Option<RowEstimate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowEstimate {
+    pub analyzed: bool,
+    pub rows: i64,
+    pub plan: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanNode {
+    pub depth: usize,
+    pub description: String,
+    pub rows: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LockImpact {
+    pub statements: Vec<StatementImpact>,
+}
+
+impl LockImpact {
+    pub fn total_time_ms(&self) -> i64 {
+        self.statements
+            .iter()
+            .map(|s| i64::from(s.execution_time_ms))
+            .sum()
+    }
+
+    pub fn locks(&self) -> impl Iterator<Item = (&StatementImpact, &HeldLock)> {
+        self.statements
+            .iter()
+            .flat_map(|s| s.locks.iter().map(move |lock| (s, lock)))
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ImpactTracer {
+    explain_analyze: bool,
+    held: HashSet<(String, LockLevel)>,
+    held_before_migration: HashSet<(String, LockLevel)>,
+    taken_in_migration: HashSet<(String, LockLevel)>,
+    migration_start: usize,
+    impact: LockImpact,
+}
+
+impl ImpactTracer {
+    pub(crate) fn new(explain_analyze: bool) -> Self {
+        Self {
+            explain_analyze,
+            ..Self::default()
+        }
+    }
+
+    // EXPLAIN ANALYZE executes the statement, so it replaces the plain
+    // execution; a plain EXPLAIN runs first and never fails the dry-run.
+    pub(crate) async fn execute(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        filename: &str,
+        stmt: &SqlStatement,
+    ) -> Result<(i32, Option<RowEstimate>), StatementFailure> {
+        let explainable = vellum_sql_engine::analyze_statement(stmt.sql(), Some(filename))
+            .is_ok_and(|analysis| analysis.explainable);
+        if !explainable {
+            return Ok((statement::run_statement(tx, stmt).await?, None));
+        }
+
+        if self.explain_analyze {
+            let started = std::time::Instant::now();
+            let result = explain(tx, stmt.sql(), true).await;
+            let execution_time_ms = statement::duration_ms(started.elapsed());
+            return match result {
+                Ok(estimate) => Ok((execution_time_ms, Some(estimate))),
+                Err(e) => Err(StatementFailure {
+                    execution_time_ms,
+                    message: e.to_string(),
+                }),
+            };
+        }
+
+        let estimate = estimate(tx, stmt.sql()).await.map_err(|e| StatementFailure {
+            execution_time_ms: 0,
+            message: e.to_string(),
+        })?;
+        let execution_time_ms = statement::run_statement(tx, stmt).await?;
+        Ok((execution_time_ms, estimate))
+    }
+
+    pub(crate) async fn record(
+        &mut self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        filename: &str,
+        stmt: &SqlStatement,
+        execution_time_ms: i32,
+        rows: Option<RowEstimate>,
+    ) -> Result<(), ExecutorError> {
+        let held_locks: Vec<(String, String)> = sqlx::query_as(SQL_SELECT_HELD_LOCKS)
+            .fetch_all(&mut **tx)
+            .await
+            .map_err(|e| ExecutorError::DryRunTransactionError {
+                operation: "lock_report".to_string(),
+                message: e.to_string(),
+                original_error: None,
+            })?;
+
+        let mut acquired: Vec<(String, LockLevel)> = held_locks
+            .into_iter()
+            .filter_map(|(relation, mode)| Some((relation, LockLevel::from_pg_mode(&mode)?)))
+            .filter(|key| self.held.insert(key.clone()))
+            .collect();
+
+        let analysis = vellum_sql_engine::analyze_statement(stmt.sql(), Some(filename)).ok();
+        let predicted = match &analysis {
+            Some(analysis) => resolve_relations(tx, &analysis.locks).await?,
+            None => Vec::new(),
+        };
+
+        // The dry-run shares one transaction across migrations, so a lock an
+        // earlier migration already holds never shows up as new in pg_locks.
+        // Fall back to the statically predicted locks for those.
+        for key in predicted {
+            if self.held_before_migration.contains(&key)
+                && !self.taken_in_migration.contains(&key)
+                && !acquired.contains(&key)
+            {
+                acquired.push(key);
+            }
+        }
+
+        let locks = acquired
+            .into_iter()
+            .filter(|key| self.taken_in_migration.insert(key.clone()))
+            .map(|(relation, level)| HeldLock {
+                relation,
+                level,
+                held_ms: 0,
+            })
+            .collect();
+
+        let kind = analysis
+            .map(|analysis| analysis.kind)
+            .unwrap_or_else(|| statement::statement_kind(stmt.sql()));
+
+        self.impact.statements.push(StatementImpact {
+            filename: filename.to_string(),
+            ordinal: stmt.ordinal(),
+            kind,
+            execution_time_ms,
+            locks,
+            rows,
+        });
+
+        Ok(())
+    }
+
+    // A real run commits each migration separately, so a lock taken by a
+    // statement is held until the end of that migration's transaction.
+    pub(crate) fn finish_migration(&mut self) {
+        let statements = &mut self.impact.statements[self.migration_start..];
+        let mut remaining_ms = 0i32;
+        for stmt in statements.iter_mut().rev() {
+            remaining_ms = remaining_ms.saturating_add(stmt.execution_time_ms);
+            for lock in &mut stmt.locks {
+                lock.held_ms = remaining_ms;
+            }
+        }
+        self.migration_start = self.impact.statements.len();
+        self.held_before_migration = self.held.clone();
+        self.taken_in_migration.clear();
+    }
+
+    pub(crate) fn into_impact(self) -> LockImpact {
+        self.impact
+    }
+}
+
+// Resolves predicted lock targets the way the statement did, through the
+// current search_path. Relations that no longer exist keep their written name.
+async fn resolve_relations(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    locks: &[RelationLock],
+) -> Result<Vec<(String, LockLevel)>, ExecutorError> {
+    if locks.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let names: Vec<String> = locks.iter().map(|lock| quote_relation(&lock.relation)).collect();
+    let resolved: Vec<Option<String>> = sqlx::query_scalar(SQL_RESOLVE_RELATIONS)
+        .bind(&names)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| ExecutorError::DryRunTransactionError {
+            operation: "lock_report".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })?;
+
+    Ok(locks
+        .iter()
+        .zip(resolved)
+        .map(|(lock, name)| (name.unwrap_or_else(|| lock.relation.clone()), lock.level))
+        .collect())
+}
+
+// Predicted names are already case-folded by the parser, so each part is
+// quoted to keep to_regclass from folding it again.
+fn quote_relation(relation: &str) -> String {
+    let quote = |part: &str| format!("\"{}\"", part.replace('"', "\"\""));
+    match relation.split_once('.') {
+        Some((schema, name)) => format!("{}.{}", quote(schema), quote(name)),
+        None => quote(relation),
+    }
+}
+
+async fn estimate(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    sql: &str,
+) -> Result<Option<RowEstimate>, sqlx::Error> {
+    sqlx::query(&format!("SAVEPOINT {EXPLAIN_SAVEPOINT}"))
+        .execute(&mut **tx)
+        .await?;
+
+    let estimate = explain(tx, sql, false).await.ok();
+    let finish = if estimate.is_some() {
+        format!("RELEASE SAVEPOINT {EXPLAIN_SAVEPOINT}")
+    } else {
+        format!("ROLLBACK TO SAVEPOINT {EXPLAIN_SAVEPOINT}")
+    };
+    sqlx::query(&finish).execute(&mut **tx).await?;
+
+    Ok(estimate)
+}
+
+async fn explain(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    sql: &str,
+    analyze: bool,
+) -> Result<RowEstimate, sqlx::Error> {
+    let options = if analyze { "ANALYZE, FORMAT JSON" } else { "FORMAT JSON" };
+    let sql = sql.trim_end().trim_end_matches(';');
+    let (output,): (JsonValue,) = sqlx::query_as(&format!("EXPLAIN ({options}) {sql}"))
+        .fetch_one(&mut **tx)
+        .await?;
+
+    let root = output
+        .get(0)
+        .and_then(|entry| entry.get("Plan"))
+        .ok_or_else(|| sqlx::Error::Protocol("EXPLAIN returned no plan".to_string()))?;
+
+    let mut plan = Vec::new();
+    flatten_plan(root, 0, analyze, &mut plan);
+
+    // ModifyTable only reports rows it returns, so the affected rows are the
+    // rows fed into it by its input.
+    let rows = if root.get("Node Type").and_then(JsonValue::as_str) == Some("ModifyTable") {
+        plan.iter().find(|node| node.depth == 1).map(|node| node.rows)
+    } else {
+        plan.first().map(|node| node.rows)
+    };
+
+    Ok(RowEstimate {
+        analyzed: analyze,
+        rows: rows.unwrap_or(0),
+        plan,
+    })
+}
+
+fn flatten_plan(node: &JsonValue, depth: usize, analyze: bool, out: &mut Vec<PlanNode>) {
+    let field = |name: &str| node.get(name).and_then(JsonValue::as_str);
+    let number = |name: &str| node.get(name).and_then(JsonValue::as_f64).unwrap_or(0.0);
+
+    let node_type = field("Node Type").unwrap_or("Unknown");
+    let mut description = match (node_type, field("Operation")) {
+        ("ModifyTable", Some(operation)) => operation.to_string(),
+        _ => node_type.to_string(),
+    };
+    if let Some(relation) = field("Relation Name") {
+        description.push_str(" on ");
+        description.push_str(relation);
+    }
+    if let Some(index) = field("Index Name") {
+        description.push_str(" using ");
+        description.push_str(index);
+    }
+
+    let rows = if analyze {
+        number("Actual Rows") * number("Actual Loops").max(1.0)
+    } else {
+        number("Plan Rows")
+    };
+
+    out.push(PlanNode {
+        depth,
+        description,
+        rows: rows.round() as i64,
+    });
+
+    if let Some(children) = node.get("Plans").and_then(JsonValue::as_array) {
+        for child in children {
+            flatten_plan(child, depth + 1, analyze, out);
+        }
+    }
+}
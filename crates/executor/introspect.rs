@@ -0,0 +1,245 @@
+use crate::error::ExecutorError;
+use std::collections::BTreeMap;
+use vellum_schema::{Column, Constraint, ConstraintKind, EnumType, Index, Schema, Table};
+
+// Everything vellum itself, PostgreSQL or an extension owns is left out, so a
+// fresh database with only vellum's bookkeeping introspects as empty.
+const USER_NAMESPACE: &str = "
+  n.nspname NOT IN ('information_schema', 'vellum')
+  AND n.nspname NOT LIKE 'pg\\_%'
+";
+
+const SQL_SELECT_SCHEMAS: &str = "
+SELECT n.nspname::text
+FROM pg_namespace n
+WHERE {namespace}
+  AND NOT EXISTS (
+    SELECT 1 FROM pg_depend d
+    WHERE d.classid = 'pg_namespace'::regclass AND d.objid = n.oid AND d.deptype = 'e'
+  )
+";
+
+const SQL_SELECT_TABLES: &str = "
+SELECT n.nspname::text, c.relname::text
+FROM pg_class c
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE {namespace}
+  AND c.relkind IN ('r', 'p')
+  AND NOT EXISTS (
+    SELECT 1 FROM pg_depend d
+    WHERE d.classid = 'pg_class'::regclass AND d.objid = c.oid AND d.deptype = 'e'
+  )
+";
+
+const SQL_SELECT_COLUMNS: &str = "
+SELECT n.nspname::text, c.relname::text, a.attname::text,
+       format_type(a.atttypid, a.atttypmod), NOT a.attnotnull,
+       pg_get_expr(d.adbin, d.adrelid)
+FROM pg_attribute a
+JOIN pg_class c ON c.oid = a.attrelid
+JOIN pg_namespace n ON n.oid = c.relnamespace
+LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+WHERE {namespace}
+  AND c.relkind IN ('r', 'p')
+  AND a.attnum > 0
+  AND NOT a.attisdropped
+";
+
+const SQL_SELECT_INDEXES: &str = "
+SELECT n.nspname::text, c.relname::text, i.relname::text,
+       ARRAY(
+         SELECT pg_get_indexdef(x.indexrelid, k, true)
+         FROM generate_series(1, x.indnkeyatts::int) AS k
+         ORDER BY k
+       ),
+       x.indisunique, am.amname::text
+FROM pg_index x
+JOIN pg_class i ON i.oid = x.indexrelid
+JOIN pg_class c ON c.oid = x.indrelid
+JOIN pg_namespace n ON n.oid = c.relnamespace
+JOIN pg_am am ON am.oid = i.relam
+WHERE {namespace}
+  AND c.relkind IN ('r', 'p')
+";
+
+const SQL_SELECT_CONSTRAINTS: &str = "
+SELECT n.nspname::text, c.relname::text, con.conname::text, con.contype::text,
+       ARRAY(
+         SELECT a.attname::text
+         FROM unnest(con.conkey) WITH ORDINALITY AS k(attnum, ord)
+         JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+         ORDER BY k.ord
+       ),
+       CASE WHEN con.confrelid = 0 THEN NULL ELSE con.confrelid::regclass::text END,
+       ARRAY(
+         SELECT a.attname::text
+         FROM unnest(con.confkey) WITH ORDINALITY AS k(attnum, ord)
+         JOIN pg_attribute a ON a.attrelid = con.confrelid AND a.attnum = k.attnum
+         ORDER BY k.ord
+       ),
+       pg_get_constraintdef(con.oid, true)
+FROM pg_constraint con
+JOIN pg_class c ON c.oid = con.conrelid
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE {namespace}
+  AND con.contype IN ('p', 'u', 'f', 'c')
+";
+
+const SQL_SELECT_ENUMS: &str = "
+SELECT n.nspname::text, t.typname::text,
+       ARRAY(
+         SELECT e.enumlabel::text FROM pg_enum e
+         WHERE e.enumtypid = t.oid
+         ORDER BY e.enumsortorder
+       )
+FROM pg_type t
+JOIN pg_namespace n ON n.oid = t.typnamespace
+WHERE {namespace}
+  AND t.typtype = 'e'
+  AND NOT EXISTS (
+    SELECT 1 FROM pg_depend d
+    WHERE d.classid = 'pg_type'::regclass AND d.objid = t.oid AND d.deptype = 'e'
+  )
+";
+
+type ColumnRow = (String, String, String, String, bool, Option<String>);
+type IndexRow = (String, String, String, Vec<String>, bool, String);
+type ConstraintRow = (
+    String,
+    String,
+    String,
+    String,
+    Vec<String>,
+    Option<String>,
+    Vec<String>,
+    String,
+);
+
+pub async fn introspect(pool: &sqlx::PgPool) -> Result<BTreeMap<String, Schema>, ExecutorError> {
+    select_schemas(pool)
+        .await
+        .map_err(|e| ExecutorError::IntrospectionFailed {
+            message: e.to_string(),
+        })
+}
+
+async fn select_schemas(pool: &sqlx::PgPool) -> Result<BTreeMap<String, Schema>, sqlx::Error> {
+    let query = |sql: &str| sql.replace("{namespace}", USER_NAMESPACE);
+
+    let names: Vec<(String,)> = sqlx::query_as(&query(SQL_SELECT_SCHEMAS))
+        .fetch_all(pool)
+        .await?;
+    let mut schemas: BTreeMap<String, Schema> = names
+        .into_iter()
+        .map(|(name,)| {
+            let schema = Schema {
+                name: name.clone(),
+                tables: BTreeMap::new(),
+                enum_types: BTreeMap::new(),
+            };
+            (name, schema)
+        })
+        .collect();
+
+    let tables: Vec<(String, String)> = sqlx::query_as(&query(SQL_SELECT_TABLES))
+        .fetch_all(pool)
+        .await?;
+    for (schema, name) in tables {
+        if let Some(schema) = schemas.get_mut(&schema) {
+            let table = Table {
+                name: name.clone(),
+                columns: BTreeMap::new(),
+                indexes: BTreeMap::new(),
+                constraints: BTreeMap::new(),
+            };
+            schema.tables.insert(name, table);
+        }
+    }
+
+    let columns: Vec<ColumnRow> = sqlx::query_as(&query(SQL_SELECT_COLUMNS))
+        .fetch_all(pool)
+        .await?;
+    for (schema, table, name, data_type, nullable, default) in columns {
+        let Some(table) = find_table(&mut schemas, &schema, &table) else {
+            continue;
+        };
+        table.columns.insert(
+            name.clone(),
+            Column {
+                name,
+                data_type,
+                nullable,
+                default,
+            },
+        );
+    }
+
+    let indexes: Vec<IndexRow> = sqlx::query_as(&query(SQL_SELECT_INDEXES))
+        .fetch_all(pool)
+        .await?;
+    for (schema, table, name, columns, unique, method) in indexes {
+        let Some(table) = find_table(&mut schemas, &schema, &table) else {
+            continue;
+        };
+        table.indexes.insert(
+            name.clone(),
+            Index {
+                name,
+                columns,
+                unique,
+                method,
+            },
+        );
+    }
+
+    let constraints: Vec<ConstraintRow> = sqlx::query_as(&query(SQL_SELECT_CONSTRAINTS))
+        .fetch_all(pool)
+        .await?;
+    for (schema, table, name, contype, columns, referenced_table, referenced_columns, definition) in
+        constraints
+    {
+        let kind = match contype.as_str() {
+            "p" => ConstraintKind::PrimaryKey { columns },
+            "u" => ConstraintKind::Unique { columns },
+            "f" => ConstraintKind::ForeignKey {
+                columns,
+                referenced_table: referenced_table.unwrap_or_default(),
+                referenced_columns,
+            },
+            _ => ConstraintKind::Check {
+                expression: definition
+                    .strip_prefix("CHECK ")
+                    .unwrap_or(&definition)
+                    .to_string(),
+            },
+        };
+
+        let Some(table) = find_table(&mut schemas, &schema, &table) else {
+            continue;
+        };
+        table
+            .constraints
+            .insert(name.clone(), Constraint { name, kind });
+    }
+
+    let enums: Vec<(String, String, Vec<String>)> = sqlx::query_as(&query(SQL_SELECT_ENUMS))
+        .fetch_all(pool)
+        .await?;
+    for (schema, name, values) in enums {
+        if let Some(schema) = schemas.get_mut(&schema) {
+            schema
+                .enum_types
+                .insert(name.clone(), EnumType { name, values });
+        }
+    }
+
+    Ok(schemas)
+}
+
+fn find_table<'a>(
+    schemas: &'a mut BTreeMap<String, Schema>,
+    schema: &str,
+    table: &str,
+) -> Option<&'a mut Table> {
+    schemas.get_mut(schema)?.tables.get_mut(table)
+}
@@ -1,11 +1,30 @@
 pub mod audit;
+pub mod baseline;
+pub mod connection;
 pub mod dry_run;
 pub mod error;
+pub mod impact;
+pub mod introspect;
 pub mod mode;
+pub mod planner;
+pub mod reconcile;
+pub mod repair;
+pub mod repeatable;
 pub mod runner;
+pub mod shadow;
 pub mod statement;
 pub mod transaction;
 
+pub use baseline::BaselineReport;
+pub use connection::{
+    ConnectionManager, ConnectionSettings, DEFAULT_APPLICATION_NAME, DEFAULT_POOL_SIZE,
+};
 pub use error::ExecutorError;
+pub use impact::{HeldLock, LockImpact, PlanNode, RowEstimate, StatementImpact};
+pub use introspect::introspect;
 pub use mode::ExecutionMode;
+pub use reconcile::{reconcile, AbandonedRun};
+pub use repair::{RepairAction, RepairOutcome, RepairReport};
 pub use runner::{RunReport, Runner};
+pub use shadow::ShadowDatabase;
+pub use vellum_lock::{LockScope, LockSettings, LockWait, LockWaiting, DEFAULT_LOCK_TIMEOUT};
@@ -0,0 +1,60 @@
+use crate::audit;
+use crate::error::ExecutorError;
+use std::collections::HashMap;
+use vellum_migration::{
+    Migration, MigrationPlan, RepeatableMigration, StepKind, StepReason, Version,
+};
+
+pub async fn compute(
+    pool: &sqlx::PgPool,
+    migrations: &[Migration],
+    repeatables: &[RepeatableMigration],
+) -> Result<MigrationPlan, ExecutorError> {
+    let history = audit::select_applied_history(pool).await?;
+    Ok(MigrationPlan::build(&history, migrations, repeatables))
+}
+
+pub(crate) struct Checksums<'a> {
+    pub version: &'a Version,
+    pub expected: &'a str,
+    pub actual: &'a str,
+}
+
+pub(crate) fn checksum_mismatch(plan: &MigrationPlan) -> Option<Checksums<'_>> {
+    let step = plan.first_blocked()?;
+    match (&step.version, &step.reason) {
+        (Some(version), StepReason::ChecksumMismatch { expected, actual }) => Some(Checksums {
+            version,
+            expected,
+            actual,
+        }),
+        _ => None,
+    }
+}
+
+pub fn resolve<'a>(
+    plan: &MigrationPlan,
+    migrations: &'a [Migration],
+    repeatables: &'a [RepeatableMigration],
+) -> (Vec<&'a Migration>, Vec<&'a RepeatableMigration>) {
+    let by_version: HashMap<&Version, &Migration> =
+        migrations.iter().map(|m| (&m.version, m)).collect();
+    let by_name: HashMap<&str, &RepeatableMigration> =
+        repeatables.iter().map(|m| (m.name.as_str(), m)).collect();
+
+    let mut versioned = Vec::new();
+    let mut repeatable = Vec::new();
+    for step in plan.to_apply() {
+        match (step.kind, &step.version) {
+            (StepKind::Versioned, Some(version)) => {
+                versioned.extend(by_version.get(version).copied());
+            }
+            (StepKind::Repeatable, _) => {
+                repeatable.extend(by_name.get(step.name.as_str()).copied());
+            }
+            _ => {}
+        }
+    }
+
+    (versioned, repeatable)
+}
@@ -0,0 +1,63 @@
+use crate::error::ExecutorError;
+use uuid::Uuid;
+
+// A run is abandoned once the lock session that owned it is gone. Sessions are
+// matched on pid and start time so a recycled pid cannot keep a dead run
+// alive. Runs recorded before owners were tracked have no pid; they are only
+// abandoned if they started before the caller's lock was granted ($1), since
+// any run that old cannot still be holding it.
+const SQL_MARK_ABANDONED: &str = "
+UPDATE vellum.vellum_runs r
+SET status = 'abandoned',
+    finished_at = now()
+WHERE r.status = 'running'
+  AND CASE
+    WHEN r.owner_pid IS NULL THEN r.started_at < $1::timestamptz
+    ELSE NOT EXISTS (
+      SELECT 1 FROM pg_stat_activity a
+      WHERE a.pid = r.owner_pid
+        AND (a.backend_start IS NULL OR a.backend_start <= r.started_at)
+    )
+  END
+RETURNING r.id, r.mode, to_char(r.started_at, 'YYYY-MM-DD HH24:MI:SS TZ'), r.owner_pid, r.client_host
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbandonedRun {
+    pub run_id: Uuid,
+    pub mode: String,
+    pub started_at: String,
+    pub owner_pid: Option<i32>,
+    pub client_host: Option<String>,
+}
+
+type AbandonedRow = (Uuid, String, String, Option<i32>, Option<String>);
+
+// Must be called while holding the migration lock; `lock_acquired_at` is
+// `AdvisoryLockGuard::acquired_at`.
+pub async fn reconcile(
+    pool: &sqlx::PgPool,
+    lock_acquired_at: &str,
+) -> Result<Vec<AbandonedRun>, ExecutorError> {
+    let rows: Vec<AbandonedRow> = sqlx::query_as(SQL_MARK_ABANDONED)
+        .bind(lock_acquired_at)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| ExecutorError::RunReconciliationFailed {
+            message: e.to_string(),
+        })?;
+
+    let mut abandoned: Vec<AbandonedRun> = rows
+        .into_iter()
+        .map(|(run_id, mode, started_at, owner_pid, client_host)| AbandonedRun {
+            run_id,
+            mode,
+            started_at,
+            owner_pid,
+            client_host,
+        })
+        .collect();
+    abandoned.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+
+    Ok(abandoned)
+}
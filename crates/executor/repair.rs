@@ -0,0 +1,166 @@
+use crate::audit;
+use crate::error::ExecutorError;
+use std::collections::HashMap;
+use uuid::Uuid;
+use vellum_migration::{Migration, Version};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairAction {
+    ChecksumUpdated,
+    FailedRemoved,
+    OrphanRemoved,
+    Unchanged,
+}
+
+impl RepairAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RepairAction::ChecksumUpdated => "checksum_updated",
+            RepairAction::FailedRemoved => "failed_removed",
+            RepairAction::OrphanRemoved => "orphan_removed",
+            RepairAction::Unchanged => "unchanged",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairOutcome {
+    pub version: Version,
+    pub action: RepairAction,
+    pub old_checksum: Option<String>,
+    pub new_checksum: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairReport {
+    pub run_id: Uuid,
+    pub outcomes: Vec<RepairOutcome>,
+}
+
+pub async fn run(
+    pool: &sqlx::PgPool,
+    run_id: Uuid,
+    owner_pid: i32,
+    vellum_version: &str,
+    migrations: &[Migration],
+    versions: &[Version],
+    reason: &str,
+) -> Result<RepairReport, ExecutorError> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(ExecutorError::RepairReasonMissing);
+    }
+
+    audit::insert_run_with_mode(pool, run_id, owner_pid, "repair", vellum_version).await?;
+
+    match repair_versions(pool, run_id, migrations, versions, reason).await {
+        Ok(outcomes) => {
+            audit::mark_run_success(pool, run_id).await?;
+            Ok(RepairReport { run_id, outcomes })
+        }
+        Err(err) => {
+            let _ = audit::mark_run_failed(pool, run_id, &err).await;
+            Err(err)
+        }
+    }
+}
+
+async fn repair_versions(
+    pool: &sqlx::PgPool,
+    run_id: Uuid,
+    migrations: &[Migration],
+    versions: &[Version],
+    reason: &str,
+) -> Result<Vec<RepairOutcome>, ExecutorError> {
+    let by_version: HashMap<&Version, &Migration> =
+        migrations.iter().map(|m| (&m.version, m)).collect();
+
+    let mut targets = versions.to_vec();
+    targets.sort();
+    targets.dedup();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| ExecutorError::RunTrackingFailed {
+            run_id: run_id.to_string(),
+            operation: "begin_repair".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })?;
+
+    let mut outcomes = Vec::with_capacity(targets.len());
+
+    for version in targets {
+        let version_str = version.to_string();
+        let Some(record) = audit::select_migration_record(&mut tx, &version_str).await? else {
+            return Err(ExecutorError::RepairTargetNotFound { version });
+        };
+
+        let outcome = match by_version.get(&version) {
+            None => {
+                audit::delete_migration(&mut tx, record.id).await?;
+                RepairOutcome {
+                    version,
+                    action: if record.success {
+                        RepairAction::OrphanRemoved
+                    } else {
+                        RepairAction::FailedRemoved
+                    },
+                    old_checksum: Some(record.checksum),
+                    new_checksum: None,
+                }
+            }
+            Some(_) if !record.success => {
+                audit::delete_migration(&mut tx, record.id).await?;
+                RepairOutcome {
+                    version,
+                    action: RepairAction::FailedRemoved,
+                    old_checksum: Some(record.checksum),
+                    new_checksum: None,
+                }
+            }
+            Some(m) if !m.matches_checksum(&record.checksum) => {
+                audit::update_migration_checksum(&mut tx, record.id, &m.checksum).await?;
+                RepairOutcome {
+                    version,
+                    action: RepairAction::ChecksumUpdated,
+                    old_checksum: Some(record.checksum),
+                    new_checksum: Some(m.checksum.clone()),
+                }
+            }
+            Some(m) => RepairOutcome {
+                version,
+                action: RepairAction::Unchanged,
+                old_checksum: Some(record.checksum),
+                new_checksum: Some(m.checksum.clone()),
+            },
+        };
+
+        if outcome.action != RepairAction::Unchanged {
+            audit::insert_repair(
+                &mut tx,
+                run_id,
+                &version_str,
+                outcome.action.as_str(),
+                outcome.old_checksum.as_deref(),
+                outcome.new_checksum.as_deref(),
+                reason,
+            )
+            .await?;
+        }
+
+        outcomes.push(outcome);
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| ExecutorError::RunTrackingFailed {
+            run_id: run_id.to_string(),
+            operation: "commit_repair".to_string(),
+            message: e.to_string(),
+            original_error: None,
+        })?;
+
+    Ok(outcomes)
+}
@@ -0,0 +1,83 @@
+use crate::audit;
+use crate::error::ExecutorError;
+use crate::impact::ImpactTracer;
+use crate::statement::{self, SqlStatement};
+use crate::transaction;
+use uuid::Uuid;
+use vellum_migration::RepeatableMigration;
+
+pub async fn execute(
+    pool: &sqlx::PgPool,
+    run_id: Uuid,
+    migration: &RepeatableMigration,
+) -> Result<(), ExecutorError> {
+    let statements = parse(migration)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| failed(migration, None, format!("transaction begin failed: {e}")))?;
+    transaction::tag(&mut tx, run_id)
+        .await
+        .map_err(|e| failed(migration, None, format!("transaction begin failed: {e}")))?;
+
+    let migration_id = audit::insert_repeatable_migration(&mut tx, run_id, migration).await?;
+    let migration_started = std::time::Instant::now();
+
+    for stmt in &statements {
+        match statement::run_statement(&mut tx, stmt).await {
+            Ok(execution_time_ms) => {
+                audit::insert_statement(&mut tx, migration_id, stmt, execution_time_ms, true, None)
+                    .await?;
+            }
+            Err(failure) => {
+                let err = failed(migration, Some(stmt.ordinal()), failure.message);
+                let _ = tx.rollback().await;
+                return Err(err);
+            }
+        }
+    }
+
+    let migration_elapsed_ms = statement::duration_ms(migration_started.elapsed());
+    audit::mark_migration_success(&mut tx, migration_id, migration_elapsed_ms).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| failed(migration, None, format!("transaction commit failed: {e}")))
+}
+
+pub(crate) async fn validate(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    migration: &RepeatableMigration,
+    tracer: &mut ImpactTracer,
+) -> Result<(), ExecutorError> {
+    for stmt in &parse(migration)? {
+        match tracer.execute(tx, &migration.filename, stmt).await {
+            Ok((execution_time_ms, rows)) => {
+                tracer
+                    .record(tx, &migration.filename, stmt, execution_time_ms, rows)
+                    .await?
+            }
+            Err(failure) => return Err(failed(migration, Some(stmt.ordinal()), failure.message)),
+        }
+    }
+
+    Ok(())
+}
+
+fn parse(migration: &RepeatableMigration) -> Result<Vec<SqlStatement>, ExecutorError> {
+    statement::parse_statements(&migration.sql, Some(&migration.filename))
+        .map_err(|message| failed(migration, None, message))
+}
+
+fn failed(
+    migration: &RepeatableMigration,
+    statement_ordinal: Option<i32>,
+    message: String,
+) -> ExecutorError {
+    ExecutorError::RepeatableMigrationFailed {
+        name: migration.name.clone(),
+        statement_ordinal,
+        message,
+    }
+}
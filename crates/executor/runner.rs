@@ -1,138 +1,485 @@
 use crate::audit;
+use crate::baseline::{self, BaselineReport};
+use crate::connection::{ConnectionManager, ConnectionSettings};
 use crate::error::ExecutorError;
+use crate::impact::LockImpact;
 use crate::mode::ExecutionMode;
 use crate::dry_run;
+use crate::planner;
+use crate::reconcile::{self, AbandonedRun};
+use crate::repair::{self, RepairReport};
+use crate::repeatable;
 use crate::statement;
 use crate::transaction;
+use core::future::Future;
 use core::time::Duration;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::time::{sleep, Instant};
 use uuid::Uuid;
-use vellum_lock::{AdvisoryLockGuard, LockError};
-use vellum_migration::Migration;
+use vellum_lock::{AdvisoryLockGuard, LockError, LockOwner, LockSettings, LockWait, LockWaiting};
+use vellum_migration::{
+    Migration, MigrationPlan, RepeatableMigration, SavedPlan, StepAction, StepKind, Version,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RunReport {
     pub run_id: Uuid,
+    pub plan: MigrationPlan,
     pub applied: usize,
     pub skipped: usize,
+    pub repeatable_applied: usize,
+    pub repeatable_skipped: usize,
+    pub lock_impact: Option<LockImpact>,
+}
+
+impl RunReport {
+    pub(crate) fn from_plan(run_id: Uuid, plan: MigrationPlan) -> Self {
+        Self {
+            run_id,
+            applied: plan.count(StepKind::Versioned, StepAction::Apply),
+            skipped: plan.count(StepKind::Versioned, StepAction::Skip),
+            repeatable_applied: plan.count(StepKind::Repeatable, StepAction::Apply),
+            repeatable_skipped: plan.count(StepKind::Repeatable, StepAction::Skip),
+            lock_impact: None,
+            plan,
+        }
+    }
 }
 
 async fn run_apply(
     pool: &sqlx::PgPool,
+    run_id: Uuid,
+    owner_pid: i32,
     vellum_version: &str,
     migrations: &[Migration],
+    repeatables: &[RepeatableMigration],
+    saved: Option<&SavedPlan>,
 ) -> Result<RunReport, ExecutorError> {
-    let run_id = audit::insert_run(pool, vellum_version).await?;
+    audit::insert_run(pool, run_id, owner_pid, vellum_version).await?;
 
-    let mut applied = 0usize;
-    let mut skipped = 0usize;
+    let history = match audit::select_applied_history(pool).await {
+        Ok(history) => history,
+        Err(err) => {
+            let _ = audit::mark_run_failed(pool, run_id, &err).await;
+            return Err(err);
+        }
+    };
+    let plan = MigrationPlan::build(&history, migrations, repeatables);
 
-    for m in migrations {
-        let version_str = m.version.to_string();
-        let existing_checksum = audit::get_applied_checksum(pool, &version_str).await?;
+    if let Some(saved) = saved {
+        let checked = match audit::select_database_identity(pool).await {
+            Ok(database) => saved
+                .check(&database, &history, &plan)
+                .map_err(|reason| ExecutorError::PlanStale { reason }),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = checked {
+            let _ = audit::mark_run_failed(pool, run_id, &err).await;
+            return Err(err);
+        }
+    }
 
-        if let Some(db_checksum) = existing_checksum {
-            if db_checksum == m.checksum {
-                skipped += 1;
-                continue;
-            }
+    if let Some(mismatch) = planner::checksum_mismatch(&plan) {
+        let err = ExecutorError::ChecksumMismatch {
+            version: mismatch.version.clone(),
+            expected: mismatch.expected.to_string(),
+            actual: mismatch.actual.to_string(),
+        };
 
-            let err = ExecutorError::ChecksumMismatch {
-                version: m.version,
-                expected: db_checksum,
-                actual: m.checksum.clone(),
-            };
+        let _ = audit::mark_run_failed(pool, run_id, &err).await;
+        return Err(err);
+    }
+
+    let (to_apply, repeatables_to_apply) = planner::resolve(&plan, migrations, repeatables);
 
+    for m in to_apply {
+        if let Err(err) = execute_one(pool, run_id, m).await {
             let _ = audit::mark_run_failed(pool, run_id, &err).await;
             return Err(err);
         }
+    }
 
-        match execute_one(pool, run_id, m).await {
-            Ok(()) => {
-                applied += 1;
-            }
-            Err(err) => {
-                let _ = audit::mark_run_failed(pool, run_id, &err).await;
-                return Err(err);
-            }
+    for m in repeatables_to_apply {
+        if let Err(err) = repeatable::execute(pool, run_id, m).await {
+            let _ = audit::mark_run_failed(pool, run_id, &err).await;
+            return Err(err);
         }
     }
 
     audit::mark_run_success(pool, run_id).await?;
 
-    Ok(RunReport {
-        run_id,
-        applied,
-        skipped,
-    })
+    Ok(RunReport::from_plan(run_id, plan))
+}
+
+type LockListener = Arc<dyn Fn(&LockWaiting) + Send + Sync>;
+type ReconcileListener = Arc<dyn Fn(&[AbandonedRun]) + Send + Sync>;
+
+// How long a cancelled run waits for its sessions to roll back on their own
+// before terminating them.
+const CANCEL_GRACE: Duration = Duration::from_secs(5);
+
+enum Interruption {
+    LockLost(LockError),
+    Cancelled,
 }
 
 #[derive(Clone)]
 pub struct Runner {
     pool: sqlx::PgPool,
     vellum_version: String,
-    database_url: String,
+    connection_settings: ConnectionSettings,
+    lock_settings: LockSettings,
+    lock_listener: Option<LockListener>,
+    reconcile_listener: Option<ReconcileListener>,
+    cancel: Option<watch::Receiver<bool>>,
+    explain_analyze: bool,
 }
 
 impl Runner {
     pub fn new(
         pool: sqlx::PgPool,
-        database_url: impl Into<String>,
         vellum_version: impl Into<String>,
     ) -> Self {
         Self {
             pool,
-            database_url: database_url.into(),
             vellum_version: vellum_version.into(),
+            connection_settings: ConnectionSettings::default(),
+            lock_settings: LockSettings::default(),
+            lock_listener: None,
+            reconcile_listener: None,
+            cancel: None,
+            explain_analyze: false,
         }
     }
 
-    pub async fn run(&self, migrations: &[Migration]) -> Result<RunReport, ExecutorError> {
-        self.run_with_mode(ExecutionMode::Apply, migrations).await
+    // Only the connect timeout matters here: it governs how long the lock
+    // session keeps retrying while the server is unreachable.
+    pub fn with_connection_settings(mut self, connection_settings: ConnectionSettings) -> Self {
+        self.connection_settings = connection_settings;
+        self
+    }
+
+    pub fn with_lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_settings.wait = LockWait::Timeout(lock_timeout);
+        self
+    }
+
+    pub fn with_lock_settings(mut self, lock_settings: LockSettings) -> Self {
+        self.lock_settings = lock_settings;
+        self
+    }
+
+    pub fn with_lock_listener(
+        mut self,
+        listener: impl Fn(&LockWaiting) + Send + Sync + 'static,
+    ) -> Self {
+        self.lock_listener = Some(Arc::new(listener));
+        self
+    }
+
+    pub fn with_reconcile_listener(
+        mut self,
+        listener: impl Fn(&[AbandonedRun]) + Send + Sync + 'static,
+    ) -> Self {
+        self.reconcile_listener = Some(Arc::new(listener));
+        self
+    }
+
+    // Sending `true` cancels the current run: the in-flight query is
+    // cancelled, its migration rolled back, and the run marked cancelled.
+    pub fn with_cancellation(mut self, cancel: watch::Receiver<bool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn with_explain_analyze(mut self, explain_analyze: bool) -> Self {
+        self.explain_analyze = explain_analyze;
+        self
+    }
+
+    pub async fn run(
+        &self,
+        migrations: &[Migration],
+        repeatables: &[RepeatableMigration],
+    ) -> Result<RunReport, ExecutorError> {
+        self.run_with_mode(ExecutionMode::Apply, migrations, repeatables)
+            .await
     }
 
     pub async fn run_with_mode(
         &self,
         mode: ExecutionMode,
         migrations: &[Migration],
+        repeatables: &[RepeatableMigration],
     ) -> Result<RunReport, ExecutorError> {
-        let lock_timeout = Duration::from_secs(30);
-        let lock = AdvisoryLockGuard::acquire(&self.database_url, lock_timeout)
-            .await
-            .map_err(map_lock_error)?;
-
-        let result = self.run_locked(mode, migrations).await;
-        match lock.release().await {
-            Ok(()) => result,
-            Err(release_err) => {
-                let original_error = result.as_ref().err().map(|e| e.to_string());
-                Err(ExecutorError::LockReleaseFailed {
-                    message: release_err.to_string(),
-                    original_error,
+        let run_id = Uuid::new_v4();
+        let lock = self.acquire_lock(run_id).await?;
+        let run = self.run_locked(run_id, lock.backend_pid(), mode, migrations, repeatables);
+        self.hold(lock, run_id, run).await
+    }
+
+    pub async fn apply_saved(
+        &self,
+        saved: &SavedPlan,
+        migrations: &[Migration],
+        repeatables: &[RepeatableMigration],
+    ) -> Result<RunReport, ExecutorError> {
+        let run_id = Uuid::new_v4();
+        let lock = self.acquire_lock(run_id).await?;
+        let owner_pid = lock.backend_pid();
+        let run = run_apply(
+            &self.pool,
+            run_id,
+            owner_pid,
+            &self.vellum_version,
+            migrations,
+            repeatables,
+            Some(saved),
+        );
+        self.hold(lock, run_id, run).await
+    }
+
+    pub async fn plan(
+        &self,
+        migrations: &[Migration],
+        repeatables: &[RepeatableMigration],
+    ) -> Result<MigrationPlan, ExecutorError> {
+        planner::compute(&self.pool, migrations, repeatables).await
+    }
+
+    pub async fn repair(
+        &self,
+        migrations: &[Migration],
+        versions: &[Version],
+        reason: &str,
+    ) -> Result<RepairReport, ExecutorError> {
+        let run_id = Uuid::new_v4();
+        let lock = self.acquire_lock(run_id).await?;
+        let owner_pid = lock.backend_pid();
+        let run = repair::run(
+            &self.pool,
+            run_id,
+            owner_pid,
+            &self.vellum_version,
+            migrations,
+            versions,
+            reason,
+        );
+        self.hold(lock, run_id, run).await
+    }
+
+    pub async fn baseline(
+        &self,
+        migrations: &[Migration],
+        version: &Version,
+    ) -> Result<BaselineReport, ExecutorError> {
+        let run_id = Uuid::new_v4();
+        let lock = self.acquire_lock(run_id).await?;
+        let owner_pid = lock.backend_pid();
+        let run = baseline::run(
+            &self.pool,
+            run_id,
+            owner_pid,
+            &self.vellum_version,
+            migrations,
+            version,
+        );
+        self.hold(lock, run_id, run).await
+    }
+
+    // Takes the migration lock on its own and marks runs whose lock session
+    // is gone as abandoned.
+    pub async fn reconcile(&self) -> Result<Vec<AbandonedRun>, ExecutorError> {
+        let lock = self.lock(Uuid::new_v4()).await?;
+        let result = reconcile::reconcile(&self.pool, lock.acquired_at()).await;
+        release_lock(lock, result).await
+    }
+
+    async fn acquire_lock(&self, run_id: Uuid) -> Result<AdvisoryLockGuard, ExecutorError> {
+        let lock = self.lock(run_id).await?;
+
+        // Holding the lock is a good moment to tidy up after runs that died
+        // without recording an outcome. It never stands in the way of the run.
+        if let Ok(abandoned) = reconcile::reconcile(&self.pool, lock.acquired_at()).await {
+            if let Some(listener) = self.reconcile_listener.as_ref().filter(|_| !abandoned.is_empty()) {
+                listener(&abandoned);
+            }
+        }
+
+        Ok(lock)
+    }
+
+    async fn lock(&self, run_id: Uuid) -> Result<AdvisoryLockGuard, ExecutorError> {
+        let owner = LockOwner::current(run_id);
+        // The lock session reuses the pool's connect options so it carries
+        // the same credentials, TLS settings and application_name.
+        let mut lock_options = (*self.pool.connect_options()).clone();
+        if let Some(database) = &self.lock_settings.scope.database {
+            lock_options = lock_options.database(database);
+        }
+
+        let listener = self.lock_listener.clone();
+        let on_wait = move |waiting: &LockWaiting| {
+            if let Some(listener) = &listener {
+                listener(waiting);
+            }
+        };
+
+        let lock_connection =
+            ConnectionManager::from_options(lock_options, self.connection_settings.clone());
+        let acquire = async {
+            let conn = lock_connection.connection().await?;
+            AdvisoryLockGuard::acquire(conn, &self.lock_settings, &owner, on_wait)
+                .await
+                .map_err(map_lock_error)
+        };
+
+        tokio::select! {
+            lock = acquire => lock,
+            () = cancelled(self.cancel.clone()) => {
+                Err(ExecutorError::RunCancelled {
+                    cancelled_sessions: 0,
                 })
             }
         }
     }
 
+    // Runs `run` while heartbeating the lock session. If the lock is lost or
+    // the run is cancelled, `run` is dropped mid-flight and its tagged
+    // sessions are dealt with so PostgreSQL rolls back their transaction.
+    async fn hold<T>(
+        &self,
+        mut lock: AdvisoryLockGuard,
+        run_id: Uuid,
+        run: impl Future<Output = Result<T, ExecutorError>>,
+    ) -> Result<T, ExecutorError> {
+        let outcome = tokio::select! {
+            result = run => Ok(result),
+            lost = lock.watch() => Err(Interruption::LockLost(lost)),
+            () = cancelled(self.cancel.clone()) => Err(Interruption::Cancelled),
+        };
+
+        match outcome {
+            Ok(result) => release_lock(lock, result).await,
+            Err(Interruption::LockLost(lost)) => {
+                drop(lock);
+
+                let terminated_sessions = transaction::terminate_sessions(&self.pool, run_id)
+                    .await
+                    .unwrap_or_default();
+                let err = ExecutorError::MigrationLockLost {
+                    message: lost.to_string(),
+                    terminated_sessions,
+                };
+                let _ = audit::mark_run_failed(&self.pool, run_id, &err).await;
+                Err(err)
+            }
+            Err(Interruption::Cancelled) => {
+                let cancelled_sessions = self.cancel_run(run_id).await;
+                let _ = audit::mark_run_cancelled(&self.pool, run_id).await;
+                release_lock(lock, Err(ExecutorError::RunCancelled { cancelled_sessions })).await
+            }
+        }
+    }
+
+    // The dropped run hands its connection back to the pool, which rolls the
+    // transaction back once the cancelled query returns. Sessions that are
+    // still busy after CANCEL_GRACE are terminated, which rolls back as well.
+    async fn cancel_run(&self, run_id: Uuid) -> u64 {
+        let cancelled_sessions = transaction::cancel_sessions(&self.pool, run_id)
+            .await
+            .unwrap_or_default();
+
+        let deadline = Instant::now() + CANCEL_GRACE;
+        while transaction::count_sessions(&self.pool, run_id)
+            .await
+            .is_ok_and(|sessions| sessions > 0)
+        {
+            if Instant::now() >= deadline {
+                let _ = transaction::terminate_sessions(&self.pool, run_id).await;
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        cancelled_sessions
+    }
+
     async fn run_locked(
         &self,
+        run_id: Uuid,
+        owner_pid: i32,
         mode: ExecutionMode,
         migrations: &[Migration],
+        repeatables: &[RepeatableMigration],
     ) -> Result<RunReport, ExecutorError> {
         match mode {
-            ExecutionMode::Apply => run_apply(&self.pool, &self.vellum_version, migrations).await,
+            ExecutionMode::Apply => {
+                run_apply(
+                    &self.pool,
+                    run_id,
+                    owner_pid,
+                    &self.vellum_version,
+                    migrations,
+                    repeatables,
+                    None,
+                )
+                .await
+            }
             ExecutionMode::DryRun => {
-                dry_run::run(&self.pool, &self.vellum_version, migrations).await
+                dry_run::run(
+                    &self.pool,
+                    run_id,
+                    owner_pid,
+                    &self.vellum_version,
+                    migrations,
+                    repeatables,
+                    self.explain_analyze,
+                )
+                .await
             }
         }
     }
 }
 
+async fn release_lock<T>(
+    lock: AdvisoryLockGuard,
+    result: Result<T, ExecutorError>,
+) -> Result<T, ExecutorError> {
+    match lock.release().await {
+        Ok(()) => result,
+        Err(release_err) => {
+            let original_error = result.as_ref().err().map(|e| e.to_string());
+            Err(ExecutorError::LockReleaseFailed {
+                message: release_err.to_string(),
+                original_error,
+            })
+        }
+    }
+}
+
+async fn cancelled(cancel: Option<watch::Receiver<bool>>) {
+    if let Some(mut cancel) = cancel {
+        if cancel.wait_for(|cancelled| *cancelled).await.is_ok() {
+            return;
+        }
+    }
+    core::future::pending::<()>().await
+}
+
 fn map_lock_error(err: LockError) -> ExecutorError {
     match err {
-        LockError::MigrationLockUnavailable { timeout_ms } => {
-            ExecutorError::MigrationLockUnavailable { timeout_ms }
+        LockError::MigrationLockUnavailable { timeout_ms, holder } => {
+            ExecutorError::MigrationLockUnavailable { timeout_ms, holder }
         }
-        LockError::LockAcquireFailed { message } => ExecutorError::LockAcquireFailed { message },
+        LockError::LockAcquireFailed { message } | LockError::LockQueryFailed { message } => {
+            ExecutorError::LockAcquireFailed { message }
+        }
+        LockError::LockLost { message } => ExecutorError::MigrationLockLost {
+            message,
+            terminated_sessions: 0,
+        },
         LockError::LockReleaseFailed { message } => ExecutorError::LockReleaseFailed {
             message,
             original_error: None,
@@ -145,14 +492,14 @@ async fn execute_one(
     run_id: Uuid,
     migration: &Migration,
 ) -> Result<(), ExecutorError> {
-    let migration_version = migration.version;
+    let migration_version = &migration.version;
     let statements = statement::split_statements(
         &migration.sql,
         Some(&migration.filename),
         migration_version,
     )?;
 
-    let mut tx = transaction::begin(pool, migration_version).await?;
+    let mut tx = transaction::begin(pool, run_id, migration_version).await?;
 
     let migration_id = audit::insert_migration(&mut tx, run_id, migration).await?;
     let migration_started = std::time::Instant::now();
@@ -0,0 +1,116 @@
+use crate::error::ExecutorError;
+use core::time::Duration;
+use sqlx::Executor;
+use tokio::time::{sleep, Instant};
+
+const SQL_COUNT_SESSIONS: &str = "
+SELECT count(*)
+FROM pg_stat_activity
+WHERE datname = $1 AND pid <> pg_backend_pid()
+";
+
+const SQL_TERMINATE_SESSIONS: &str = "
+SELECT pg_terminate_backend(pid)
+FROM pg_stat_activity
+WHERE datname = $1 AND pid <> pg_backend_pid()
+";
+
+// object_in_use: other sessions are still connected to the database.
+const SQLSTATE_OBJECT_IN_USE: &str = "55006";
+
+const DROP_TIMEOUT: Duration = Duration::from_secs(5);
+const DROP_RETRY: Duration = Duration::from_millis(100);
+
+pub struct ShadowDatabase {
+    name: String,
+}
+
+impl ShadowDatabase {
+    pub async fn create(pool: &sqlx::PgPool) -> Result<Self, ExecutorError> {
+        create_from(pool, "vellum_shadow", "template0").await
+    }
+
+    // PostgreSQL can only copy a template nobody is connected to, so `pool`
+    // must be connected to a different database than `template`.
+    pub async fn clone_of(pool: &sqlx::PgPool, template: &str) -> Result<Self, ExecutorError> {
+        let (sessions,): (i64,) = sqlx::query_as(SQL_COUNT_SESSIONS)
+            .bind(template)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| ExecutorError::ShadowDatabaseFailed {
+                operation: "clone".to_string(),
+                database: template.to_string(),
+                message: e.to_string(),
+            })?;
+
+        if sessions > 0 {
+            return Err(ExecutorError::ShadowDatabaseFailed {
+                operation: "clone".to_string(),
+                database: template.to_string(),
+                message: format!(
+                    "{sessions} other session(s) are connected to {template}; a database can only be cloned while nobody is connected to it"
+                ),
+            });
+        }
+
+        create_from(pool, "vellum_clone", template).await
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // DROP DATABASE ... WITH (FORCE) needs PostgreSQL 13, so leftover
+    // sessions are terminated by hand and the drop is retried while they
+    // finish exiting.
+    pub async fn drop(self, pool: &sqlx::PgPool) -> Result<(), ExecutorError> {
+        let failed = |e: sqlx::Error| ExecutorError::ShadowDatabaseFailed {
+            operation: "drop".to_string(),
+            database: self.name.clone(),
+            message: e.to_string(),
+        };
+        let drop_sql = format!("DROP DATABASE IF EXISTS \"{}\"", self.name);
+        let deadline = Instant::now() + DROP_TIMEOUT;
+
+        loop {
+            sqlx::query(SQL_TERMINATE_SESSIONS)
+                .bind(&self.name)
+                .execute(pool)
+                .await
+                .map_err(failed)?;
+
+            match pool.execute(drop_sql.as_str()).await {
+                Ok(_) => return Ok(()),
+                Err(e) if is_in_use(&e) && Instant::now() < deadline => {
+                    sleep(DROP_RETRY).await;
+                }
+                Err(e) => return Err(failed(e)),
+            }
+        }
+    }
+}
+
+async fn create_from(
+    pool: &sqlx::PgPool,
+    prefix: &str,
+    template: &str,
+) -> Result<ShadowDatabase, ExecutorError> {
+    let name = format!("{prefix}_{}", uuid::Uuid::new_v4().simple());
+
+    // CREATE DATABASE cannot run inside a transaction block, so it goes
+    // through the simple query protocol rather than a prepared statement.
+    let template = template.replace('"', "\"\"");
+    pool.execute(format!("CREATE DATABASE \"{name}\" TEMPLATE \"{template}\"").as_str())
+        .await
+        .map_err(|e| ExecutorError::ShadowDatabaseFailed {
+            operation: "create".to_string(),
+            database: name.clone(),
+            message: e.to_string(),
+        })?;
+
+    Ok(ShadowDatabase { name })
+}
+
+fn is_in_use(err: &sqlx::Error) -> bool {
+    matches!(err, sqlx::Error::Database(db) if db.code().as_deref() == Some(SQLSTATE_OBJECT_IN_USE))
+}
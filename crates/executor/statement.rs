@@ -1,5 +1,6 @@
 use crate::error::ExecutorError;
 use core::time::Duration;
+use vellum_migration::Version;
 use vellum_sql_engine::StatementParser;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,22 +24,32 @@ pub struct StatementOutcome {
     pub error_message: Option<String>,
 }
 
-pub fn split_statements(
-    sql: &str,
-    source_name: Option<&str>,
-    migration_version: i64,
-) -> Result<Vec<SqlStatement>, ExecutorError> {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementFailure {
+    pub execution_time_ms: i32,
+    pub message: String,
+}
+
+pub fn parse_statements(sql: &str, source_name: Option<&str>) -> Result<Vec<SqlStatement>, String> {
     let parser = vellum_sql_engine::PgQueryStatementParser::new();
     let parsed = parser
         .parse_statements(sql, source_name)
-        .map_err(|e| ExecutorError::StatementParsingFailed {
-            migration_version,
-            message: e.to_string(),
-        })?;
+        .map_err(|e| e.to_string())?;
 
     Ok(parsed.into_iter().map(SqlStatement).collect())
 }
 
+pub fn split_statements(
+    sql: &str,
+    source_name: Option<&str>,
+    migration_version: &Version,
+) -> Result<Vec<SqlStatement>, ExecutorError> {
+    parse_statements(sql, source_name).map_err(|message| ExecutorError::StatementParsingFailed {
+        migration_version: migration_version.clone(),
+        message,
+    })
+}
+
 pub fn statement_kind(sql: &str) -> String {
     let token = sql
         .split_whitespace()
@@ -66,18 +77,14 @@ pub fn duration_ms(d: Duration) -> i32 {
     }
 }
 
-pub async fn execute_statement(
+pub async fn run_statement(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
-    migration_version: i64,
     stmt: &SqlStatement,
-) -> Result<i32, ExecutorError> {
+) -> Result<i32, StatementFailure> {
     let kind = statement_kind(stmt.sql());
     if is_forbidden_transaction_control(&kind) {
-        return Err(ExecutorError::StatementExecutionFailed {
-            migration_version,
-            statement_ordinal: stmt.ordinal(),
+        return Err(StatementFailure {
             execution_time_ms: 0,
-            statement: stmt.sql().to_string(),
             message: "transaction control statements are not allowed inside migration files"
                 .to_string(),
         });
@@ -89,12 +96,25 @@ pub async fn execute_statement(
 
     match result {
         Ok(_) => Ok(elapsed),
-        Err(e) => Err(ExecutorError::StatementExecutionFailed {
-            migration_version,
-            statement_ordinal: stmt.ordinal(),
+        Err(e) => Err(StatementFailure {
             execution_time_ms: elapsed,
-            statement: stmt.sql().to_string(),
             message: e.to_string(),
         }),
     }
 }
+
+pub async fn execute_statement(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    migration_version: &Version,
+    stmt: &SqlStatement,
+) -> Result<i32, ExecutorError> {
+    run_statement(tx, stmt)
+        .await
+        .map_err(|failure| ExecutorError::StatementExecutionFailed {
+            migration_version: migration_version.clone(),
+            statement_ordinal: stmt.ordinal(),
+            execution_time_ms: failure.execution_time_ms,
+            statement: stmt.sql().to_string(),
+            message: failure.message,
+        })
+}
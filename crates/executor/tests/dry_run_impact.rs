@@ -0,0 +1,36 @@
+mod support;
+
+use support::{migration, ScratchDatabase};
+use vellum_executor::{ExecutionMode, Runner};
+use vellum_sql_engine::LockLevel;
+
+#[tokio::test]
+async fn locks_name_relations_the_same_way_whatever_the_search_path() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    let migrations = [
+        migration(1, "CREATE TABLE public.orders (id int);"),
+        migration(2, "ALTER TABLE orders ADD COLUMN note text;"),
+        migration(3, "ALTER TABLE public.orders ADD COLUMN total int;"),
+    ];
+
+    let report = Runner::new(db.pool.clone(), "test")
+        .run_with_mode(ExecutionMode::DryRun, &migrations, &[])
+        .await
+        .unwrap();
+
+    let locks: Vec<(String, String, LockLevel)> = report
+        .lock_impact
+        .unwrap()
+        .locks()
+        .map(|(stmt, lock)| (stmt.filename.clone(), lock.relation.clone(), lock.level))
+        .collect();
+    assert_eq!(
+        locks,
+        ["001_m1.sql", "002_m2.sql", "003_m3.sql"]
+            .map(|file| (file.to_string(), "public.orders".to_string(), LockLevel::AccessExclusive))
+            .to_vec()
+    );
+    db.drop().await;
+}
@@ -0,0 +1,53 @@
+mod support;
+
+use support::{migration, record_migration, ScratchDatabase, INIT_SCHEMA};
+use vellum_executor::{audit, planner};
+use vellum_migration::{StepAction, StepReason, Version};
+
+// A database bootstrapped by a release that predates repeatable migrations
+// (internal schema version 1) has no `kind` column until `migrate` upgrades it.
+async fn v1_database() -> Option<ScratchDatabase> {
+    let db = ScratchDatabase::empty().await?;
+    db.execute(INIT_SCHEMA).await;
+    Some(db)
+}
+
+#[tokio::test]
+async fn v1_history_is_read_as_versioned_migrations() {
+    let Some(db) = v1_database().await else {
+        return;
+    };
+    let applied = migration(1, "CREATE TABLE a (id int);");
+    record_migration(&db, 1, &applied.checksum, true).await;
+
+    assert!(!audit::has_migration_kind(&db.pool).await.unwrap());
+    let history = audit::select_applied_history(&db.pool).await.unwrap();
+    assert_eq!(history.versioned.get(&Version::from(1)), Some(&applied.checksum));
+    assert!(history.repeatable.is_empty());
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn v1_history_plans_like_an_upgraded_one() {
+    let Some(db) = v1_database().await else {
+        return;
+    };
+    let applied = migration(1, "CREATE TABLE a (id int);");
+    let pending = migration(2, "CREATE TABLE b (id int);");
+    record_migration(&db, 1, &applied.checksum, true).await;
+
+    let plan = planner::compute(&db.pool, &[applied, pending], &[]).await.unwrap();
+
+    assert_eq!(plan.steps[0].reason, StepReason::AlreadyApplied);
+    assert_eq!(plan.steps[1].action, StepAction::Apply);
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn upgraded_history_reports_the_kind_column() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    assert!(audit::has_migration_kind(&db.pool).await.unwrap());
+    db.drop().await;
+}
@@ -0,0 +1,42 @@
+mod support;
+
+use sqlx::Connection;
+use support::ScratchDatabase;
+use vellum_lock::holder;
+
+#[tokio::test]
+async fn terminate_waits_for_the_backend_to_exit() {
+    let Some(db) = ScratchDatabase::empty().await else {
+        return;
+    };
+    let mut admin = db.connection().connection().await.unwrap();
+    let mut victim = db.connection().connection().await.unwrap();
+    let (pid,): (i32,) = sqlx::query_as("SELECT pg_backend_pid()")
+        .fetch_one(&mut victim)
+        .await
+        .unwrap();
+
+    assert!(holder::terminate(&mut admin, pid).await.unwrap());
+
+    let alive: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_stat_activity WHERE pid = $1)")
+        .bind(pid)
+        .fetch_one(&mut admin)
+        .await
+        .unwrap();
+    assert!(!alive);
+    assert!(victim.ping().await.is_err());
+    let _ = admin.close().await;
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn terminate_reports_an_unknown_backend() {
+    let Some(db) = ScratchDatabase::empty().await else {
+        return;
+    };
+    let mut admin = db.connection().connection().await.unwrap();
+
+    assert!(!holder::terminate(&mut admin, i32::MAX).await.unwrap());
+    let _ = admin.close().await;
+    db.drop().await;
+}
@@ -0,0 +1,74 @@
+mod support;
+
+use support::ScratchDatabase;
+use uuid::Uuid;
+use vellum_executor::{reconcile, ExecutorError, Runner};
+use vellum_lock::{AdvisoryLockGuard, LockOwner, LockSettings, LockWait};
+
+async fn insert_running_run(db: &ScratchDatabase, started_at: &str) -> Uuid {
+    let run_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO vellum.vellum_runs (id, started_at, mode, status, db_name, db_user, vellum_version)
+         VALUES ($1, $2::timestamptz, 'apply', 'running', current_database(), current_user, 'test')",
+    )
+    .bind(run_id)
+    .bind(started_at)
+    .execute(&db.pool)
+    .await
+    .expect("insert run");
+    run_id
+}
+
+#[tokio::test]
+async fn ownerless_runs_started_after_the_lock_was_granted_are_kept() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    let before = insert_running_run(&db, "2024-01-01T00:00:00Z").await;
+    let after = insert_running_run(&db, "2024-01-01T00:00:02Z").await;
+
+    let abandoned = reconcile(&db.pool, "2024-01-01T00:00:01.000000Z").await.unwrap();
+
+    assert_eq!(
+        abandoned.iter().map(|run| run.run_id).collect::<Vec<_>>(),
+        vec![before]
+    );
+    let status: String = sqlx::query_scalar("SELECT status FROM vellum.vellum_runs WHERE id = $1")
+        .bind(after)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "running");
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn runner_reconcile_waits_for_the_migration_lock() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    insert_running_run(&db, "2024-01-01T00:00:00Z").await;
+    let held = AdvisoryLockGuard::acquire(
+        db.connection().connection().await.unwrap(),
+        &LockSettings::default(),
+        &LockOwner::current(Uuid::new_v4()),
+        |_| {},
+    )
+    .await
+    .unwrap();
+
+    let runner = Runner::new(db.pool.clone(), "test").with_lock_settings(LockSettings {
+        wait: LockWait::NoWait,
+        ..LockSettings::default()
+    });
+    let err = runner.reconcile().await.unwrap_err();
+    assert!(
+        matches!(err, ExecutorError::MigrationLockUnavailable { .. }),
+        "{err}"
+    );
+
+    held.release().await.unwrap();
+    let abandoned = runner.reconcile().await.unwrap();
+    assert_eq!(abandoned.len(), 1);
+    db.drop().await;
+}
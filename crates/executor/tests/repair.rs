@@ -0,0 +1,125 @@
+mod support;
+
+use support::{migration, record_migration, ScratchDatabase};
+use uuid::Uuid;
+use vellum_executor::{repair, ExecutorError, RepairAction};
+use vellum_migration::Version;
+
+async fn repair_run(
+    db: &ScratchDatabase,
+    migrations: &[vellum_migration::Migration],
+    versions: &[u64],
+    reason: &str,
+) -> Result<repair::RepairReport, ExecutorError> {
+    let versions: Vec<Version> = versions.iter().copied().map(Version::from).collect();
+    repair::run(&db.pool, Uuid::new_v4(), 0, "test", migrations, &versions, reason).await
+}
+
+async fn recorded(db: &ScratchDatabase, version: u64) -> Option<(String, bool)> {
+    sqlx::query_as("SELECT checksum, success FROM vellum.vellum_migrations WHERE version = $1")
+        .bind(version.to_string())
+        .fetch_optional(&db.pool)
+        .await
+        .unwrap()
+}
+
+async fn repairs(db: &ScratchDatabase) -> Vec<(String, String, String)> {
+    sqlx::query_as("SELECT version, action, reason FROM vellum.vellum_repairs ORDER BY id")
+        .fetch_all(&db.pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn blank_reason_is_rejected_before_touching_the_database() {
+    let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+    let err = repair::run(&pool, Uuid::new_v4(), 0, "test", &[], &[Version::from(1)], "  ")
+        .await
+        .unwrap_err();
+    assert_eq!(err, ExecutorError::RepairReasonMissing);
+}
+
+#[tokio::test]
+async fn checksum_mismatch_is_updated_to_the_file_checksum() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    let file = migration(1, "CREATE TABLE t (id int);");
+    record_migration(&db, 1, "stale", true).await;
+
+    let checksum = file.checksum.clone();
+
+    let report = repair_run(&db, &[file], &[1], "edited comment").await.unwrap();
+
+    assert_eq!(report.outcomes[0].action, RepairAction::ChecksumUpdated);
+    assert_eq!(report.outcomes[0].old_checksum.as_deref(), Some("stale"));
+    assert_eq!(recorded(&db, 1).await, Some((checksum, true)));
+    assert_eq!(
+        repairs(&db).await,
+        vec![("1".to_string(), "checksum_updated".to_string(), "edited comment".to_string())]
+    );
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn failed_record_is_removed() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    let file = migration(1, "CREATE TABLE t (id int);");
+    record_migration(&db, 1, &file.checksum, false).await;
+
+    let report = repair_run(&db, &[file], &[1], "retry").await.unwrap();
+
+    assert_eq!(report.outcomes[0].action, RepairAction::FailedRemoved);
+    assert_eq!(recorded(&db, 1).await, None);
+    assert_eq!(repairs(&db).await[0].1, "failed_removed");
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn record_without_file_is_removed_as_orphan() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    record_migration(&db, 2, "gone", true).await;
+
+    let report = repair_run(&db, &[], &[2], "file deleted").await.unwrap();
+
+    assert_eq!(report.outcomes[0].action, RepairAction::OrphanRemoved);
+    assert_eq!(report.outcomes[0].new_checksum, None);
+    assert_eq!(recorded(&db, 2).await, None);
+    assert_eq!(repairs(&db).await[0].1, "orphan_removed");
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn matching_record_is_left_unchanged_and_not_audited() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    let file = migration(1, "CREATE TABLE t (id int);");
+    record_migration(&db, 1, &file.checksum, true).await;
+
+    let report = repair_run(&db, &[file], &[1], "noop").await.unwrap();
+
+    assert_eq!(report.outcomes[0].action, RepairAction::Unchanged);
+    assert!(repairs(&db).await.is_empty());
+    db.drop().await;
+}
+
+#[tokio::test]
+async fn unknown_version_fails_and_rolls_back_earlier_repairs() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    record_migration(&db, 1, "stale", true).await;
+    let file = migration(1, "CREATE TABLE t (id int);");
+
+    let err = repair_run(&db, &[file], &[1, 9], "typo").await.unwrap_err();
+
+    assert_eq!(err, ExecutorError::RepairTargetNotFound { version: Version::from(9) });
+    assert_eq!(recorded(&db, 1).await, Some(("stale".to_string(), true)));
+    assert!(repairs(&db).await.is_empty());
+    db.drop().await;
+}
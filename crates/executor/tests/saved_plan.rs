@@ -0,0 +1,39 @@
+mod support;
+
+use support::{migration, ScratchDatabase};
+use vellum_executor::{audit, ExecutorError, Runner};
+use vellum_migration::{AppliedHistory, MigrationPlan, SavedPlan, StaleReason};
+
+#[tokio::test]
+async fn saved_plan_for_another_cluster_is_rejected() {
+    let Some(db) = ScratchDatabase::bootstrapped().await else {
+        return;
+    };
+    let migrations = [migration(1, "CREATE TABLE t (id int);")];
+    let plan = MigrationPlan::build(&AppliedHistory::default(), &migrations, &[]);
+
+    let mut database = audit::select_database_identity(&db.pool).await.unwrap();
+    let saved = SavedPlan::new("test", &database, &AppliedHistory::default(), &plan);
+    database.system_identifier = Some("1".to_string());
+    let elsewhere = SavedPlan::new("test", &database, &AppliedHistory::default(), &plan);
+
+    let runner = Runner::new(db.pool.clone(), "test");
+    let err = runner.apply_saved(&elsewhere, &migrations, &[]).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ExecutorError::PlanStale {
+                reason: StaleReason::DatabaseChanged { .. }
+            }
+        ),
+        "{err}"
+    );
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('t') IS NOT NULL")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert!(!exists);
+
+    runner.apply_saved(&saved, &migrations, &[]).await.unwrap();
+    db.drop().await;
+}
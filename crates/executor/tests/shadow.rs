@@ -0,0 +1,26 @@
+mod support;
+
+use support::ScratchDatabase;
+use vellum_executor::ShadowDatabase;
+
+#[tokio::test]
+async fn drop_disconnects_sessions_still_on_the_shadow() {
+    let Some(db) = ScratchDatabase::empty().await else {
+        return;
+    };
+    let shadow = ShadowDatabase::create(&db.pool).await.unwrap();
+    let name = shadow.name().to_string();
+    let session = db.connection().for_database(&name).connection().await.unwrap();
+
+    shadow.drop(&db.pool).await.unwrap();
+
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_database WHERE datname = $1)")
+            .bind(&name)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert!(!exists);
+    drop(session);
+    db.drop().await;
+}
@@ -0,0 +1,122 @@
+#![allow(dead_code)]
+
+use sqlx::Executor;
+use uuid::Uuid;
+use vellum_contracts::migrations::DatabaseMigrator;
+use vellum_executor::{ConnectionManager, ConnectionSettings};
+use vellum_migration::{Migration, Version};
+
+pub const DATABASE_URL_VAR: &str = "VELLUM_TEST_DATABASE_URL";
+
+pub const INIT_SCHEMA: &str = include_str!("../../../db/migrations/001_init_schema.sql");
+
+// Tests that need PostgreSQL run against a fresh database created on the
+// server named by VELLUM_TEST_DATABASE_URL and are skipped when it is unset.
+pub struct ScratchDatabase {
+    admin: sqlx::PgPool,
+    connection: ConnectionManager,
+    name: String,
+    pub pool: sqlx::PgPool,
+}
+
+impl ScratchDatabase {
+    pub async fn empty() -> Option<Self> {
+        let Ok(url) = std::env::var(DATABASE_URL_VAR) else {
+            eprintln!("skipping: {DATABASE_URL_VAR} is not set");
+            return None;
+        };
+
+        let connection = ConnectionManager::new(&url, ConnectionSettings::default())
+            .expect("valid test database URL");
+        let admin = connection.pool().await.expect("connect to test server");
+        let name = format!("vellum_test_{}", Uuid::new_v4().simple());
+        admin
+            .execute(format!("CREATE DATABASE \"{name}\" TEMPLATE template0").as_str())
+            .await
+            .expect("create scratch database");
+
+        let connection = connection.for_database(&name);
+        let pool = connection.pool().await.expect("connect to scratch database");
+        Some(Self {
+            admin,
+            connection,
+            name,
+            pool,
+        })
+    }
+
+    pub async fn bootstrapped() -> Option<Self> {
+        let db = Self::empty().await?;
+        vellum_db::SqlxDatabaseMigrator::from_pool(db.pool.clone())
+            .apply_baseline()
+            .await
+            .expect("bootstrap vellum schema");
+        Some(db)
+    }
+
+    pub fn connection(&self) -> &ConnectionManager {
+        &self.connection
+    }
+
+    pub async fn execute(&self, sql: &str) {
+        sqlx::raw_sql(sql)
+            .execute(&self.pool)
+            .await
+            .unwrap_or_else(|e| panic!("{sql}: {e}"));
+    }
+
+    // Connections released just before the pool closes can outlive it for a
+    // moment, so whatever is left is terminated before the drop.
+    pub async fn drop(self) {
+        self.pool.close().await;
+        let _ = sqlx::query(
+            "SELECT pg_terminate_backend(pid) FROM pg_stat_activity WHERE datname = $1",
+        )
+        .bind(&self.name)
+        .execute(&self.admin)
+        .await;
+        if let Err(e) = self
+            .admin
+            .execute(format!("DROP DATABASE IF EXISTS \"{}\"", self.name).as_str())
+            .await
+        {
+            eprintln!("failed to drop {}: {e}", self.name);
+        }
+        self.admin.close().await;
+    }
+}
+
+pub fn migration(version: u64, sql: &str) -> Migration {
+    Migration::new(
+        Version::from(version),
+        format!("m{version}"),
+        format!("{version:03}_m{version}.sql"),
+        vellum_migration::sha256_hex(sql.as_bytes()),
+        sql.to_string(),
+    )
+}
+
+pub async fn record_migration(db: &ScratchDatabase, version: u64, checksum: &str, success: bool) {
+    let run_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO vellum.vellum_runs (id, started_at, finished_at, mode, status, db_name, db_user, vellum_version)
+         VALUES ($1, now(), now(), 'apply', 'success', current_database(), current_user, 'test')",
+    )
+    .bind(run_id)
+    .execute(&db.pool)
+    .await
+    .expect("insert run");
+
+    sqlx::query(
+        "INSERT INTO vellum.vellum_migrations (version, name, checksum, execution_time_ms, success, run_id)
+         VALUES ($1, $2, $3, 0, $4, $5)",
+    )
+    .bind(version.to_string())
+    .bind(format!("m{version}"))
+    .bind(checksum)
+    .bind(success)
+    .bind(run_id)
+    .execute(&db.pool)
+    .await
+    .expect("insert migration");
+}
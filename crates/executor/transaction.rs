@@ -1,36 +1,97 @@
 use crate::error::ExecutorError;
+use uuid::Uuid;
+use vellum_migration::Version;
 
 pub async fn begin<'a>(
     pool: &'a sqlx::PgPool,
-    migration_version: i64,
+    run_id: Uuid,
+    migration_version: &Version,
 ) -> Result<sqlx::Transaction<'a, sqlx::Postgres>, ExecutorError> {
-    pool.begin().await.map_err(|e| ExecutorError::TransactionBeginFailed {
-        migration_version,
+    let begin_failed = |e: sqlx::Error| ExecutorError::TransactionBeginFailed {
+        migration_version: migration_version.clone(),
         message: e.to_string(),
-    })
+    };
+
+    let mut tx = pool.begin().await.map_err(begin_failed)?;
+    tag(&mut tx, run_id).await.map_err(begin_failed)?;
+    Ok(tx)
+}
+
+pub fn session_name(run_id: Uuid) -> String {
+    format!("vellum run {run_id}")
+}
+
+// Names the session for the lifetime of the transaction only, so a run that
+// loses its lock can find the sessions it still has busy and terminate them.
+pub async fn tag(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    run_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT set_config('application_name', $1, true)")
+        .bind(session_name(run_id))
+        .execute(&mut **tx)
+        .await
+        .map(|_| ())
+}
+
+pub async fn cancel_sessions(pool: &sqlx::PgPool, run_id: Uuid) -> Result<u64, sqlx::Error> {
+    let (cancelled,): (i64,) = sqlx::query_as(
+        "SELECT count(*) FILTER (WHERE pg_cancel_backend(pid))
+         FROM pg_stat_activity
+         WHERE application_name = $1 AND pid <> pg_backend_pid()",
+    )
+    .bind(session_name(run_id))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(cancelled as u64)
+}
+
+pub async fn count_sessions(pool: &sqlx::PgPool, run_id: Uuid) -> Result<u64, sqlx::Error> {
+    let (sessions,): (i64,) = sqlx::query_as(
+        "SELECT count(*) FROM pg_stat_activity WHERE application_name = $1",
+    )
+    .bind(session_name(run_id))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(sessions as u64)
+}
+
+pub async fn terminate_sessions(pool: &sqlx::PgPool, run_id: Uuid) -> Result<u64, sqlx::Error> {
+    let (terminated,): (i64,) = sqlx::query_as(
+        "SELECT count(*) FILTER (WHERE pg_terminate_backend(pid))
+         FROM pg_stat_activity
+         WHERE application_name = $1 AND pid <> pg_backend_pid()",
+    )
+    .bind(session_name(run_id))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(terminated as u64)
 }
 
 pub async fn commit(
     tx: sqlx::Transaction<'_, sqlx::Postgres>,
-    migration_version: i64,
+    migration_version: &Version,
 ) -> Result<(), ExecutorError> {
     tx.commit()
         .await
         .map_err(|e| ExecutorError::TransactionCommitFailed {
-            migration_version,
+            migration_version: migration_version.clone(),
             message: e.to_string(),
         })
 }
 
 pub async fn rollback(
     tx: sqlx::Transaction<'_, sqlx::Postgres>,
-    migration_version: i64,
+    migration_version: &Version,
     original_error: &ExecutorError,
 ) -> Result<(), ExecutorError> {
     tx.rollback()
         .await
         .map_err(|e| ExecutorError::TransactionRollbackFailed {
-            migration_version,
+            migration_version: migration_version.clone(),
             message: e.to_string(),
             original_error: original_error.to_string(),
         })
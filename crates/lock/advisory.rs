@@ -1,6 +1,9 @@
 use crate::error::LockError;
+use core::time::Duration;
 use sqlx::PgConnection;
 
+const LOCK_NOT_AVAILABLE: &str = "55P03";
+
 pub const VELLUM_LOCK_KEY_NAMESPACE: u64 = 0x5645_4c4c_554d_4c4b;
 
 pub fn lock_key(database_name: &str) -> i64 {
@@ -25,6 +28,32 @@ pub async fn current_database(conn: &mut PgConnection) -> Result<String, LockErr
     Ok(row.0)
 }
 
+pub async fn backend_pid(conn: &mut PgConnection) -> Result<i32, LockError> {
+    let row: (i32,) = sqlx::query_as("select pg_backend_pid()")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| LockError::LockAcquireFailed {
+            message: format!("pg_backend_pid query failed: {e}"),
+        })?;
+
+    Ok(row.0)
+}
+
+// Server time in ISO 8601 UTC, which `::timestamptz` reads back exactly
+// whatever the session's DateStyle.
+pub async fn server_time(conn: &mut PgConnection) -> Result<String, LockError> {
+    let row: (String,) = sqlx::query_as(
+        "select to_char(clock_timestamp() at time zone 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS.US\"Z\"')",
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| LockError::LockAcquireFailed {
+        message: format!("clock_timestamp query failed: {e}"),
+    })?;
+
+    Ok(row.0)
+}
+
 pub async fn try_lock(conn: &mut PgConnection, key: i64) -> Result<bool, LockError> {
     let row: (bool,) = sqlx::query_as("select pg_try_advisory_lock($1)")
         .bind(key)
@@ -37,6 +66,64 @@ pub async fn try_lock(conn: &mut PgConnection, key: i64) -> Result<bool, LockErr
     Ok(row.0)
 }
 
+// Blocks in pg_advisory_lock for at most `wait`, so waiters queue for the lock
+// in order instead of racing each other with pg_try_advisory_lock.
+pub async fn lock_within(
+    conn: &mut PgConnection,
+    key: i64,
+    wait: Duration,
+) -> Result<bool, LockError> {
+    // lock_timeout = 0 disables the timeout, so never round down to it.
+    let wait_ms = wait.as_millis().max(1);
+    sqlx::query(&format!("set lock_timeout = {wait_ms}"))
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| LockError::LockAcquireFailed {
+            message: format!("setting lock_timeout failed: {e}"),
+        })?;
+
+    let locked = match sqlx::query("select pg_advisory_lock($1)")
+        .bind(key)
+        .execute(&mut *conn)
+        .await
+    {
+        Ok(_) => Ok(true),
+        Err(e) if is_lock_timeout(&e) => Ok(false),
+        Err(e) => Err(LockError::LockAcquireFailed {
+            message: format!("pg_advisory_lock failed: {e}"),
+        }),
+    };
+
+    sqlx::query("reset lock_timeout")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| LockError::LockAcquireFailed {
+            message: format!("resetting lock_timeout failed: {e}"),
+        })?;
+
+    locked
+}
+
+pub async fn is_held(conn: &mut PgConnection, key: i64) -> Result<bool, LockError> {
+    let bits = key as u64;
+    let row: (bool,) = sqlx::query_as(
+        "select exists (
+           select 1 from pg_locks
+           where locktype = 'advisory' and granted and pid = pg_backend_pid()
+             and objsubid = 1 and classid::bigint = $1 and objid::bigint = $2
+         )",
+    )
+    .bind((bits >> 32) as i64)
+    .bind((bits & 0xffff_ffff) as i64)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| LockError::LockLost {
+        message: format!("lock session did not answer: {e}"),
+    })?;
+
+    Ok(row.0)
+}
+
 pub async fn unlock(conn: &mut PgConnection, key: i64) -> Result<bool, LockError> {
     let row: (bool,) = sqlx::query_as("select pg_advisory_unlock($1)")
         .bind(key)
@@ -48,3 +135,9 @@ pub async fn unlock(conn: &mut PgConnection, key: i64) -> Result<bool, LockError
 
     Ok(row.0)
 }
+
+fn is_lock_timeout(err: &sqlx::Error) -> bool {
+    err.as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == LOCK_NOT_AVAILABLE)
+}
@@ -1,9 +1,11 @@
+use crate::holder::LockHolder;
 use core::fmt;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LockError {
     MigrationLockUnavailable {
         timeout_ms: u64,
+        holder: Option<Box<LockHolder>>,
     },
     LockAcquireFailed {
         message: String,
@@ -11,13 +13,23 @@ pub enum LockError {
     LockReleaseFailed {
         message: String,
     },
+    LockQueryFailed {
+        message: String,
+    },
+    LockLost {
+        message: String,
+    },
 }
 
 impl fmt::Display for LockError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            LockError::MigrationLockUnavailable { timeout_ms } => {
-                write!(f, "migration lock unavailable (timeout_ms={timeout_ms})")
+            LockError::MigrationLockUnavailable { timeout_ms, holder } => {
+                write!(f, "migration lock unavailable (timeout_ms={timeout_ms})")?;
+                match holder {
+                    Some(holder) => write!(f, "; held by {holder}"),
+                    None => Ok(()),
+                }
             }
             LockError::LockAcquireFailed { message } => {
                 write!(f, "lock acquire failed: {message}")
@@ -25,6 +37,12 @@ impl fmt::Display for LockError {
             LockError::LockReleaseFailed { message } => {
                 write!(f, "lock release failed: {message}")
             }
+            LockError::LockQueryFailed { message } => {
+                write!(f, "lock query failed: {message}")
+            }
+            LockError::LockLost { message } => {
+                write!(f, "migration lock lost: {message}")
+            }
         }
     }
 }
@@ -1,43 +1,86 @@
 use crate::advisory;
 use crate::error::LockError;
+use crate::holder;
+use crate::owner::{self, LockOwner};
+use crate::settings::{LockSettings, LockWait, LockWaiting};
 use core::time::Duration;
 use sqlx::{Connection, PgConnection};
-use tokio::time::{sleep, Instant};
+use tokio::time::{sleep, timeout, Instant};
+
+const WAIT_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(15);
 
 pub struct AdvisoryLockGuard {
     conn: Option<PgConnection>,
     key: i64,
+    backend_pid: i32,
+    acquired_at: String,
 }
 
 impl AdvisoryLockGuard {
-    pub async fn acquire(database_url: &str, timeout: Duration) -> Result<Self, LockError> {
-        let mut conn = PgConnection::connect(database_url)
-            .await
-            .map_err(|e| LockError::LockAcquireFailed {
-                message: format!("connect failed: {e}"),
-            })?;
+    // Takes the lock on a session the caller opened, so connecting follows
+    // the caller's retry policy. The session is owned by the guard from here
+    // on and closed when the lock is released or cannot be taken.
+    pub async fn acquire(
+        mut conn: PgConnection,
+        settings: &LockSettings,
+        lock_owner: &LockOwner,
+        mut on_wait: impl FnMut(&LockWaiting),
+    ) -> Result<Self, LockError> {
+        // Closing the connection drops the advisory lock as well.
+        match lock(&mut conn, settings, lock_owner, &mut on_wait).await {
+            Ok((key, backend_pid, acquired_at)) => Ok(Self {
+                conn: Some(conn),
+                key,
+                backend_pid,
+                acquired_at,
+            }),
+            Err(err) => {
+                let _ = conn.close().await;
+                Err(err)
+            }
+        }
+    }
 
-        let db_name = advisory::current_database(&mut conn).await?;
-        let key = advisory::lock_key(&db_name);
+    pub fn backend_pid(&self) -> i32 {
+        self.backend_pid
+    }
 
-        let deadline = Instant::now() + timeout;
-        let poll = Duration::from_millis(200);
+    // Server time at which the lock was granted, as an ISO 8601 UTC string.
+    pub fn acquired_at(&self) -> &str {
+        &self.acquired_at
+    }
+
+    // Resolves only when the lock session stops answering or no longer holds
+    // the lock, so a run can race its work against it.
+    pub async fn watch(&mut self) -> LockError {
+        let Some(conn) = self.conn.as_mut() else {
+            return LockError::LockLost {
+                message: "lock connection missing".to_string(),
+            };
+        };
 
         loop {
-            if advisory::try_lock(&mut conn, key).await? {
-                return Ok(Self {
-                    conn: Some(conn),
-                    key,
-                });
-            }
+            sleep(HEARTBEAT_INTERVAL).await;
 
-            if Instant::now() >= deadline {
-                return Err(LockError::MigrationLockUnavailable {
-                    timeout_ms: timeout.as_millis() as u64,
-                });
+            match timeout(HEARTBEAT_TIMEOUT, advisory::is_held(conn, self.key)).await {
+                Ok(Ok(true)) => {}
+                Ok(Ok(false)) => {
+                    return LockError::LockLost {
+                        message: "the lock session no longer holds the advisory lock".to_string(),
+                    }
+                }
+                Ok(Err(err)) => return err,
+                Err(_) => {
+                    return LockError::LockLost {
+                        message: format!(
+                            "the lock session did not answer within {}s",
+                            HEARTBEAT_TIMEOUT.as_secs()
+                        ),
+                    }
+                }
             }
-
-            sleep(poll).await;
         }
     }
 
@@ -51,6 +94,11 @@ impl AdvisoryLockGuard {
             }
         };
 
+        if let Err(err) = owner::clear(&mut conn, self.key).await {
+            let _ = conn.close().await;
+            return Err(err);
+        }
+
         match advisory::unlock(&mut conn, self.key).await {
             Ok(true) => conn.close().await.map_err(|e| LockError::LockReleaseFailed {
                 message: format!("connection close failed: {e}"),
@@ -80,3 +128,57 @@ impl AdvisoryLockGuard {
         }
     }
 }
+
+async fn lock(
+    conn: &mut PgConnection,
+    settings: &LockSettings,
+    lock_owner: &LockOwner,
+    on_wait: &mut impl FnMut(&LockWaiting),
+) -> Result<(i64, i32, String), LockError> {
+    let db_name = advisory::current_database(conn).await?;
+    let key = settings.scope.key(&db_name);
+
+    if !advisory::try_lock(conn, key).await? {
+        wait(conn, key, settings.wait, on_wait).await?;
+    }
+
+    let acquired_at = advisory::server_time(conn).await?;
+    owner::record(conn, key, lock_owner).await?;
+    Ok((key, advisory::backend_pid(conn).await?, acquired_at))
+}
+
+async fn wait(
+    conn: &mut PgConnection,
+    key: i64,
+    lock_wait: LockWait,
+    on_wait: &mut impl FnMut(&LockWaiting),
+) -> Result<(), LockError> {
+    let started = Instant::now();
+
+    loop {
+        let holder = holder::holder(conn, key).await.ok().flatten();
+
+        let slice = match lock_wait {
+            LockWait::NoWait => Duration::ZERO,
+            LockWait::Timeout(timeout) => timeout
+                .saturating_sub(started.elapsed())
+                .min(WAIT_REPORT_INTERVAL),
+            LockWait::Forever => WAIT_REPORT_INTERVAL,
+        };
+        if slice.is_zero() {
+            return Err(LockError::MigrationLockUnavailable {
+                timeout_ms: lock_wait.timeout_ms().unwrap_or_default(),
+                holder: holder.map(Box::new),
+            });
+        }
+
+        on_wait(&LockWaiting {
+            waited: started.elapsed(),
+            holder,
+        });
+
+        if advisory::lock_within(conn, key, slice).await? {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::error::LockError;
+use crate::owner;
+use core::fmt;
+use core::time::Duration;
+use sqlx::PgConnection;
+use tokio::time::{sleep, Instant};
+use uuid::Uuid;
+
+// A single bigint advisory key is split across classid (high half) and objid
+// (low half) in pg_locks, with objsubid = 1.
+const SQL_SELECT_HOLDER: &str = "
+SELECT a.pid, a.usename::text, a.application_name, a.client_addr::text, a.state,
+       to_char(a.backend_start, 'YYYY-MM-DD HH24:MI:SS TZ')
+FROM pg_locks l
+JOIN pg_stat_activity a ON a.pid = l.pid
+WHERE l.locktype = 'advisory'
+  AND l.granted
+  AND l.objsubid = 1
+  AND l.database = (SELECT oid FROM pg_database WHERE datname = current_database())
+  AND l.classid::bigint = $1
+  AND l.objid::bigint = $2
+";
+
+const SQL_SELECT_RECORD: &str = "
+SELECT o.owner_run_id, o.backend_pid, o.client_host, o.client_pid,
+       to_char(o.acquired_at, 'YYYY-MM-DD HH24:MI:SS TZ'),
+       extract(epoch FROM now() - o.acquired_at)::bigint,
+       r.mode
+FROM vellum.vellum_locks o
+LEFT JOIN vellum.vellum_runs r ON r.id = o.owner_run_id
+WHERE o.lock_key = $1
+";
+
+const SQL_DELETE_RECORD: &str = "DELETE FROM vellum.vellum_locks WHERE lock_key = $1";
+
+const SQL_BACKEND_ALIVE: &str = "SELECT EXISTS (SELECT 1 FROM pg_stat_activity WHERE pid = $1)";
+
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(5);
+const TERMINATE_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockHolder {
+    pub pid: i32,
+    pub user: Option<String>,
+    pub application_name: Option<String>,
+    pub client_addr: Option<String>,
+    pub state: Option<String>,
+    pub backend_start: Option<String>,
+    pub record: Option<LockRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockRecord {
+    pub run_id: Uuid,
+    pub run_mode: Option<String>,
+    pub backend_pid: Option<i32>,
+    pub client_host: Option<String>,
+    pub client_pid: Option<i32>,
+    pub acquired_at: String,
+    pub held_seconds: i64,
+}
+
+impl fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.record {
+            Some(record) => {
+                write!(f, "run {}", record.run_id)?;
+                if let Some(mode) = &record.run_mode {
+                    write!(f, " ({mode})")?;
+                }
+                if let Some(host) = &record.client_host {
+                    write!(f, " on {host}")?;
+                }
+                if let Some(pid) = record.client_pid {
+                    write!(f, " pid {pid}")?;
+                }
+                write!(
+                    f,
+                    ", backend pid {}, held for {}s",
+                    self.pid, record.held_seconds
+                )
+            }
+            None => {
+                write!(f, "backend pid {}", self.pid)?;
+                if let Some(user) = &self.user {
+                    write!(f, " ({user}")?;
+                    if let Some(addr) = &self.client_addr {
+                        write!(f, "@{addr}")?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+pub async fn holder(conn: &mut PgConnection, key: i64) -> Result<Option<LockHolder>, LockError> {
+    let bits = key as u64;
+    let row: Option<HolderRow> = sqlx::query_as(SQL_SELECT_HOLDER)
+        .bind((bits >> 32) as i64)
+        .bind((bits & 0xffff_ffff) as i64)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(query_failed)?;
+
+    let Some((pid, user, application_name, client_addr, state, backend_start)) = row else {
+        return Ok(None);
+    };
+
+    // The record is only trusted when it was written by the session that
+    // actually holds the lock; anything else is left over from a dead run.
+    let record = recorded_owner(conn, key)
+        .await?
+        .filter(|record| record.backend_pid == Some(pid));
+
+    Ok(Some(LockHolder {
+        pid,
+        user,
+        application_name,
+        client_addr,
+        state,
+        backend_start,
+        record,
+    }))
+}
+
+pub async fn recorded_owner(
+    conn: &mut PgConnection,
+    key: i64,
+) -> Result<Option<LockRecord>, LockError> {
+    if !owner::has_record_table(conn).await.map_err(query_failed)? {
+        return Ok(None);
+    }
+
+    let row: Option<RecordRow> = sqlx::query_as(SQL_SELECT_RECORD)
+        .bind(key.to_string())
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(query_failed)?;
+
+    Ok(row.map(
+        |(run_id, backend_pid, client_host, client_pid, acquired_at, held_seconds, run_mode)| {
+            LockRecord {
+                run_id,
+                run_mode,
+                backend_pid,
+                client_host,
+                client_pid,
+                acquired_at,
+                held_seconds,
+            }
+        },
+    ))
+}
+
+// The two-argument pg_terminate_backend that waits for the backend to exit
+// needs PostgreSQL 14, so the signal is sent with the one-argument form and
+// pg_stat_activity is polled until the backend is gone.
+pub async fn terminate(conn: &mut PgConnection, pid: i32) -> Result<bool, LockError> {
+    let (signalled,): (bool,) = sqlx::query_as("SELECT pg_terminate_backend($1)")
+        .bind(pid)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(query_failed)?;
+    if !signalled {
+        return Ok(false);
+    }
+
+    let deadline = Instant::now() + TERMINATE_TIMEOUT;
+    loop {
+        let (alive,): (bool,) = sqlx::query_as(SQL_BACKEND_ALIVE)
+            .bind(pid)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(query_failed)?;
+        if !alive {
+            return Ok(true);
+        }
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+        sleep(TERMINATE_POLL).await;
+    }
+}
+
+pub async fn clear_record(conn: &mut PgConnection, key: i64) -> Result<bool, LockError> {
+    if !owner::has_record_table(conn).await.map_err(query_failed)? {
+        return Ok(false);
+    }
+
+    let result = sqlx::query(SQL_DELETE_RECORD)
+        .bind(key.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(query_failed)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+type HolderRow = (
+    i32,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+type RecordRow = (
+    Uuid,
+    Option<i32>,
+    Option<String>,
+    Option<i32>,
+    String,
+    i64,
+    Option<String>,
+);
+
+fn query_failed(e: sqlx::Error) -> LockError {
+    LockError::LockQueryFailed {
+        message: e.to_string(),
+    }
+}
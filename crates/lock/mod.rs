@@ -1,6 +1,12 @@
 pub mod advisory;
 pub mod error;
 pub mod guard;
+pub mod holder;
+pub mod owner;
+pub mod settings;
 
 pub use error::LockError;
 pub use guard::AdvisoryLockGuard;
+pub use holder::{LockHolder, LockRecord};
+pub use owner::LockOwner;
+pub use settings::{LockScope, LockSettings, LockWait, LockWaiting, DEFAULT_LOCK_TIMEOUT};
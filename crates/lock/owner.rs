@@ -0,0 +1,94 @@
+use crate::error::LockError;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+const SQL_UPSERT_OWNER: &str = "
+INSERT INTO vellum.vellum_locks (lock_key, acquired_at, owner_run_id, backend_pid, client_host, client_pid)
+VALUES ($1, now(), $2, pg_backend_pid(), $3, $4)
+ON CONFLICT (lock_key) DO UPDATE
+SET acquired_at = EXCLUDED.acquired_at,
+    owner_run_id = EXCLUDED.owner_run_id,
+    backend_pid = EXCLUDED.backend_pid,
+    client_host = EXCLUDED.client_host,
+    client_pid = EXCLUDED.client_pid
+";
+
+const SQL_DELETE_OWNER: &str = "
+DELETE FROM vellum.vellum_locks
+WHERE lock_key = $1 AND backend_pid = pg_backend_pid()
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockOwner {
+    pub run_id: Uuid,
+    pub client_host: String,
+    pub client_pid: u32,
+}
+
+impl LockOwner {
+    pub fn current(run_id: Uuid) -> Self {
+        Self {
+            run_id,
+            client_host: whoami::fallible::hostname().unwrap_or_else(|_| "unknown".to_string()),
+            client_pid: std::process::id(),
+        }
+    }
+}
+
+pub(crate) async fn record(
+    conn: &mut PgConnection,
+    key: i64,
+    owner: &LockOwner,
+) -> Result<(), LockError> {
+    // A lock taken in another database (LockScope::database) may live where
+    // vellum was never bootstrapped, so there is nowhere to record the owner.
+    let exists = has_record_table(conn)
+        .await
+        .map_err(|e| LockError::LockAcquireFailed {
+            message: format!("recording lock owner failed: {e}"),
+        })?;
+    if !exists {
+        return Ok(());
+    }
+
+    sqlx::query(SQL_UPSERT_OWNER)
+        .bind(key.to_string())
+        .bind(owner.run_id)
+        .bind(&owner.client_host)
+        .bind(i64::from(owner.client_pid))
+        .execute(&mut *conn)
+        .await
+        .map(|_| ())
+        .map_err(|e| LockError::LockAcquireFailed {
+            message: format!("recording lock owner failed: {e}"),
+        })
+}
+
+pub(crate) async fn clear(conn: &mut PgConnection, key: i64) -> Result<(), LockError> {
+    let exists = has_record_table(conn)
+        .await
+        .map_err(|e| LockError::LockReleaseFailed {
+            message: format!("clearing lock owner failed: {e}"),
+        })?;
+    if !exists {
+        return Ok(());
+    }
+
+    sqlx::query(SQL_DELETE_OWNER)
+        .bind(key.to_string())
+        .execute(&mut *conn)
+        .await
+        .map(|_| ())
+        .map_err(|e| LockError::LockReleaseFailed {
+            message: format!("clearing lock owner failed: {e}"),
+        })
+}
+
+pub(crate) async fn has_record_table(conn: &mut PgConnection) -> Result<bool, sqlx::Error> {
+    let (exists,): (bool,) =
+        sqlx::query_as("SELECT to_regclass('vellum.vellum_locks') IS NOT NULL")
+            .fetch_one(&mut *conn)
+            .await?;
+
+    Ok(exists)
+}
@@ -0,0 +1,59 @@
+use crate::advisory;
+use crate::holder::LockHolder;
+use core::time::Duration;
+
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockWait {
+    NoWait,
+    Timeout(Duration),
+    Forever,
+}
+
+impl Default for LockWait {
+    fn default() -> Self {
+        LockWait::Timeout(DEFAULT_LOCK_TIMEOUT)
+    }
+}
+
+impl LockWait {
+    pub fn timeout_ms(&self) -> Option<u64> {
+        match self {
+            LockWait::NoWait => Some(0),
+            LockWait::Timeout(timeout) => Some(timeout.as_millis() as u64),
+            LockWait::Forever => None,
+        }
+    }
+}
+
+// Advisory locks are scoped to a single database. Without a namespace the key
+// is derived from the database name alone, which keeps it compatible with
+// runs that predate namespaces; `database` moves the lock to another database
+// on the same server so runs against different databases can share it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LockScope {
+    pub namespace: Option<String>,
+    pub database: Option<String>,
+}
+
+impl LockScope {
+    pub fn key(&self, lock_database: &str) -> i64 {
+        match &self.namespace {
+            Some(namespace) => advisory::lock_key(&format!("{lock_database}\0{namespace}")),
+            None => advisory::lock_key(lock_database),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LockSettings {
+    pub scope: LockScope,
+    pub wait: LockWait,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockWaiting {
+    pub waited: Duration,
+    pub holder: Option<LockHolder>,
+}
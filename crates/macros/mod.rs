@@ -0,0 +1,102 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use std::path::PathBuf;
+use syn::{parse_macro_input, LitStr};
+use vellum_migration::{discover_all_with, DiscoveryOptions, Migration};
+
+// Expands to a `Vec<Migration>` of the versioned migrations in `dir`, relative
+// to the calling crate's `Cargo.toml`. Repeatable (`R__`) files are rejected
+// with a compile error rather than dropped, since the embedded list has no
+// place for them. The expansion names its types through `vellum_core`, so it
+// is meant to be used as `vellum_core::embed_migrations!`.
+#[proc_macro]
+pub fn embed_migrations(input: TokenStream) -> TokenStream {
+    let dir = parse_macro_input!(input as LitStr);
+
+    match expand(&dir) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(dir: &LitStr) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(dir.span(), "CARGO_MANIFEST_DIR is not set"))?;
+    let path = PathBuf::from(manifest_dir).join(dir.value());
+
+    let discovered = discover_all_with(&path, &DiscoveryOptions::default()).map_err(|e| {
+        syn::Error::new(
+            dir.span(),
+            format!("embed_migrations!(\"{}\"): {e}", dir.value()),
+        )
+    })?;
+
+    if let Some(repeatable) = discovered.repeatable.first() {
+        return Err(syn::Error::new(
+            dir.span(),
+            format!(
+                "embed_migrations!(\"{}\"): repeatable migration '{}' cannot be embedded; \
+                 only versioned migrations are supported, move repeatables to a separate directory",
+                dir.value(),
+                repeatable.filename
+            ),
+        ));
+    }
+
+    let tracked = tracked_files(&path).map_err(|e| {
+        syn::Error::new(
+            dir.span(),
+            format!("embed_migrations!(\"{}\"): {e}", dir.value()),
+        )
+    })?;
+
+    let migrations = discovered
+        .versioned
+        .iter()
+        .map(|m| migration_tokens(&path, m))
+        .collect::<Vec<_>>();
+
+    Ok(quote! {
+        {
+            #(const _: &[u8] = ::core::include_bytes!(#tracked);)*
+            let migrations: ::std::vec::Vec<::vellum_core::__private::Migration> = ::std::vec![
+                #(#migrations),*
+            ];
+            migrations
+        }
+    })
+}
+
+fn migration_tokens(dir: &std::path::Path, m: &Migration) -> proc_macro2::TokenStream {
+    let parts = m.version.parts();
+    let name = &m.name;
+    let filename = &m.filename;
+    let checksum = &m.checksum;
+    let file_path = dir.join(&m.filename).display().to_string();
+
+    quote! {
+        ::vellum_core::__private::Migration::new(
+            ::vellum_core::__private::Version::new(::std::vec![#(#parts),*]),
+            ::std::string::String::from(#name),
+            ::std::string::String::from(#filename),
+            ::std::string::String::from(#checksum),
+            ::std::string::String::from(::core::include_str!(#file_path)),
+        )
+    }
+}
+
+// Every file in the directory, not only the embedded ones, is included so
+// that editing a down script or a file that fails discovery also rebuilds.
+// Stable Rust has no way to track the directory itself; adding a file needs
+// a `cargo:rerun-if-changed` on the directory in the caller's build script.
+fn tracked_files(dir: &std::path::Path) -> std::io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push(path.display().to_string());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,13 @@
+use vellum_core::__private::{discover_migrations, Version};
+use vellum_core::embed_migrations;
+
+#[test]
+fn embeds_discovered_migrations_with_checksums() {
+    let embedded = embed_migrations!("tests/migrations");
+    let discovered =
+        discover_migrations(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/migrations")).unwrap();
+
+    assert_eq!(embedded, discovered);
+    assert_eq!(embedded[0].version, Version::from(1));
+    assert_eq!(embedded[1].filename, "0002_users_email.sql");
+}
@@ -0,0 +1,2 @@
+-- Reverts 0002; never executed.
+ALTER TABLE users DROP COLUMN email;
@@ -1,4 +1,61 @@
 use sha2::{Digest, Sha256};
+use vellum_sql::SqlParseError;
+
+// Deparsed text is only stable for one parser release line, so the stored id
+// names the PostgreSQL major version of the parser: `normalized-sha256-pg17:`.
+// A parser upgrade then shows up as a different algorithm rather than as
+// tampering. Checksums stored before the version was part of the id were all
+// produced by the PostgreSQL 17 parser.
+const NORMALIZED_SHA256_PREFIX: &str = "normalized-sha256";
+const LEGACY_NORMALIZED_SHA256_PREFIX: &str = "normalized-sha256:";
+const LEGACY_PARSER_MAJOR_VERSION: i32 = 17;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChecksumAlgorithm {
+    #[default]
+    Sha256,
+    NormalizedSha256,
+}
+
+impl ChecksumAlgorithm {
+    pub fn id(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::NormalizedSha256 => "normalized-sha256",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "sha256" => Some(ChecksumAlgorithm::Sha256),
+            "normalized-sha256" => Some(ChecksumAlgorithm::NormalizedSha256),
+            _ => None,
+        }
+    }
+
+    pub fn of(checksum: &str) -> Self {
+        if checksum.starts_with(NORMALIZED_SHA256_PREFIX) {
+            ChecksumAlgorithm::NormalizedSha256
+        } else {
+            ChecksumAlgorithm::Sha256
+        }
+    }
+
+    pub fn compute(&self, sql: &str) -> Result<String, SqlParseError> {
+        match self {
+            ChecksumAlgorithm::Sha256 => Ok(sha256_hex(sql.as_bytes())),
+            ChecksumAlgorithm::NormalizedSha256 => {
+                let masked = crate::placeholder::mask(sql);
+                let parsed = vellum_sql::parse_sql(&masked)?;
+                Ok(format!(
+                    "{NORMALIZED_SHA256_PREFIX}-pg{}:{}",
+                    parsed.parser_major_version(),
+                    sha256_hex(parsed.normalized()?.as_bytes())
+                ))
+            }
+        }
+    }
+}
 
 pub fn sha256_hex(bytes: &[u8]) -> String {
     let mut hasher = Sha256::new();
@@ -7,9 +64,31 @@ pub fn sha256_hex(bytes: &[u8]) -> String {
     hex::encode(digest)
 }
 
+pub fn checksum_matches(stored: &str, sql: &str) -> bool {
+    let Ok(actual) = ChecksumAlgorithm::of(stored).compute(sql) else {
+        return false;
+    };
+    match stored.strip_prefix(LEGACY_NORMALIZED_SHA256_PREFIX) {
+        Some(digest) => {
+            actual == format!("{NORMALIZED_SHA256_PREFIX}-pg{LEGACY_PARSER_MAJOR_VERSION}:{digest}")
+        }
+        None => actual == stored,
+    }
+}
+
+// The parser major version a normalized checksum was recorded with, so a
+// mismatch caused by a parser upgrade can be told apart from an edited file.
+pub fn normalized_parser_version(checksum: &str) -> Option<i32> {
+    if checksum.starts_with(LEGACY_NORMALIZED_SHA256_PREFIX) {
+        return Some(LEGACY_PARSER_MAJOR_VERSION);
+    }
+    let rest = checksum.strip_prefix(NORMALIZED_SHA256_PREFIX)?.strip_prefix("-pg")?;
+    rest.split_once(':')?.0.parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::sha256_hex;
+    use super::{checksum_matches, normalized_parser_version, sha256_hex, ChecksumAlgorithm};
 
     #[test]
     fn sha256_hex_is_deterministic() {
@@ -24,4 +103,76 @@ mod tests {
         let expected = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
         assert_eq!(got, expected);
     }
+
+    #[test]
+    fn normalized_checksum_ignores_comments_and_line_endings() {
+        let a = ChecksumAlgorithm::NormalizedSha256
+            .compute("CREATE TABLE t (id int);\n")
+            .unwrap();
+        let b = ChecksumAlgorithm::NormalizedSha256
+            .compute("-- owner: billing\r\ncreate table t (\r\n  id int\r\n);  \r\n")
+            .unwrap();
+        assert_eq!(a, b);
+        assert!(a.starts_with("normalized-sha256-pg17:"));
+    }
+
+    #[test]
+    fn normalized_checksum_matches_pinned_value() {
+        // A parser bump that changes deparse output must also change the
+        // version in the id; update this value only together with it.
+        assert_eq!(
+            ChecksumAlgorithm::NormalizedSha256
+                .compute("create table t (id int);")
+                .unwrap(),
+            "normalized-sha256-pg17:2a0f26dda670d698fc504b41cf36a1d62c9330fac01a6196b32708f4fdaa570e"
+        );
+    }
+
+    #[test]
+    fn legacy_normalized_checksums_still_verify() {
+        let sql = "CREATE TABLE t (id int);";
+        let current = ChecksumAlgorithm::NormalizedSha256.compute(sql).unwrap();
+        let legacy = current.replace("normalized-sha256-pg17:", "normalized-sha256:");
+
+        assert_eq!(ChecksumAlgorithm::of(&legacy), ChecksumAlgorithm::NormalizedSha256);
+        assert!(checksum_matches(&legacy, sql));
+        assert!(!checksum_matches(&legacy, "CREATE TABLE u (id int);"));
+        assert_eq!(normalized_parser_version(&legacy), Some(17));
+    }
+
+    #[test]
+    fn checksums_from_another_parser_version_are_identifiable() {
+        let sql = "CREATE TABLE t (id int);";
+        let current = ChecksumAlgorithm::NormalizedSha256.compute(sql).unwrap();
+        let older = current.replace("-pg17:", "-pg16:");
+
+        assert!(!checksum_matches(&older, sql));
+        assert_eq!(normalized_parser_version(&older), Some(16));
+        assert_eq!(normalized_parser_version(&current), Some(17));
+        assert_eq!(normalized_parser_version("abc123"), None);
+    }
+
+    #[test]
+    fn both_schemes_verify_against_the_same_file() {
+        let sql = "SELECT 1;\n";
+        let raw = ChecksumAlgorithm::Sha256.compute(sql).unwrap();
+        let normalized = ChecksumAlgorithm::NormalizedSha256.compute(sql).unwrap();
+
+        assert_eq!(ChecksumAlgorithm::of(&raw), ChecksumAlgorithm::Sha256);
+        assert!(checksum_matches(&raw, sql));
+        assert!(checksum_matches(&normalized, "select 1; -- edited\n"));
+        assert!(!checksum_matches(&raw, "select 1; -- edited\n"));
+        assert!(!checksum_matches(&normalized, "SELECT 2;\n"));
+    }
+
+    #[test]
+    fn normalized_checksum_accepts_placeholders() {
+        let a = ChecksumAlgorithm::NormalizedSha256
+            .compute("create table ${schema}.t (id int);")
+            .unwrap();
+        let b = ChecksumAlgorithm::NormalizedSha256
+            .compute("-- comment\nCREATE TABLE ${schema}.t (id INT);")
+            .unwrap();
+        assert_eq!(a, b);
+    }
 }
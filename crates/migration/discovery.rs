@@ -1,69 +1,88 @@
-use crate::checksum::sha256_hex;
+use crate::checksum::ChecksumAlgorithm;
 use crate::error::MigrationDiscoveryError;
-use crate::model::Migration;
+use crate::filename::FilenameScheme;
+use crate::model::{Migration, RepeatableMigration};
+use crate::placeholder::{Placeholders, SourceMap};
+use crate::source::{FilesystemSource, MigrationSource, SourceFile};
+use crate::version::Version;
 use std::collections::HashMap;
-use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DiscoveryOptions {
+    pub checksum_algorithm: ChecksumAlgorithm,
+    pub filename_scheme: FilenameScheme,
+    pub placeholders: Option<Placeholders>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DiscoveredMigrations {
+    pub versioned: Vec<Migration>,
+    pub repeatable: Vec<RepeatableMigration>,
+}
 
 pub fn discover_migrations(dir: impl AsRef<Path>) -> Result<Vec<Migration>, MigrationDiscoveryError> {
-    let dir = dir.as_ref();
-    let dir_display = dir.display().to_string();
+    discover_migrations_with(dir, &DiscoveryOptions::default())
+}
 
-    let entries = fs::read_dir(dir).map_err(|e| MigrationDiscoveryError::Io {
-        path: dir_display.clone(),
-        message: e.to_string(),
-    })?;
+pub fn discover_migrations_with(
+    dir: impl AsRef<Path>,
+    options: &DiscoveryOptions,
+) -> Result<Vec<Migration>, MigrationDiscoveryError> {
+    discover_all_with(dir, options).map(|d| d.versioned)
+}
 
-    let mut files: Vec<PathBuf> = Vec::new();
-    for entry in entries {
-        let entry = entry.map_err(|e| MigrationDiscoveryError::Io {
-            path: dir_display.clone(),
-            message: e.to_string(),
-        })?;
+pub fn discover_all_with(
+    dir: impl AsRef<Path>,
+    options: &DiscoveryOptions,
+) -> Result<DiscoveredMigrations, MigrationDiscoveryError> {
+    discover_from(&FilesystemSource::new(dir.as_ref()), options)
+}
 
-        let path = entry.path();
-        if path.is_file() {
-            files.push(path);
-        }
-    }
+pub fn discover_from(
+    source: &dyn MigrationSource,
+    options: &DiscoveryOptions,
+) -> Result<DiscoveredMigrations, MigrationDiscoveryError> {
+    let algorithm = options.checksum_algorithm;
+    let location = source.location();
 
-    if files.is_empty() {
-        return Err(MigrationDiscoveryError::EmptyMigrationsDir { dir: dir_display });
-    }
+    let mut files = source.files()?;
+    files.sort_by(|a, b| a.filename.cmp(&b.filename));
 
-    files.sort_by(|a, b| {
-        let a_name = a.file_name().map(|s| s.to_string_lossy()).unwrap_or_default();
-        let b_name = b.file_name().map(|s| s.to_string_lossy()).unwrap_or_default();
-        a_name.cmp(&b_name)
-    });
-
-    let mut candidates: Vec<(i64, String, String, PathBuf)> = Vec::new();
-    for path in files {
-        let filename_os = path.file_name().ok_or_else(|| MigrationDiscoveryError::Io {
-            path: path.display().to_string(),
-            message: "missing filename".to_string(),
-        })?;
-        let filename = filename_os.to_string_lossy().to_string();
+    if let Some(pair) = files.windows(2).find(|w| w[0].filename == w[1].filename) {
+        return Err(MigrationDiscoveryError::DuplicateFile {
+            filename: pair[0].filename.clone(),
+            location,
+        });
+    }
 
-        if !filename.ends_with(".sql") {
-            return Err(MigrationDiscoveryError::InvalidFilename {
-                filename,
-                reason: "file extension must be .sql".to_string(),
-            });
+    let mut candidates: Vec<(Version, String, SourceFile)> = Vec::new();
+    let mut repeatable_candidates: Vec<(String, SourceFile)> = Vec::new();
+    for file in files {
+        if let Some(name) = options.filename_scheme.parse_repeatable(&file.filename)? {
+            repeatable_candidates.push((name, file));
+            continue;
         }
 
-        let (version, name) = parse_filename(&filename)?;
-        candidates.push((version, name, filename, path));
+        let Some(parsed) = options.filename_scheme.parse(&file.filename)? else {
+            continue;
+        };
+        candidates.push((parsed.version, parsed.name, file));
+    }
+
+    if candidates.is_empty() && repeatable_candidates.is_empty() {
+        return Err(MigrationDiscoveryError::EmptyMigrationsDir { dir: location });
     }
 
-    candidates.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.2.cmp(&b.2)));
+    candidates.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.2.filename.cmp(&b.2.filename)));
 
-    let mut seen: HashMap<i64, String> = HashMap::new();
-    let mut out = Vec::with_capacity(candidates.len());
+    let mut seen: HashMap<Version, String> = HashMap::new();
+    let mut versioned = Vec::with_capacity(candidates.len());
 
-    for (version, name, filename, path) in candidates {
+    for (version, name, file) in candidates {
+        let filename = file.filename;
 
-        if let Some(first) = seen.insert(version, filename.clone()) {
+        if let Some(first) = seen.insert(version.clone(), filename.clone()) {
             return Err(MigrationDiscoveryError::DuplicateVersion {
                 version,
                 first,
@@ -71,79 +90,81 @@ pub fn discover_migrations(dir: impl AsRef<Path>) -> Result<Vec<Migration>, Migr
             });
         }
 
-        let bytes = fs::read(&path).map_err(|e| MigrationDiscoveryError::Io {
-            path: path.display().to_string(),
-            message: e.to_string(),
-        })?;
+        let (sql, checksum) = decode_migration_file(&location, &filename, file.contents, algorithm)?;
+        let (rendered, map) = render(options, &sql, &filename)?;
+        versioned.push(Migration::new(version, name, filename, checksum, sql).with_rendered_sql(rendered, map));
+    }
 
-        let checksum = sha256_hex(&bytes);
-        let sql = String::from_utf8(bytes).map_err(|e| MigrationDiscoveryError::Io {
-            path: path.display().to_string(),
-            message: format!("file is not valid UTF-8: {e}"),
-        })?;
+    repeatable_candidates.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.filename.cmp(&b.1.filename)));
 
-        out.push(Migration::new(version, name, filename, checksum, sql));
+    let mut repeatable = Vec::with_capacity(repeatable_candidates.len());
+    for (name, file) in repeatable_candidates {
+        let filename = file.filename;
+        let (sql, checksum) = decode_migration_file(&location, &filename, file.contents, algorithm)?;
+        let (rendered, map) = render(options, &sql, &filename)?;
+        repeatable.push(
+            RepeatableMigration::new(name, filename, checksum, sql).with_rendered_sql(rendered, map),
+        );
     }
 
-    Ok(out)
+    Ok(DiscoveredMigrations {
+        versioned,
+        repeatable,
+    })
 }
 
-fn parse_filename(filename: &str) -> Result<(i64, String), MigrationDiscoveryError> {
-    if filename.contains(std::path::MAIN_SEPARATOR) {
-        return Err(MigrationDiscoveryError::InvalidFilename {
-            filename: filename.to_string(),
-            reason: "filename must not contain path separators".to_string(),
-        });
-    }
-
-    if !filename.ends_with(".sql") {
-        return Err(MigrationDiscoveryError::InvalidFilename {
-            filename: filename.to_string(),
-            reason: "file extension must be .sql".to_string(),
-        });
+fn render(
+    options: &DiscoveryOptions,
+    sql: &str,
+    filename: &str,
+) -> Result<(String, SourceMap), MigrationDiscoveryError> {
+    match &options.placeholders {
+        Some(placeholders) => placeholders.render_mapped(sql, filename),
+        None => Ok((sql.to_string(), SourceMap::default())),
     }
+}
 
-    let base = &filename[..filename.len() - 4];
-    let (version_str, name) = base.split_once('_').ok_or_else(|| {
-        MigrationDiscoveryError::InvalidFilename {
-            filename: filename.to_string(),
-            reason: "expected format <version>_<name>.sql".to_string(),
-        }
+fn decode_migration_file(
+    location: &str,
+    filename: &str,
+    contents: Vec<u8>,
+    algorithm: ChecksumAlgorithm,
+) -> Result<(String, String), MigrationDiscoveryError> {
+    let sql = String::from_utf8(contents).map_err(|e| MigrationDiscoveryError::Io {
+        path: Path::new(location).join(filename).display().to_string(),
+        message: format!("file is not valid UTF-8: {e}"),
     })?;
 
-    if name.is_empty() {
-        return Err(MigrationDiscoveryError::InvalidFilename {
+    let checksum = algorithm
+        .compute(&sql)
+        .map_err(|e| MigrationDiscoveryError::ChecksumFailed {
             filename: filename.to_string(),
-            reason: "name segment must not be empty".to_string(),
-        });
-    }
-
-    if !version_str.chars().all(|c| c.is_ascii_digit()) {
-        return Err(MigrationDiscoveryError::InvalidFilename {
-            filename: filename.to_string(),
-            reason: "version must be a positive integer".to_string(),
-        });
-    }
+            algorithm: algorithm.id().to_string(),
+            message: e.to_string(),
+        })?;
 
-    let version: i64 = version_str.parse().map_err(|_| MigrationDiscoveryError::InvalidFilename {
-        filename: filename.to_string(),
-        reason: "version is not a valid i64".to_string(),
-    })?;
+    Ok((sql, checksum))
+}
 
-    if version <= 0 {
-        return Err(MigrationDiscoveryError::InvalidFilename {
+pub fn parse_filename(filename: &str) -> Result<(Version, String), MigrationDiscoveryError> {
+    match FilenameScheme::Vellum.parse(filename)? {
+        Some(parsed) => Ok((parsed.version, parsed.name)),
+        None => Err(MigrationDiscoveryError::InvalidFilename {
             filename: filename.to_string(),
-            reason: "version must be a positive integer".to_string(),
-        });
+            reason: "down migrations are not executable migrations".to_string(),
+        }),
     }
-
-    Ok((version, name.to_string()))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::discover_migrations;
+    use super::{
+        discover_all_with, discover_from, discover_migrations, discover_migrations_with,
+        DiscoveryOptions,
+    };
     use crate::error::MigrationDiscoveryError;
+    use crate::filename::FilenameScheme;
+    use crate::version::Version;
     use std::fs;
 
     #[test]
@@ -169,11 +190,124 @@ mod tests {
         fs::write(tmp.path().join("1_a.sql"), b"select 1;").unwrap();
 
         let migrations = discover_migrations(tmp.path()).unwrap();
-        let versions: Vec<i64> = migrations.into_iter().map(|m| m.version).collect();
-        assert_eq!(versions, vec![1, 2, 10]);
+        let versions: Vec<Version> = migrations.into_iter().map(|m| m.version).collect();
+        assert_eq!(versions, vec![Version::from(1), Version::from(2), Version::from(10)]);
+
+        fs::write(tmp.path().join("2_b.down.sql"), b"select 2;").unwrap();
+        assert_eq!(discover_migrations(tmp.path()).unwrap().len(), 3);
 
         fs::write(tmp.path().join("2_dup.sql"), b"select 2b;").unwrap();
         let err = discover_migrations(tmp.path()).unwrap_err();
-        assert!(matches!(err, MigrationDiscoveryError::DuplicateVersion { version: 2, .. }));
+        assert!(matches!(
+            err,
+            MigrationDiscoveryError::DuplicateVersion { ref version, .. } if *version == Version::from(2)
+        ));
+    }
+
+    #[test]
+    fn flyway_scheme_orders_multi_part_versions() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("V1.10__c.sql"), b"select 3;").unwrap();
+        fs::write(tmp.path().join("V1.2__b.sql"), b"select 2;").unwrap();
+        fs::write(tmp.path().join("V1__a.sql"), b"select 1;").unwrap();
+
+        let options = DiscoveryOptions {
+            filename_scheme: FilenameScheme::Flyway,
+            ..DiscoveryOptions::default()
+        };
+        let migrations = discover_migrations_with(tmp.path(), &options).unwrap();
+        let versions: Vec<String> = migrations.iter().map(|m| m.version.to_string()).collect();
+        assert_eq!(versions, vec!["1", "1.2", "1.10"]);
+
+        fs::write(tmp.path().join("V1.2.0__dup.sql"), b"select 4;").unwrap();
+        let err = discover_migrations_with(tmp.path(), &options).unwrap_err();
+        assert!(matches!(err, MigrationDiscoveryError::DuplicateVersion { .. }));
+    }
+
+    #[test]
+    fn repeatable_migrations_follow_versioned_in_name_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("1_init.sql"), b"select 1;").unwrap();
+        fs::write(tmp.path().join("R_views.sql"), b"select 2;").unwrap();
+        fs::write(tmp.path().join("R_grants.sql"), b"select 3;").unwrap();
+
+        let discovered = discover_all_with(tmp.path(), &DiscoveryOptions::default()).unwrap();
+        assert_eq!(discovered.versioned.len(), 1);
+        let names: Vec<&str> = discovered.repeatable.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["grants", "views"]);
+
+        assert_eq!(discover_migrations(tmp.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn placeholders_render_sql_but_not_checksums() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("1_init.sql"), b"create schema ${schema};").unwrap();
+
+        let mut placeholders = crate::placeholder::Placeholders::new();
+        placeholders.insert("schema", "app");
+        let options = DiscoveryOptions {
+            placeholders: Some(placeholders),
+            ..DiscoveryOptions::default()
+        };
+
+        let rendered = discover_migrations_with(tmp.path(), &options).unwrap();
+        let raw = discover_migrations(tmp.path()).unwrap();
+        assert_eq!(rendered[0].sql, "create schema app;");
+        assert_eq!(rendered[0].checksum, raw[0].checksum);
+
+        let err = discover_migrations_with(
+            tmp.path(),
+            &DiscoveryOptions {
+                placeholders: Some(crate::placeholder::Placeholders::new()),
+                ..DiscoveryOptions::default()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, MigrationDiscoveryError::UnknownPlaceholder { line: 1, column: 15, .. }));
+    }
+
+    #[test]
+    fn literal_placeholder_syntax_in_comments_and_bodies_is_left_alone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let sql = "-- templates look like ${name}\n\
+                   create function greet() returns text as $$ select '${name}' $$ language sql;";
+        fs::write(tmp.path().join("1_init.sql"), sql).unwrap();
+
+        let unconfigured = discover_migrations_with(tmp.path(), &DiscoveryOptions::default()).unwrap();
+        assert_eq!(unconfigured[0].sql, sql);
+
+        let mut placeholders = crate::placeholder::Placeholders::new();
+        placeholders.insert("schema", "app");
+        let configured = discover_migrations_with(
+            tmp.path(),
+            &DiscoveryOptions {
+                placeholders: Some(placeholders),
+                checksum_algorithm: crate::checksum::ChecksumAlgorithm::NormalizedSha256,
+                ..DiscoveryOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(configured[0].sql, sql);
+    }
+
+    #[test]
+    fn in_memory_source_shares_filesystem_validation() {
+        let source = crate::source::InMemorySource::default()
+            .with_file("2_b.sql", "select 2;")
+            .with_file("10_c.sql", "select 10;")
+            .with_file("R_views.sql", "select 3;");
+        let discovered = discover_from(&source, &DiscoveryOptions::default()).unwrap();
+        let versions: Vec<String> = discovered.versioned.iter().map(|m| m.version.to_string()).collect();
+        assert_eq!(versions, vec!["2", "10"]);
+        assert_eq!(discovered.repeatable.len(), 1);
+
+        let duplicate = source.with_file("02_dup.sql", "select 4;");
+        let err = discover_from(&duplicate, &DiscoveryOptions::default()).unwrap_err();
+        assert!(matches!(err, MigrationDiscoveryError::DuplicateVersion { .. }));
+
+        let invalid = crate::source::InMemorySource::default().with_file("x.sql", "select 1;");
+        let err = discover_from(&invalid, &DiscoveryOptions::default()).unwrap_err();
+        assert!(matches!(err, MigrationDiscoveryError::InvalidFilename { .. }));
     }
 }
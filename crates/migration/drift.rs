@@ -1,10 +1,11 @@
 use crate::error::MigrationDriftError;
 use crate::model::Migration;
+use crate::version::Version;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DbMigration {
-    pub version: i64,
+    pub version: Version,
     pub checksum: String,
 }
 
@@ -12,21 +13,22 @@ pub fn detect_drift(
     filesystem: &[Migration],
     db: &[DbMigration],
 ) -> Result<(), MigrationDriftError> {
-    let mut fs_by_version: HashMap<i64, &Migration> = HashMap::with_capacity(filesystem.len());
+    let mut fs_by_version: HashMap<&Version, &Migration> =
+        HashMap::with_capacity(filesystem.len());
     for m in filesystem {
-        fs_by_version.insert(m.version, m);
+        fs_by_version.insert(&m.version, m);
     }
 
     for dbm in db {
         let Some(fsm) = fs_by_version.get(&dbm.version) else {
             return Err(MigrationDriftError::MissingMigrationFile {
-                version: dbm.version,
+                version: dbm.version.clone(),
             });
         };
 
-        if fsm.checksum != dbm.checksum {
+        if !fsm.matches_checksum(&dbm.checksum) {
             return Err(MigrationDriftError::ChecksumMismatch {
-                version: dbm.version,
+                version: dbm.version.clone(),
                 expected: dbm.checksum.clone(),
                 actual: fsm.checksum.clone(),
             });
@@ -40,10 +42,11 @@ pub fn detect_drift(
 mod tests {
     use super::{detect_drift, DbMigration};
     use crate::model::Migration;
+    use crate::version::Version;
 
-    fn m(version: i64, checksum: &str) -> Migration {
+    fn m(version: u64, checksum: &str) -> Migration {
         Migration::new(
-            version,
+            Version::from(version),
             format!("m{version}"),
             format!("{version}_m{version}.sql"),
             checksum.to_string(),
@@ -56,11 +59,11 @@ mod tests {
         let fs = vec![m(1, "a"), m(2, "b"), m(3, "c")];
         let db = vec![
             DbMigration {
-                version: 1,
+                version: Version::from(1),
                 checksum: "a".to_string(),
             },
             DbMigration {
-                version: 2,
+                version: Version::from(2),
                 checksum: "b".to_string(),
             },
         ];
@@ -71,21 +74,27 @@ mod tests {
     fn error_when_db_has_missing_file() {
         let fs = vec![m(2, "b")];
         let db = vec![DbMigration {
-            version: 1,
+            version: Version::from(1),
             checksum: "a".to_string(),
         }];
         let err = detect_drift(&fs, &db).unwrap_err();
-        assert!(matches!(err, crate::error::MigrationDriftError::MissingMigrationFile { version: 1 }));
+        assert!(matches!(
+            err,
+            crate::error::MigrationDriftError::MissingMigrationFile { ref version } if *version == Version::from(1)
+        ));
     }
 
     #[test]
     fn error_when_checksum_mismatch() {
         let fs = vec![m(1, "fs")];
         let db = vec![DbMigration {
-            version: 1,
+            version: Version::from(1),
             checksum: "db".to_string(),
         }];
         let err = detect_drift(&fs, &db).unwrap_err();
-        assert!(matches!(err, crate::error::MigrationDriftError::ChecksumMismatch { version: 1, .. }));
+        assert!(matches!(
+            err,
+            crate::error::MigrationDriftError::ChecksumMismatch { ref version, .. } if *version == Version::from(1)
+        ));
     }
 }
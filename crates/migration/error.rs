@@ -1,12 +1,31 @@
+use crate::version::Version;
 use std::error::Error;
 use std::fmt;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionParseError {
+    pub input: String,
+    pub reason: String,
+}
+
+impl fmt::Display for VersionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid migration version '{}': {}", self.input, self.reason)
+    }
+}
+
+impl Error for VersionParseError {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MigrationDiscoveryError {
     EmptyMigrationsDir { dir: String },
     InvalidFilename { filename: String, reason: String },
-    DuplicateVersion { version: i64, first: String, second: String },
+    DuplicateVersion { version: Version, first: String, second: String },
+    DuplicateFile { filename: String, location: String },
     Io { path: String, message: String },
+    ChecksumFailed { filename: String, algorithm: String, message: String },
+    UnknownPlaceholder { filename: String, line: usize, column: usize, name: String },
+    InvalidPlaceholder { filename: String, line: usize, column: usize, reason: String },
 }
 
 impl fmt::Display for MigrationDiscoveryError {
@@ -26,9 +45,35 @@ impl fmt::Display for MigrationDiscoveryError {
                 f,
                 "duplicate migration version {version}: '{first}' and '{second}'"
             ),
+            MigrationDiscoveryError::DuplicateFile { filename, location } => {
+                write!(f, "duplicate migration file '{filename}' in {location}")
+            }
             MigrationDiscoveryError::Io { path, message } => {
                 write!(f, "I/O error while reading '{path}': {message}")
             }
+            MigrationDiscoveryError::ChecksumFailed {
+                filename,
+                algorithm,
+                message,
+            } => write!(
+                f,
+                "failed to compute {algorithm} checksum for '{filename}': {message}"
+            ),
+            MigrationDiscoveryError::UnknownPlaceholder {
+                filename,
+                line,
+                column,
+                name,
+            } => write!(
+                f,
+                "unknown placeholder '${{{name}}}' at {filename}:{line}:{column}"
+            ),
+            MigrationDiscoveryError::InvalidPlaceholder {
+                filename,
+                line,
+                column,
+                reason,
+            } => write!(f, "invalid placeholder at {filename}:{line}:{column}: {reason}"),
         }
     }
 }
@@ -37,9 +82,9 @@ impl Error for MigrationDiscoveryError {}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MigrationDriftError {
-    MissingMigrationFile { version: i64 },
+    MissingMigrationFile { version: Version },
     ChecksumMismatch {
-        version: i64,
+        version: Version,
         expected: String,
         actual: String,
     },
@@ -65,3 +110,30 @@ impl fmt::Display for MigrationDriftError {
 }
 
 impl Error for MigrationDriftError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationScaffoldError {
+    InvalidName { name: String, reason: String },
+    AlreadyExists { path: String },
+    Discovery { error: MigrationDiscoveryError },
+    Io { path: String, message: String },
+}
+
+impl fmt::Display for MigrationScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationScaffoldError::InvalidName { name, reason } => {
+                write!(f, "invalid migration name '{name}': {reason}")
+            }
+            MigrationScaffoldError::AlreadyExists { path } => {
+                write!(f, "migration file already exists: {path}")
+            }
+            MigrationScaffoldError::Discovery { error } => write!(f, "{error}"),
+            MigrationScaffoldError::Io { path, message } => {
+                write!(f, "I/O error while writing '{path}': {message}")
+            }
+        }
+    }
+}
+
+impl Error for MigrationScaffoldError {}
@@ -0,0 +1,287 @@
+use crate::error::MigrationDiscoveryError;
+use crate::version::{parse_parts, Version};
+
+pub const DOWN_SUFFIX: &str = ".down.sql";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilenameScheme {
+    #[default]
+    Vellum,
+    Flyway,
+    GolangMigrate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedFilename {
+    pub version: Version,
+    pub version_text: String,
+    pub name: String,
+}
+
+impl FilenameScheme {
+    pub fn id(&self) -> &'static str {
+        match self {
+            FilenameScheme::Vellum => "vellum",
+            FilenameScheme::Flyway => "flyway",
+            FilenameScheme::GolangMigrate => "golang-migrate",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "vellum" => Some(FilenameScheme::Vellum),
+            "flyway" => Some(FilenameScheme::Flyway),
+            "golang-migrate" => Some(FilenameScheme::GolangMigrate),
+            _ => None,
+        }
+    }
+
+    pub fn expected_format(&self) -> &'static str {
+        match self {
+            FilenameScheme::Vellum => "<version>_<name>.sql",
+            FilenameScheme::Flyway => "V<version>__<description>.sql",
+            FilenameScheme::GolangMigrate => "<version>_<name>.up.sql",
+        }
+    }
+
+    pub fn repeatable_format(&self) -> &'static str {
+        match self {
+            FilenameScheme::Vellum => "R_<name>.sql",
+            FilenameScheme::Flyway => "R__<description>.sql",
+            FilenameScheme::GolangMigrate => "R_<name>.up.sql",
+        }
+    }
+
+    pub fn parse(&self, filename: &str) -> Result<Option<ParsedFilename>, MigrationDiscoveryError> {
+        check_extension(filename)?;
+
+        if self.parse_repeatable(filename)?.is_some() {
+            return Ok(None);
+        }
+
+        match self {
+            FilenameScheme::Vellum => {
+                if filename.ends_with(DOWN_SUFFIX) {
+                    return Ok(None);
+                }
+                parse_vellum(filename).map(Some)
+            }
+            FilenameScheme::Flyway => {
+                if filename.starts_with('U') {
+                    return Ok(None);
+                }
+                parse_flyway(filename).map(Some)
+            }
+            FilenameScheme::GolangMigrate => {
+                if filename.ends_with(DOWN_SUFFIX) {
+                    return Ok(None);
+                }
+                parse_golang_migrate(filename).map(Some)
+            }
+        }
+    }
+
+    pub fn parse_repeatable(&self, filename: &str) -> Result<Option<String>, MigrationDiscoveryError> {
+        check_extension(filename)?;
+
+        if filename.ends_with(DOWN_SUFFIX) {
+            return Ok(None);
+        }
+
+        let (prefix, suffix) = match self {
+            FilenameScheme::Vellum => ("R_", ".sql"),
+            FilenameScheme::Flyway => ("R__", ".sql"),
+            FilenameScheme::GolangMigrate => ("R_", ".up.sql"),
+        };
+
+        let Some(name) = filename
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(suffix))
+        else {
+            return Ok(None);
+        };
+
+        if name.is_empty() {
+            return Err(invalid(filename, "repeatable migration name must not be empty"));
+        }
+
+        Ok(Some(name.to_string()))
+    }
+
+    pub fn format(&self, version_text: &str, name: &str) -> String {
+        match self {
+            FilenameScheme::Vellum => format!("{version_text}_{name}.sql"),
+            FilenameScheme::Flyway => format!("V{version_text}__{name}.sql"),
+            FilenameScheme::GolangMigrate => format!("{version_text}_{name}.up.sql"),
+        }
+    }
+
+    pub fn down_filename(&self, version_text: &str, name: &str) -> String {
+        match self {
+            FilenameScheme::Vellum | FilenameScheme::GolangMigrate => {
+                format!("{version_text}_{name}{DOWN_SUFFIX}")
+            }
+            FilenameScheme::Flyway => format!("U{version_text}__{name}.sql"),
+        }
+    }
+}
+
+fn check_extension(filename: &str) -> Result<(), MigrationDiscoveryError> {
+    if filename.contains(std::path::MAIN_SEPARATOR) {
+        return Err(invalid(filename, "filename must not contain path separators"));
+    }
+
+    if !filename.ends_with(".sql") {
+        return Err(invalid(filename, "file extension must be .sql"));
+    }
+
+    Ok(())
+}
+
+fn parse_vellum(filename: &str) -> Result<ParsedFilename, MigrationDiscoveryError> {
+    let base = &filename[..filename.len() - 4];
+    let (version_str, name) = base
+        .split_once('_')
+        .ok_or_else(|| invalid(filename, "expected format <version>_<name>.sql"))?;
+
+    if name.is_empty() {
+        return Err(invalid(filename, "name segment must not be empty"));
+    }
+
+    if version_str.is_empty() || !version_str.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid(filename, "version must be a positive integer"));
+    }
+
+    parsed(filename, version_str, name, &[])
+}
+
+fn parse_flyway(filename: &str) -> Result<ParsedFilename, MigrationDiscoveryError> {
+    let base = &filename[..filename.len() - 4];
+    let rest = base
+        .strip_prefix('V')
+        .ok_or_else(|| invalid(filename, "expected format V<version>__<description>.sql"))?;
+    let (version_str, name) = rest
+        .split_once("__")
+        .ok_or_else(|| invalid(filename, "expected format V<version>__<description>.sql"))?;
+
+    if name.is_empty() {
+        return Err(invalid(filename, "description must not be empty"));
+    }
+
+    parsed(filename, version_str, name, &['.', '_'])
+}
+
+fn parse_golang_migrate(filename: &str) -> Result<ParsedFilename, MigrationDiscoveryError> {
+    let base = filename
+        .strip_suffix(".up.sql")
+        .ok_or_else(|| invalid(filename, "expected format <version>_<name>.up.sql"))?;
+    let (version_str, name) = base
+        .split_once('_')
+        .ok_or_else(|| invalid(filename, "expected format <version>_<name>.up.sql"))?;
+
+    if name.is_empty() {
+        return Err(invalid(filename, "name segment must not be empty"));
+    }
+
+    parsed(filename, version_str, name, &[])
+}
+
+fn parsed(
+    filename: &str,
+    version_str: &str,
+    name: &str,
+    extra_separators: &[char],
+) -> Result<ParsedFilename, MigrationDiscoveryError> {
+    let mut separators = vec!['.'];
+    separators.extend_from_slice(extra_separators);
+
+    let version =
+        parse_parts(version_str, &separators).map_err(|e| invalid(filename, &e.to_string()))?;
+
+    if version.is_zero() {
+        return Err(invalid(filename, "version must be a positive integer"));
+    }
+
+    Ok(ParsedFilename {
+        version,
+        version_text: version_str.to_string(),
+        name: name.to_string(),
+    })
+}
+
+fn invalid(filename: &str, reason: &str) -> MigrationDiscoveryError {
+    MigrationDiscoveryError::InvalidFilename {
+        filename: filename.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilenameScheme;
+    use crate::version::Version;
+
+    fn version(scheme: FilenameScheme, filename: &str) -> Version {
+        scheme.parse(filename).unwrap().unwrap().version
+    }
+
+    #[test]
+    fn vellum_scheme_accepts_integers_only() {
+        assert_eq!(version(FilenameScheme::Vellum, "0042_users.sql"), Version::from(42));
+        assert!(FilenameScheme::Vellum.parse("1.2_users.sql").is_err());
+        assert!(FilenameScheme::Vellum.parse("0_users.sql").is_err());
+        assert_eq!(FilenameScheme::Vellum.parse("1_users.down.sql").unwrap(), None);
+    }
+
+    #[test]
+    fn flyway_scheme_parses_dotted_and_underscored_versions() {
+        let parsed = FilenameScheme::Flyway
+            .parse("V1.2.3__add_users.sql")
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.version, "1.2.3".parse().unwrap());
+        assert_eq!(parsed.name, "add_users");
+
+        assert_eq!(
+            version(FilenameScheme::Flyway, "V1_2__x.sql"),
+            "1.2".parse().unwrap()
+        );
+        assert_eq!(FilenameScheme::Flyway.parse("U1.2__x.sql").unwrap(), None);
+        assert!(FilenameScheme::Flyway.parse("1_x.sql").is_err());
+    }
+
+    #[test]
+    fn golang_migrate_scheme_uses_up_files() {
+        assert_eq!(
+            version(FilenameScheme::GolangMigrate, "20240101120000_desc.up.sql"),
+            Version::from(20240101120000)
+        );
+        assert_eq!(
+            FilenameScheme::GolangMigrate
+                .parse("20240101120000_desc.down.sql")
+                .unwrap(),
+            None
+        );
+        assert!(FilenameScheme::GolangMigrate.parse("1_desc.sql").is_err());
+    }
+
+    #[test]
+    fn repeatable_files_use_scheme_prefix() {
+        let cases = [
+            (FilenameScheme::Vellum, "R_views.sql"),
+            (FilenameScheme::Flyway, "R__views.sql"),
+            (FilenameScheme::GolangMigrate, "R_views.up.sql"),
+        ];
+        for (scheme, filename) in cases {
+            assert_eq!(
+                scheme.parse_repeatable(filename).unwrap().as_deref(),
+                Some("views"),
+                "{filename}"
+            );
+            assert_eq!(scheme.parse(filename).unwrap(), None, "{filename}");
+        }
+
+        assert_eq!(FilenameScheme::Vellum.parse_repeatable("1_init.sql").unwrap(), None);
+        assert!(FilenameScheme::Vellum.parse_repeatable("R_.sql").is_err());
+    }
+}
@@ -2,10 +2,32 @@ pub mod checksum;
 pub mod discovery;
 pub mod drift;
 pub mod error;
+pub mod filename;
 pub mod model;
+pub mod placeholder;
+pub mod plan;
+pub mod scaffold;
+pub mod source;
+pub mod version;
 
-pub use checksum::sha256_hex;
-pub use discovery::discover_migrations;
+pub use checksum::{checksum_matches, normalized_parser_version, sha256_hex, ChecksumAlgorithm};
+pub use discovery::{
+    discover_all_with, discover_from, discover_migrations, discover_migrations_with,
+    parse_filename, DiscoveredMigrations, DiscoveryOptions,
+};
 pub use drift::{detect_drift, DbMigration};
-pub use error::{MigrationDriftError, MigrationDiscoveryError};
-pub use model::Migration;
+pub use error::{
+    MigrationDiscoveryError, MigrationDriftError, MigrationScaffoldError, VersionParseError,
+};
+pub use filename::{FilenameScheme, ParsedFilename, DOWN_SUFFIX};
+pub use model::{Migration, RepeatableMigration};
+pub use placeholder::{Placeholders, SourceMap};
+pub use plan::{
+    AppliedHistory, DatabaseIdentity, MigrationPlan, PlanStep, SavedPlan, StaleReason, StepAction,
+    StepKind, StepReason, SAVED_PLAN_FORMAT,
+};
+pub use source::{open_source, FilesystemSource, InMemorySource, MigrationSource, SourceFile};
+#[cfg(feature = "archive")]
+pub use source::{ArchiveFormat, ArchiveSource};
+pub use scaffold::{create_migration, CreatedMigration, NewMigrationOptions, VersionScheme};
+pub use version::Version;
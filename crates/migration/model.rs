@@ -1,20 +1,80 @@
+use crate::placeholder::SourceMap;
+use crate::version::Version;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Migration {
-    pub version: i64,
+    pub version: Version,
     pub name: String,
     pub filename: String,
     pub checksum: String,
     pub sql: String,
+    pub raw_sql: String,
+    pub source_map: SourceMap,
 }
 
 impl Migration {
-    pub fn new(version: i64, name: String, filename: String, checksum: String, sql: String) -> Self {
+    pub fn new(version: Version, name: String, filename: String, checksum: String, sql: String) -> Self {
         Self {
             version,
             name,
             filename,
             checksum,
+            raw_sql: sql.clone(),
             sql,
+            source_map: SourceMap::default(),
         }
     }
+
+    pub fn with_rendered_sql(mut self, sql: String, source_map: SourceMap) -> Self {
+        self.sql = sql;
+        self.source_map = source_map;
+        self
+    }
+
+    // Maps an offset in `sql` to the same position in `raw_sql`.
+    pub fn raw_offset(&self, offset: usize) -> usize {
+        self.source_map.raw_offset(offset)
+    }
+
+    pub fn matches_checksum(&self, stored: &str) -> bool {
+        stored == self.checksum || crate::checksum::checksum_matches(stored, &self.raw_sql)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepeatableMigration {
+    pub name: String,
+    pub filename: String,
+    pub checksum: String,
+    pub sql: String,
+    pub raw_sql: String,
+    pub source_map: SourceMap,
+}
+
+impl RepeatableMigration {
+    pub fn new(name: String, filename: String, checksum: String, sql: String) -> Self {
+        Self {
+            name,
+            filename,
+            checksum,
+            raw_sql: sql.clone(),
+            sql,
+            source_map: SourceMap::default(),
+        }
+    }
+
+    pub fn with_rendered_sql(mut self, sql: String, source_map: SourceMap) -> Self {
+        self.sql = sql;
+        self.source_map = source_map;
+        self
+    }
+
+    // Maps an offset in `sql` to the same position in `raw_sql`.
+    pub fn raw_offset(&self, offset: usize) -> usize {
+        self.source_map.raw_offset(offset)
+    }
+
+    pub fn matches_checksum(&self, stored: &str) -> bool {
+        stored == self.checksum || crate::checksum::checksum_matches(stored, &self.raw_sql)
+    }
 }
@@ -0,0 +1,491 @@
+use crate::error::MigrationDiscoveryError;
+use std::collections::BTreeMap;
+
+const OPEN: &str = "${";
+// `$${` is written as a literal `${`, for SQL that needs the sequence itself,
+// e.g. inside a string literal or a dollar-quoted function body.
+const ESCAPE: &str = "$${";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Placeholders {
+    values: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Occurrence<'a> {
+    start: usize,
+    end: usize,
+    // `None` marks an escaped `$${`, which renders as `${`.
+    name: Option<&'a str>,
+    // Inside a string literal, quoted identifier or dollar-quoted body.
+    quoted: bool,
+}
+
+// Records where rendering replaced text, so positions found in rendered SQL
+// can be reported against the file as written.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SourceMap {
+    substitutions: Vec<Substitution>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Substitution {
+    raw: (usize, usize),
+    rendered: (usize, usize),
+}
+
+impl SourceMap {
+    // Offsets inside a substituted value map to the start of its placeholder.
+    pub fn raw_offset(&self, rendered_offset: usize) -> usize {
+        let mut raw_offset = rendered_offset;
+        for s in &self.substitutions {
+            if rendered_offset < s.rendered.0 {
+                break;
+            }
+            if rendered_offset < s.rendered.1 {
+                return s.raw.0;
+            }
+            raw_offset = s.raw.1 + (rendered_offset - s.rendered.1);
+        }
+        raw_offset
+    }
+}
+
+impl Placeholders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn render(&self, sql: &str, filename: &str) -> Result<String, MigrationDiscoveryError> {
+        self.render_mapped(sql, filename).map(|(rendered, _)| rendered)
+    }
+
+    pub fn render_mapped(
+        &self,
+        sql: &str,
+        filename: &str,
+    ) -> Result<(String, SourceMap), MigrationDiscoveryError> {
+        let mut out = String::with_capacity(sql.len());
+        let mut map = SourceMap::default();
+        let mut last = 0usize;
+
+        for occurrence in scan(sql, filename)? {
+            let value = match occurrence.name {
+                None => OPEN,
+                Some(name) if occurrence.quoted => match self.get(name) {
+                    Some(value) => value,
+                    None => continue,
+                },
+                Some(name) => self.get(name).ok_or_else(|| {
+                    let (line, column) = line_column(sql, occurrence.start);
+                    MigrationDiscoveryError::UnknownPlaceholder {
+                        filename: filename.to_string(),
+                        line,
+                        column,
+                        name: name.to_string(),
+                    }
+                })?,
+            };
+
+            out.push_str(&sql[last..occurrence.start]);
+            map.substitutions.push(Substitution {
+                raw: (occurrence.start, occurrence.end),
+                rendered: (out.len(), out.len() + value.len()),
+            });
+            out.push_str(value);
+            last = occurrence.end;
+        }
+
+        out.push_str(&sql[last..]);
+        Ok((out, map))
+    }
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+pub(crate) fn mask(sql: &str) -> String {
+    let Ok(occurrences) = scan(sql, "") else {
+        return sql.to_string();
+    };
+
+    let mut out = String::with_capacity(sql.len());
+    let mut last = 0usize;
+    // Quoted text parses whatever it holds, so only placeholders in code need
+    // a stand-in identifier.
+    for occurrence in occurrences.into_iter().filter(|o| !o.quoted || o.name.is_none()) {
+        out.push_str(&sql[last..occurrence.start]);
+        last = occurrence.end;
+        let Some(name) = occurrence.name else {
+            out.push_str(OPEN);
+            continue;
+        };
+        out.push_str("vellum_placeholder_");
+        out.extend(
+            name.chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }),
+        );
+    }
+
+    out.push_str(&sql[last..]);
+    out
+}
+
+// Comments are never scanned, and text inside string literals, quoted
+// identifiers and dollar-quoted bodies only substitutes placeholders that are
+// configured: anything else there is left as written, so existing SQL that
+// happens to contain `${` keeps working.
+fn scan<'a>(sql: &'a str, filename: &str) -> Result<Vec<Occurrence<'a>>, MigrationDiscoveryError> {
+    let regions = literal_regions(sql);
+    let mut region = regions.iter().peekable();
+    let mut occurrences = Vec::new();
+    let mut cursor = 0usize;
+
+    while let Some(offset) = sql[cursor..].find(OPEN) {
+        let start = cursor + offset;
+        while region.next_if(|r| r.end <= start).is_some() {}
+        let context = region
+            .peek()
+            .filter(|r| r.start <= start)
+            .map(|r| r.kind);
+
+        if context == Some(RegionKind::Comment) {
+            cursor = region.peek().map_or(sql.len(), |r| r.end);
+            continue;
+        }
+        let quoted = context == Some(RegionKind::Quoted);
+
+        if start > 0 && sql[start - 1..].starts_with(ESCAPE) {
+            let end = start + OPEN.len();
+            occurrences.push(Occurrence {
+                start: start - 1,
+                end,
+                name: None,
+                quoted,
+            });
+            cursor = end;
+            continue;
+        }
+
+        let name_start = start + OPEN.len();
+        let name = sql[name_start..]
+            .find('}')
+            .map(|len| &sql[name_start..name_start + len]);
+
+        match name {
+            Some(name) if is_valid_name(name) => {
+                let end = name_start + name.len() + 1;
+                occurrences.push(Occurrence {
+                    start,
+                    end,
+                    name: Some(name),
+                    quoted,
+                });
+                cursor = end;
+            }
+            _ if quoted => cursor = name_start,
+            None => {
+                return Err(invalid(sql, filename, start, "placeholder is not closed with '}'"));
+            }
+            Some(_) => {
+                return Err(invalid(
+                    sql,
+                    filename,
+                    start,
+                    "placeholder names may only contain ASCII letters, digits, '_', '.' and '-'",
+                ));
+            }
+        }
+    }
+
+    Ok(occurrences)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RegionKind {
+    Comment,
+    Quoted,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Region {
+    start: usize,
+    end: usize,
+    kind: RegionKind,
+}
+
+// A minimal PostgreSQL lexer: it only tracks where comments, string literals,
+// quoted identifiers and dollar-quoted bodies begin and end. Unterminated
+// regions run to the end of the file, which is where the server would report
+// them too.
+fn literal_regions(sql: &str) -> Vec<Region> {
+    let bytes = sql.as_bytes();
+    let mut regions = Vec::new();
+    let mut i = 0usize;
+
+    while i < bytes.len() {
+        let start = i;
+        let (end, kind) = match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                let end = sql[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                (end, RegionKind::Comment)
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => (block_comment_end(bytes, i), RegionKind::Comment),
+            b'\'' => {
+                let backslashes = i > 0
+                    && matches!(bytes[i - 1], b'E' | b'e')
+                    && (i < 2 || !is_identifier_byte(bytes[i - 2]));
+                (quoted_end(bytes, i, b'\'', backslashes), RegionKind::Quoted)
+            }
+            b'"' => (quoted_end(bytes, i, b'"', false), RegionKind::Quoted),
+            b'$' if sql[i..].starts_with(ESCAPE) => {
+                i += ESCAPE.len();
+                continue;
+            }
+            b'$' if i == 0 || !is_identifier_byte(bytes[i - 1]) => match dollar_tag(&sql[i..]) {
+                Some(tag) => (dollar_quoted_end(sql, i, tag), RegionKind::Quoted),
+                None => {
+                    i += 1;
+                    continue;
+                }
+            },
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        regions.push(Region { start, end, kind });
+        i = end;
+    }
+
+    regions
+}
+
+fn is_identifier_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || !b.is_ascii()
+}
+
+fn block_comment_end(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut i = start;
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (b'/', b'*') => {
+                depth += 1;
+                i += 2;
+            }
+            (b'*', b'/') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn quoted_end(bytes: &[u8], start: usize, quote: u8, backslashes: bool) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        if backslashes && bytes[i] == b'\\' {
+            i += 2;
+        } else if bytes[i] == quote {
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+            } else {
+                return i + 1;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+// Returns the opening `$tag$` (possibly `$$`) at the start of `sql`, if any.
+fn dollar_tag(sql: &str) -> Option<&str> {
+    let bytes = sql.as_bytes();
+    let first = *bytes.get(1)?;
+    if first == b'$' {
+        return Some(&sql[..2]);
+    }
+    if !(first.is_ascii_alphabetic() || first == b'_' || !first.is_ascii()) {
+        return None;
+    }
+    let len = bytes[1..]
+        .iter()
+        .position(|&b| !(b.is_ascii_alphanumeric() || b == b'_' || !b.is_ascii()))?;
+    (bytes[1 + len] == b'$').then(|| &sql[..len + 2])
+}
+
+fn dollar_quoted_end(sql: &str, start: usize, tag: &str) -> usize {
+    let mut i = start + tag.len();
+    while let Some(offset) = sql[i..].find('$') {
+        let at = i + offset;
+        if sql[at..].starts_with(ESCAPE) {
+            // `$${` is consumed before the SQL is lexed, so it never closes `$$`.
+            i = at + ESCAPE.len();
+        } else if sql[at..].starts_with(tag) {
+            return at + tag.len();
+        } else {
+            i = at + 1;
+        }
+    }
+    sql.len()
+}
+
+fn invalid(sql: &str, filename: &str, offset: usize, reason: &str) -> MigrationDiscoveryError {
+    let (line, column) = line_column(sql, offset);
+    MigrationDiscoveryError::InvalidPlaceholder {
+        filename: filename.to_string(),
+        line,
+        column,
+        reason: reason.to_string(),
+    }
+}
+
+fn line_column(sql: &str, offset: usize) -> (usize, usize) {
+    let before = &sql[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mask, Placeholders};
+    use crate::error::MigrationDiscoveryError;
+
+    fn placeholders() -> Placeholders {
+        let mut p = Placeholders::new();
+        p.insert("schema", "app");
+        p.insert("role.read", "reader");
+        p
+    }
+
+    #[test]
+    fn renders_known_placeholders() {
+        let sql = "create table ${schema}.users (id int);\ngrant select on ${schema}.users to ${role.read};";
+        let rendered = placeholders().render(sql, "1_init.sql").unwrap();
+        assert_eq!(
+            rendered,
+            "create table app.users (id int);\ngrant select on app.users to reader;"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholder_reports_line_and_column() {
+        let sql = "select 1;\n  select ${missing};";
+        let err = placeholders().render(sql, "2_x.sql").unwrap_err();
+        assert_eq!(
+            err,
+            MigrationDiscoveryError::UnknownPlaceholder {
+                filename: "2_x.sql".to_string(),
+                line: 2,
+                column: 10,
+                name: "missing".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn malformed_placeholder_is_rejected() {
+        for sql in ["select ${schema;", "select ${};", "select ${a b};"] {
+            let err = placeholders().render(sql, "x.sql").unwrap_err();
+            assert!(
+                matches!(err, MigrationDiscoveryError::InvalidPlaceholder { line: 1, column: 8, .. }),
+                "{sql}"
+            );
+        }
+    }
+
+    #[test]
+    fn mask_replaces_placeholders_with_stable_identifiers() {
+        assert_eq!(
+            mask("create table ${schema}.t to ${role.read};"),
+            "create table vellum_placeholder_schema.t to vellum_placeholder_role_read;"
+        );
+    }
+
+    #[test]
+    fn escaped_placeholder_renders_literally() {
+        let sql = "select '$${schema}', ${schema};\ndo $$ begin raise notice '$${}'; end $$;";
+        let rendered = placeholders().render(sql, "3_x.sql").unwrap();
+        assert_eq!(
+            rendered,
+            "select '${schema}', app;\ndo $$ begin raise notice '${}'; end $$;"
+        );
+    }
+
+    #[test]
+    fn mask_keeps_escaped_placeholders_literal() {
+        assert_eq!(
+            mask("select '$${role.read}' from ${schema}.t;"),
+            "select '${role.read}' from vellum_placeholder_schema.t;"
+        );
+    }
+
+    #[test]
+    fn comments_are_not_substituted() {
+        let sql = "-- ${missing}\n/* ${a b /* ${} */ */ select ${schema};";
+        let rendered = placeholders().render(sql, "4_x.sql").unwrap();
+        assert_eq!(rendered, "-- ${missing}\n/* ${a b /* ${} */ */ select app;");
+    }
+
+    #[test]
+    fn quoted_text_only_substitutes_configured_placeholders() {
+        let sql = "select '${schema}', 'it''s ${missing}', \"${x\", E'\\' ${', $f$ ${ $f$, $$ ${schema} $$, ${schema}";
+        let rendered = placeholders().render(sql, "5_x.sql").unwrap();
+        assert_eq!(
+            rendered,
+            "select 'app', 'it''s ${missing}', \"${x\", E'\\' ${', $f$ ${ $f$, $$ app $$, app"
+        );
+        assert_eq!(
+            mask(sql),
+            "select '${schema}', 'it''s ${missing}', \"${x\", E'\\' ${', $f$ ${ $f$, $$ ${schema} $$, vellum_placeholder_schema"
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_runs_to_end_of_file() {
+        let sql = "select 1;\ncreate function f() as $body$ select '${missing'";
+        assert_eq!(placeholders().render(sql, "6_x.sql").unwrap(), sql);
+    }
+
+    #[test]
+    fn source_map_points_rendered_offsets_at_the_file() {
+        let sql = "create schema ${schema};\ngrant usage on schema ${schema} to ${role.read};\nselect $${x};";
+        let (rendered, map) = placeholders().render_mapped(sql, "7_x.sql").unwrap();
+
+        for needle in ["create", ";\ngrant", " to ", ";\nselect", "x};"] {
+            assert_eq!(map.raw_offset(rendered.find(needle).unwrap()), sql.find(needle).unwrap(), "{needle}");
+        }
+        assert_eq!(map.raw_offset(rendered.find("reader").unwrap() + 2), sql.find("${role.read}").unwrap());
+        assert_eq!(map.raw_offset(rendered.len()), sql.len());
+    }
+}
@@ -0,0 +1,529 @@
+use crate::checksum::sha256_hex;
+use crate::model::{Migration, RepeatableMigration};
+use crate::version::Version;
+use core::fmt;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AppliedHistory {
+    pub versioned: HashMap<Version, String>,
+    pub repeatable: HashMap<String, String>,
+}
+
+impl AppliedHistory {
+    pub fn fingerprint(&self) -> String {
+        let mut versioned: Vec<_> = self.versioned.iter().collect();
+        versioned.sort();
+        let mut repeatable: Vec<_> = self.repeatable.iter().collect();
+        repeatable.sort();
+
+        let mut canonical = String::new();
+        for (version, checksum) in versioned {
+            canonical.push_str(&format!("versioned\t{version}\t{checksum}\n"));
+        }
+        for (name, checksum) in repeatable {
+            canonical.push_str(&format!("repeatable\t{name}\t{checksum}\n"));
+        }
+
+        sha256_hex(canonical.as_bytes())
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    Versioned,
+    Repeatable,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepAction {
+    Apply,
+    Skip,
+    Blocked,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "code", rename_all = "snake_case"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepReason {
+    Pending,
+    AlreadyApplied,
+    ChecksumMismatch { expected: String, actual: String },
+    BlockedBy { version: Version },
+    RepeatableNew,
+    RepeatableChanged,
+    RepeatableUnchanged,
+}
+
+impl fmt::Display for StepReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepReason::Pending => write!(f, "not yet applied"),
+            StepReason::AlreadyApplied => write!(f, "already applied"),
+            StepReason::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: file was modified after being applied (db={expected}, fs={actual})"
+            ),
+            StepReason::BlockedBy { version } => {
+                write!(f, "blocked by modified migration {version}")
+            }
+            StepReason::RepeatableNew => write!(f, "repeatable migration not yet applied"),
+            StepReason::RepeatableChanged => write!(f, "repeatable migration checksum changed"),
+            StepReason::RepeatableUnchanged => write!(f, "repeatable migration unchanged"),
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanStep {
+    pub kind: StepKind,
+    pub version: Option<Version>,
+    pub name: String,
+    pub filename: String,
+    pub checksum: String,
+    pub action: StepAction,
+    pub reason: StepReason,
+}
+
+impl PlanStep {
+    pub fn is_apply(&self) -> bool {
+        self.action == StepAction::Apply
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MigrationPlan {
+    pub steps: Vec<PlanStep>,
+}
+
+impl MigrationPlan {
+    pub fn build(
+        history: &AppliedHistory,
+        migrations: &[Migration],
+        repeatables: &[RepeatableMigration],
+    ) -> Self {
+        let mut steps = Vec::with_capacity(migrations.len() + repeatables.len());
+        let mut blocked_by: Option<Version> = None;
+
+        for m in migrations {
+            let (action, reason) = match history.versioned.get(&m.version) {
+                Some(stored) if m.matches_checksum(stored) => {
+                    (StepAction::Skip, StepReason::AlreadyApplied)
+                }
+                Some(stored) => {
+                    blocked_by.get_or_insert_with(|| m.version.clone());
+                    (
+                        StepAction::Blocked,
+                        StepReason::ChecksumMismatch {
+                            expected: stored.clone(),
+                            actual: m.checksum.clone(),
+                        },
+                    )
+                }
+                None => match &blocked_by {
+                    Some(version) => (
+                        StepAction::Blocked,
+                        StepReason::BlockedBy {
+                            version: version.clone(),
+                        },
+                    ),
+                    None => (StepAction::Apply, StepReason::Pending),
+                },
+            };
+
+            steps.push(PlanStep {
+                kind: StepKind::Versioned,
+                version: Some(m.version.clone()),
+                name: m.name.clone(),
+                filename: m.filename.clone(),
+                checksum: m.checksum.clone(),
+                action,
+                reason,
+            });
+        }
+
+        for m in repeatables {
+            let (action, reason) = match (history.repeatable.get(&m.name), &blocked_by) {
+                (Some(stored), _) if m.matches_checksum(stored) => {
+                    (StepAction::Skip, StepReason::RepeatableUnchanged)
+                }
+                (_, Some(version)) => (
+                    StepAction::Blocked,
+                    StepReason::BlockedBy {
+                        version: version.clone(),
+                    },
+                ),
+                (Some(_), None) => (StepAction::Apply, StepReason::RepeatableChanged),
+                (None, None) => (StepAction::Apply, StepReason::RepeatableNew),
+            };
+
+            steps.push(PlanStep {
+                kind: StepKind::Repeatable,
+                version: None,
+                name: m.name.clone(),
+                filename: m.filename.clone(),
+                checksum: m.checksum.clone(),
+                action,
+                reason,
+            });
+        }
+
+        Self { steps }
+    }
+
+    pub fn to_apply(&self) -> impl Iterator<Item = &PlanStep> {
+        self.steps.iter().filter(|s| s.is_apply())
+    }
+
+    pub fn first_blocked(&self) -> Option<&PlanStep> {
+        self.steps
+            .iter()
+            .find(|s| matches!(s.reason, StepReason::ChecksumMismatch { .. }))
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        self.steps.iter().any(|s| s.action == StepAction::Blocked)
+    }
+
+    pub fn count(&self, kind: StepKind, action: StepAction) -> usize {
+        self.steps
+            .iter()
+            .filter(|s| s.kind == kind && s.action == action)
+            .count()
+    }
+}
+
+pub const SAVED_PLAN_FORMAT: u32 = 1;
+
+// Identifies the database a plan was made for: its name within the cluster and,
+// when the server exposes it, the cluster's `system_identifier`, which tells
+// apart two clusters that both have an `app` database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseIdentity {
+    pub name: String,
+    pub system_identifier: Option<String>,
+}
+
+impl fmt::Display for DatabaseIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.system_identifier {
+            Some(id) => write!(f, "{} (system identifier {id})", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl DatabaseIdentity {
+    // Plans written before the identifier was recorded only compare names.
+    fn matches(&self, other: &DatabaseIdentity) -> bool {
+        self.name == other.name
+            && match (&self.system_identifier, &other.system_identifier) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedPlan {
+    pub format: u32,
+    pub vellum_version: String,
+    pub database: String,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub system_identifier: Option<String>,
+    pub history_fingerprint: String,
+    pub steps: Vec<PlanStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StaleReason {
+    UnsupportedFormat { format: u32 },
+    DatabaseChanged { expected: DatabaseIdentity, actual: DatabaseIdentity },
+    HistoryChanged { expected: String, actual: String },
+    Blocked { version: Version },
+    StepRemoved { filename: String },
+    StepAdded { filename: String },
+    StepChanged { filename: String, expected: String, actual: String },
+}
+
+impl fmt::Display for StaleReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StaleReason::UnsupportedFormat { format } => write!(
+                f,
+                "unsupported plan format {format} (expected {SAVED_PLAN_FORMAT})"
+            ),
+            StaleReason::DatabaseChanged { expected, actual } => write!(
+                f,
+                "the plan was created for database {expected}, not {actual}"
+            ),
+            StaleReason::HistoryChanged { expected, actual } => write!(
+                f,
+                "migration history changed since the plan was created (plan={expected}, db={actual})"
+            ),
+            StaleReason::Blocked { version } => {
+                write!(f, "applied migration {version} was modified since the plan was created")
+            }
+            StaleReason::StepRemoved { filename } => {
+                write!(f, "{filename} is in the plan but is no longer pending")
+            }
+            StaleReason::StepAdded { filename } => {
+                write!(f, "{filename} is pending but is not in the plan")
+            }
+            StaleReason::StepChanged {
+                filename,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{filename} changed since the plan was created (plan={expected}, fs={actual})"
+            ),
+        }
+    }
+}
+
+impl SavedPlan {
+    pub fn new(
+        vellum_version: impl Into<String>,
+        database: &DatabaseIdentity,
+        history: &AppliedHistory,
+        plan: &MigrationPlan,
+    ) -> Self {
+        Self {
+            format: SAVED_PLAN_FORMAT,
+            vellum_version: vellum_version.into(),
+            database: database.name.clone(),
+            system_identifier: database.system_identifier.clone(),
+            history_fingerprint: history.fingerprint(),
+            steps: plan.to_apply().cloned().collect(),
+        }
+    }
+
+    pub fn database_identity(&self) -> DatabaseIdentity {
+        DatabaseIdentity {
+            name: self.database.clone(),
+            system_identifier: self.system_identifier.clone(),
+        }
+    }
+
+    pub fn check(
+        &self,
+        database: &DatabaseIdentity,
+        history: &AppliedHistory,
+        plan: &MigrationPlan,
+    ) -> Result<(), StaleReason> {
+        if self.format != SAVED_PLAN_FORMAT {
+            return Err(StaleReason::UnsupportedFormat {
+                format: self.format,
+            });
+        }
+
+        let expected = self.database_identity();
+        if !expected.matches(database) {
+            return Err(StaleReason::DatabaseChanged {
+                expected,
+                actual: database.clone(),
+            });
+        }
+
+        let actual = history.fingerprint();
+        if actual != self.history_fingerprint {
+            return Err(StaleReason::HistoryChanged {
+                expected: self.history_fingerprint.clone(),
+                actual,
+            });
+        }
+
+        if let Some(PlanStep {
+            version: Some(version),
+            ..
+        }) = plan.first_blocked()
+        {
+            return Err(StaleReason::Blocked {
+                version: version.clone(),
+            });
+        }
+
+        let mut current = plan.to_apply();
+        for saved in &self.steps {
+            let Some(step) = current.next() else {
+                return Err(StaleReason::StepRemoved {
+                    filename: saved.filename.clone(),
+                });
+            };
+
+            if step.kind != saved.kind || step.version != saved.version || step.name != saved.name
+            {
+                return Err(StaleReason::StepRemoved {
+                    filename: saved.filename.clone(),
+                });
+            }
+
+            if step.checksum != saved.checksum || step.filename != saved.filename {
+                return Err(StaleReason::StepChanged {
+                    filename: step.filename.clone(),
+                    expected: saved.checksum.clone(),
+                    actual: step.checksum.clone(),
+                });
+            }
+        }
+
+        match current.next() {
+            Some(step) => Err(StaleReason::StepAdded {
+                filename: step.filename.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AppliedHistory, DatabaseIdentity, MigrationPlan, SavedPlan, StaleReason, StepAction,
+        StepKind, StepReason,
+    };
+    use crate::model::{Migration, RepeatableMigration};
+    use crate::version::Version;
+
+    fn migration(version: u64, checksum: &str) -> Migration {
+        Migration::new(
+            Version::from(version),
+            format!("m{version}"),
+            format!("{version}_m{version}.sql"),
+            checksum.to_string(),
+            "select 1;".to_string(),
+        )
+    }
+
+    fn database(name: &str, system_identifier: Option<&str>) -> DatabaseIdentity {
+        DatabaseIdentity {
+            name: name.to_string(),
+            system_identifier: system_identifier.map(str::to_string),
+        }
+    }
+
+    fn repeatable(name: &str, checksum: &str) -> RepeatableMigration {
+        RepeatableMigration::new(
+            name.to_string(),
+            format!("R_{name}.sql"),
+            checksum.to_string(),
+            "select 1;".to_string(),
+        )
+    }
+
+    #[test]
+    fn plans_pending_and_skipped_steps() {
+        let mut history = AppliedHistory::default();
+        history.versioned.insert(Version::from(1), "a".to_string());
+        history.repeatable.insert("views".to_string(), "v1".to_string());
+
+        let plan = MigrationPlan::build(
+            &history,
+            &[migration(1, "a"), migration(2, "b")],
+            &[repeatable("views", "v2"), repeatable("grants", "g")],
+        );
+
+        let actions: Vec<_> = plan.steps.iter().map(|s| (s.action, s.reason.clone())).collect();
+        assert_eq!(
+            actions,
+            vec![
+                (StepAction::Skip, StepReason::AlreadyApplied),
+                (StepAction::Apply, StepReason::Pending),
+                (StepAction::Apply, StepReason::RepeatableChanged),
+                (StepAction::Apply, StepReason::RepeatableNew),
+            ]
+        );
+        assert!(!plan.is_blocked());
+        assert_eq!(plan.count(StepKind::Repeatable, StepAction::Apply), 2);
+    }
+
+    #[test]
+    fn checksum_mismatch_blocks_later_steps() {
+        let mut history = AppliedHistory::default();
+        history.versioned.insert(Version::from(1), "old".to_string());
+
+        let plan = MigrationPlan::build(
+            &history,
+            &[migration(1, "new"), migration(2, "b")],
+            &[repeatable("views", "v")],
+        );
+
+        assert!(plan.is_blocked());
+        assert_eq!(plan.to_apply().count(), 0);
+        assert_eq!(plan.first_blocked().unwrap().version, Some(Version::from(1)));
+        assert_eq!(
+            plan.steps[2].reason,
+            StepReason::BlockedBy {
+                version: Version::from(1)
+            }
+        );
+    }
+
+    #[test]
+    fn saved_plan_detects_history_and_file_changes() {
+        let mut history = AppliedHistory::default();
+        history.versioned.insert(Version::from(1), "a".to_string());
+        let migrations = [migration(1, "a"), migration(2, "b")];
+        let app = database("app", Some("7000"));
+
+        let plan = MigrationPlan::build(&history, &migrations, &[]);
+        let saved = SavedPlan::new("0.1.0", &app, &history, &plan);
+        assert_eq!(saved.steps.len(), 1);
+        assert_eq!(saved.check(&app, &history, &plan), Ok(()));
+
+        let edited = [migration(1, "a"), migration(2, "b2")];
+        let replanned = MigrationPlan::build(&history, &edited, &[]);
+        assert!(matches!(
+            saved.check(&app, &history, &replanned),
+            Err(StaleReason::StepChanged { .. })
+        ));
+
+        let added = [migration(1, "a"), migration(2, "b"), migration(3, "c")];
+        let replanned = MigrationPlan::build(&history, &added, &[]);
+        assert!(matches!(
+            saved.check(&app, &history, &replanned),
+            Err(StaleReason::StepAdded { .. })
+        ));
+
+        let mut moved = history.clone();
+        moved.versioned.insert(Version::from(2), "b".to_string());
+        let replanned = MigrationPlan::build(&moved, &migrations, &[]);
+        assert!(matches!(
+            saved.check(&app, &moved, &replanned),
+            Err(StaleReason::HistoryChanged { .. })
+        ));
+    }
+
+    #[test]
+    fn saved_plan_rejects_another_database() {
+        let history = AppliedHistory::default();
+        let plan = MigrationPlan::build(&history, &[migration(1, "a")], &[]);
+        let saved = SavedPlan::new("0.1.0", &database("app", Some("7000")), &history, &plan);
+
+        for other in [database("app_test", Some("7000")), database("app", Some("8000"))] {
+            assert_eq!(
+                saved.check(&other, &history, &plan),
+                Err(StaleReason::DatabaseChanged {
+                    expected: database("app", Some("7000")),
+                    actual: other.clone(),
+                })
+            );
+        }
+
+        // Plans written before the identifier was recorded still apply by name.
+        let legacy = SavedPlan {
+            system_identifier: None,
+            ..saved.clone()
+        };
+        assert_eq!(legacy.check(&database("app", Some("8000")), &history, &plan), Ok(()));
+        assert!(legacy.check(&database("other", None), &history, &plan).is_err());
+    }
+}
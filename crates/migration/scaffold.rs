@@ -0,0 +1,363 @@
+use crate::discovery::{discover_migrations_with, DiscoveryOptions};
+use crate::error::{MigrationDiscoveryError, MigrationScaffoldError};
+use crate::filename::FilenameScheme;
+use crate::model::Migration;
+use crate::version::Version;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Timestamps are the default because the next integer is only unique within
+// one branch: two branches scaffolding at once both pick `max + 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionScheme {
+    Integer,
+    #[default]
+    Timestamp,
+}
+
+impl VersionScheme {
+    pub fn id(&self) -> &'static str {
+        match self {
+            VersionScheme::Integer => "integer",
+            VersionScheme::Timestamp => "timestamp",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "integer" => Some(VersionScheme::Integer),
+            "timestamp" => Some(VersionScheme::Timestamp),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NewMigrationOptions {
+    pub scheme: VersionScheme,
+    pub filename_scheme: FilenameScheme,
+    pub down: bool,
+    pub header: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedMigration {
+    pub version: Version,
+    pub filename: String,
+    pub path: PathBuf,
+    pub down_path: Option<PathBuf>,
+}
+
+pub fn create_migration(
+    dir: impl AsRef<Path>,
+    name: &str,
+    options: NewMigrationOptions,
+    now: SystemTime,
+) -> Result<CreatedMigration, MigrationScaffoldError> {
+    let dir = dir.as_ref();
+    validate_name(name)?;
+
+    let filename_scheme = options.filename_scheme;
+    let existing = existing_migrations(dir, filename_scheme)?;
+    let version = next_version(&existing, options.scheme, now);
+    let version_text = format_version(&existing, filename_scheme, &version);
+    let filename = filename_scheme.format(&version_text, name);
+
+    let parsed = filename_scheme
+        .parse(&filename)
+        .map_err(|e| MigrationScaffoldError::InvalidName {
+            name: name.to_string(),
+            reason: e.to_string(),
+        })?;
+    if !parsed.is_some_and(|p| p.version == version && p.name == name) {
+        return Err(MigrationScaffoldError::InvalidName {
+            name: name.to_string(),
+            reason: format!("'{filename}' does not round-trip through the filename parser"),
+        });
+    }
+
+    fs::create_dir_all(dir).map_err(|e| MigrationScaffoldError::Io {
+        path: dir.display().to_string(),
+        message: e.to_string(),
+    })?;
+
+    let label = filename_scheme.format(&version_text, name);
+    let label = label.trim_end_matches(".up.sql").trim_end_matches(".sql");
+    let path = dir.join(&filename);
+    let contents = if options.header {
+        up_header(label, now)
+    } else {
+        String::new()
+    };
+    write_new_file(&path, &contents)?;
+
+    let down_path = if options.down {
+        let down_path = dir.join(filename_scheme.down_filename(&version_text, name));
+        write_new_file(&down_path, &down_header(label))?;
+        Some(down_path)
+    } else {
+        None
+    };
+
+    Ok(CreatedMigration {
+        version,
+        filename,
+        path,
+        down_path,
+    })
+}
+
+pub fn next_version(existing: &[Migration], scheme: VersionScheme, now: SystemTime) -> Version {
+    let next = existing
+        .iter()
+        .map(|m| &m.version)
+        .max()
+        .map(Version::next)
+        .unwrap_or_else(|| Version::from(1));
+
+    match scheme {
+        VersionScheme::Integer => next,
+        VersionScheme::Timestamp => Version::from(timestamp_version(now)).max(next),
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), MigrationScaffoldError> {
+    if name.is_empty() {
+        return Err(MigrationScaffoldError::InvalidName {
+            name: name.to_string(),
+            reason: "name must not be empty".to_string(),
+        });
+    }
+
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(MigrationScaffoldError::InvalidName {
+            name: name.to_string(),
+            reason: "name may only contain ASCII letters, digits, '_' and '-'".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn existing_migrations(
+    dir: &Path,
+    filename_scheme: FilenameScheme,
+) -> Result<Vec<Migration>, MigrationScaffoldError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let options = DiscoveryOptions {
+        filename_scheme,
+        ..DiscoveryOptions::default()
+    };
+    match discover_migrations_with(dir, &options) {
+        Ok(migrations) => Ok(migrations),
+        Err(MigrationDiscoveryError::EmptyMigrationsDir { .. }) => Ok(Vec::new()),
+        Err(error) => Err(MigrationScaffoldError::Discovery { error }),
+    }
+}
+
+fn format_version(existing: &[Migration], filename_scheme: FilenameScheme, version: &Version) -> String {
+    let width = existing
+        .iter()
+        .filter_map(|m| filename_scheme.parse(&m.filename).ok().flatten())
+        .map(|p| p.version_text)
+        .filter(|v| v.starts_with('0'))
+        .map(|v| v.len())
+        .max()
+        .unwrap_or(0);
+
+    let text = version.to_string();
+    format!("{}{text}", "0".repeat(width.saturating_sub(text.len())))
+}
+
+fn write_new_file(path: &Path, contents: &str) -> Result<(), MigrationScaffoldError> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::AlreadyExists {
+                MigrationScaffoldError::AlreadyExists {
+                    path: path.display().to_string(),
+                }
+            } else {
+                MigrationScaffoldError::Io {
+                    path: path.display().to_string(),
+                    message: e.to_string(),
+                }
+            }
+        })?;
+
+    file.write_all(contents.as_bytes())
+        .map_err(|e| MigrationScaffoldError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })
+}
+
+fn up_header(label: &str, now: SystemTime) -> String {
+    let (y, mo, d, h, mi, s) = utc_datetime(now);
+    format!(
+        "-- Migration: {label}\n\
+         -- Created: {y:04}-{mo:02}-{d:02} {h:02}:{mi:02}:{s:02} UTC\n\
+         --\n\
+         -- Statements run inside a single transaction. Do not add BEGIN/COMMIT.\n\n"
+    )
+}
+
+fn down_header(label: &str) -> String {
+    format!(
+        "-- Down migration for: {label}\n\
+         -- Vellum never executes this file; it documents how to revert the change by hand.\n\n"
+    )
+}
+
+fn timestamp_version(now: SystemTime) -> u64 {
+    let (y, mo, d, h, mi, s) = utc_datetime(now);
+    (y * 10_000_000_000 + mo * 100_000_000 + d * 1_000_000 + h * 10_000 + mi * 100 + s) as u64
+}
+
+fn utc_datetime(now: SystemTime) -> (i64, i64, i64, i64, i64, i64) {
+    let secs = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+
+    // Civil-from-days (Howard Hinnant), valid for the proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let mo = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if mo <= 2 { 1 } else { 0 };
+
+    (y, mo, d, rem / 3_600, rem % 3_600 / 60, rem % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_migration, utc_datetime, NewMigrationOptions, VersionScheme};
+    use crate::discovery::discover_migrations;
+    use crate::error::MigrationScaffoldError;
+    use crate::filename::FilenameScheme;
+    use crate::version::Version;
+    use std::fs;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn integer_scheme_uses_next_version_and_keeps_padding() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("0001_init.sql"), b"select 1;").unwrap();
+        fs::write(tmp.path().join("0002_users.sql"), b"select 2;").unwrap();
+
+        let options = NewMigrationOptions {
+            scheme: VersionScheme::Integer,
+            ..NewMigrationOptions::default()
+        };
+
+        let created =
+            create_migration(tmp.path(), "add_orders_table", options, SystemTime::now()).unwrap();
+
+        assert_eq!(created.version, Version::from(3));
+        assert_eq!(created.filename, "0003_add_orders_table.sql");
+        assert!(created.path.is_file());
+    }
+
+    #[test]
+    fn timestamp_scheme_is_the_default() {
+        assert_eq!(NewMigrationOptions::default().scheme, VersionScheme::Timestamp);
+    }
+
+    #[test]
+    fn timestamp_scheme_uses_utc_and_never_goes_backwards() {
+        let tmp = tempfile::tempdir().unwrap();
+        let options = NewMigrationOptions::default();
+
+        // 2024-01-01 12:00:00 UTC
+        let first = create_migration(tmp.path(), "a", options, at(1_704_110_400)).unwrap();
+        assert_eq!(first.version, Version::from(20240101120000));
+
+        let second = create_migration(tmp.path(), "b", options, at(1_704_110_400)).unwrap();
+        assert_eq!(second.version, Version::from(20240101120001));
+    }
+
+    #[test]
+    fn down_file_and_header_are_optional() {
+        let tmp = tempfile::tempdir().unwrap();
+        let options = NewMigrationOptions {
+            scheme: VersionScheme::Integer,
+            down: true,
+            header: true,
+            ..NewMigrationOptions::default()
+        };
+
+        let created = create_migration(tmp.path(), "init", options, at(0)).unwrap();
+        let up = fs::read_to_string(&created.path).unwrap();
+        assert!(up.starts_with("-- Migration: 1_init\n-- Created: 1970-01-01 00:00:00 UTC\n"));
+
+        let down_path = created.down_path.unwrap();
+        assert!(down_path.ends_with("1_init.down.sql"));
+        assert_eq!(discover_migrations(tmp.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn flyway_scheme_increments_last_segment() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("V1.2__init.sql"), b"select 1;").unwrap();
+        let options = NewMigrationOptions {
+            scheme: VersionScheme::Integer,
+            filename_scheme: FilenameScheme::Flyway,
+            down: true,
+            ..NewMigrationOptions::default()
+        };
+
+        let created = create_migration(tmp.path(), "users", options, at(0)).unwrap();
+        assert_eq!(created.filename, "V1.3__users.sql");
+        assert!(created.down_path.unwrap().ends_with("U1.3__users.sql"));
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        for name in ["", "add orders", "../escape", "x.sql"] {
+            let err = create_migration(tmp.path(), name, NewMigrationOptions::default(), at(0))
+                .unwrap_err();
+            assert!(matches!(err, MigrationScaffoldError::InvalidName { .. }), "{name}");
+        }
+    }
+
+    #[test]
+    fn utc_datetime_handles_leap_days() {
+        assert_eq!(utc_datetime(at(1_709_164_799)), (2024, 2, 28, 23, 59, 59));
+        assert_eq!(utc_datetime(at(1_709_164_800)), (2024, 2, 29, 0, 0, 0));
+        assert_eq!(utc_datetime(at(1_709_251_199)), (2024, 2, 29, 23, 59, 59));
+        assert_eq!(utc_datetime(at(1_709_251_200)), (2024, 3, 1, 0, 0, 0));
+        assert_eq!(utc_datetime(at(1_677_628_799 + 1)), (2023, 3, 1, 0, 0, 0));
+        assert_eq!(utc_datetime(at(951_825_600)), (2000, 2, 29, 12, 0, 0));
+        assert_eq!(utc_datetime(at(4_107_542_399 + 1)), (2100, 3, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn utc_datetime_rolls_over_the_year() {
+        assert_eq!(utc_datetime(at(1_704_067_199)), (2023, 12, 31, 23, 59, 59));
+        assert_eq!(utc_datetime(at(1_704_067_200)), (2024, 1, 1, 0, 0, 0));
+        assert_eq!(utc_datetime(at(0)), (1970, 1, 1, 0, 0, 0));
+    }
+}
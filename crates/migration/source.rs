@@ -0,0 +1,360 @@
+use crate::error::MigrationDiscoveryError;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    pub filename: String,
+    pub contents: Vec<u8>,
+}
+
+pub trait MigrationSource {
+    fn location(&self) -> String;
+
+    fn files(&self) -> Result<Vec<SourceFile>, MigrationDiscoveryError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilesystemSource {
+    dir: PathBuf,
+}
+
+impl FilesystemSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl MigrationSource for FilesystemSource {
+    fn location(&self) -> String {
+        self.dir.display().to_string()
+    }
+
+    fn files(&self) -> Result<Vec<SourceFile>, MigrationDiscoveryError> {
+        let dir_display = self.location();
+
+        let entries = fs::read_dir(&self.dir).map_err(|e| MigrationDiscoveryError::Io {
+            path: dir_display.clone(),
+            message: e.to_string(),
+        })?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| MigrationDiscoveryError::Io {
+                path: dir_display.clone(),
+                message: e.to_string(),
+            })?;
+
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+
+            let filename_os = path.file_name().ok_or_else(|| MigrationDiscoveryError::Io {
+                path: path.display().to_string(),
+                message: "missing filename".to_string(),
+            })?;
+
+            let contents = fs::read(&path).map_err(|e| MigrationDiscoveryError::Io {
+                path: path.display().to_string(),
+                message: e.to_string(),
+            })?;
+
+            files.push(SourceFile {
+                filename: filename_os.to_string_lossy().to_string(),
+                contents,
+            });
+        }
+
+        Ok(files)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InMemorySource {
+    location: String,
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl InMemorySource {
+    pub fn new(location: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_file(mut self, filename: impl Into<String>, contents: impl Into<Vec<u8>>) -> Self {
+        self.add_file(filename, contents);
+        self
+    }
+
+    pub fn add_file(&mut self, filename: impl Into<String>, contents: impl Into<Vec<u8>>) {
+        self.files.insert(filename.into(), contents.into());
+    }
+}
+
+impl Default for InMemorySource {
+    fn default() -> Self {
+        Self::new("<memory>")
+    }
+}
+
+impl MigrationSource for InMemorySource {
+    fn location(&self) -> String {
+        self.location.clone()
+    }
+
+    fn files(&self) -> Result<Vec<SourceFile>, MigrationDiscoveryError> {
+        Ok(self
+            .files
+            .iter()
+            .map(|(filename, contents)| SourceFile {
+                filename: filename.clone(),
+                contents: contents.clone(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(feature = "archive")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+#[cfg(feature = "archive")]
+impl ArchiveFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "archive")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveSource {
+    path: PathBuf,
+    format: ArchiveFormat,
+    prefix: Option<String>,
+}
+
+#[cfg(feature = "archive")]
+impl ArchiveSource {
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, MigrationDiscoveryError> {
+        let path = path.into();
+        let format = ArchiveFormat::from_path(&path).ok_or_else(|| MigrationDiscoveryError::Io {
+            path: path.display().to_string(),
+            message: "unsupported archive format: expected .tar, .tar.gz, .tgz or .zip"
+                .to_string(),
+        })?;
+
+        Ok(Self {
+            path,
+            format,
+            prefix: None,
+        })
+    }
+
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into().trim_matches('/').to_string());
+        self
+    }
+
+    fn entries(&self) -> Result<Vec<(String, Vec<u8>)>, MigrationDiscoveryError> {
+        let file = fs::File::open(&self.path).map_err(|e| self.io_error(e))?;
+
+        match self.format {
+            ArchiveFormat::Tar => self.tar_entries(file),
+            ArchiveFormat::TarGz => self.tar_entries(flate2::read::GzDecoder::new(file)),
+            ArchiveFormat::Zip => self.zip_entries(file),
+        }
+    }
+
+    fn tar_entries(
+        &self,
+        reader: impl std::io::Read,
+    ) -> Result<Vec<(String, Vec<u8>)>, MigrationDiscoveryError> {
+        let mut archive = tar::Archive::new(reader);
+        let mut out = Vec::new();
+
+        for entry in archive.entries().map_err(|e| self.io_error(e))? {
+            let mut entry = entry.map_err(|e| self.io_error(e))?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let path = entry
+                .path()
+                .map_err(|e| self.io_error(e))?
+                .to_string_lossy()
+                .to_string();
+            let mut contents = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut contents).map_err(|e| self.io_error(e))?;
+            out.push((path, contents));
+        }
+
+        Ok(out)
+    }
+
+    fn zip_entries(&self, file: fs::File) -> Result<Vec<(String, Vec<u8>)>, MigrationDiscoveryError> {
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| self.io_error(e))?;
+        let mut out = Vec::new();
+
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(|e| self.io_error(e))?;
+            if !entry.is_file() {
+                continue;
+            }
+
+            let Some(path) = entry.enclosed_name() else {
+                return Err(self.io_error(format!("unsafe entry path '{}'", entry.name())));
+            };
+            let path = path.to_string_lossy().to_string();
+            let mut contents = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut contents).map_err(|e| self.io_error(e))?;
+            out.push((path, contents));
+        }
+
+        Ok(out)
+    }
+
+    fn io_error(&self, e: impl std::fmt::Display) -> MigrationDiscoveryError {
+        MigrationDiscoveryError::Io {
+            path: self.path.display().to_string(),
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "archive")]
+impl MigrationSource for ArchiveSource {
+    fn location(&self) -> String {
+        match &self.prefix {
+            Some(prefix) if !prefix.is_empty() => format!("{}!/{prefix}", self.path.display()),
+            _ => self.path.display().to_string(),
+        }
+    }
+
+    fn files(&self) -> Result<Vec<SourceFile>, MigrationDiscoveryError> {
+        let entries: Vec<(String, String, Vec<u8>)> = self
+            .entries()?
+            .into_iter()
+            .map(|(path, contents)| {
+                let path = path.trim_start_matches("./");
+                let (dir, filename) = path.rsplit_once('/').unwrap_or(("", path));
+                (dir.to_string(), filename.to_string(), contents)
+            })
+            .collect();
+
+        let prefix = match &self.prefix {
+            Some(prefix) => prefix.clone(),
+            None => default_prefix(&entries),
+        };
+
+        Ok(entries
+            .into_iter()
+            .filter(|(dir, _, _)| *dir == prefix)
+            .map(|(_, filename, contents)| SourceFile { filename, contents })
+            .collect())
+    }
+}
+
+#[cfg(feature = "archive")]
+fn default_prefix(entries: &[(String, String, Vec<u8>)]) -> String {
+    if entries.iter().any(|(dir, _, _)| dir.is_empty()) {
+        return String::new();
+    }
+
+    let mut top_level = entries
+        .iter()
+        .map(|(dir, _, _)| dir.split('/').next().unwrap_or(""));
+    match top_level.next() {
+        Some(first) if top_level.all(|d| d == first) => first.to_string(),
+        _ => String::new(),
+    }
+}
+
+pub fn open_source(path: impl AsRef<Path>) -> Result<Box<dyn MigrationSource>, MigrationDiscoveryError> {
+    let path = path.as_ref();
+
+    if path.is_dir() {
+        return Ok(Box::new(FilesystemSource::new(path)));
+    }
+
+    #[cfg(feature = "archive")]
+    if path.is_file() && ArchiveFormat::from_path(path).is_some() {
+        return Ok(Box::new(ArchiveSource::new(path)?));
+    }
+
+    Err(MigrationDiscoveryError::Io {
+        path: path.display().to_string(),
+        message: "not a migrations directory or supported archive".to_string(),
+    })
+}
+
+#[cfg(all(test, feature = "archive"))]
+mod tests {
+    use super::{ArchiveSource, MigrationSource};
+    use std::io::Write;
+
+    fn names(source: &dyn MigrationSource) -> Vec<String> {
+        let mut names: Vec<String> = source.files().unwrap().into_iter().map(|f| f.filename).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn tar_gz_archive_uses_single_top_level_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("bundle.tar.gz");
+
+        let file = std::fs::File::create(&path).unwrap();
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, body) in [("migrations/1_init.sql", "select 1;"), ("migrations/2_b.sql", "select 2;")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(body.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, body.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let source = ArchiveSource::new(&path).unwrap();
+        assert_eq!(names(&source), vec!["1_init.sql", "2_b.sql"]);
+    }
+
+    #[test]
+    fn zip_archive_honours_explicit_prefix() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("bundle.zip");
+
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for name in ["db/1_init.sql", "docs/README.sql"] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(b"select 1;").unwrap();
+        }
+        writer.finish().unwrap();
+
+        let source = ArchiveSource::new(&path).unwrap().with_prefix("db/");
+        assert_eq!(names(&source), vec!["1_init.sql"]);
+        assert!(ArchiveSource::new(tmp.path().join("bundle.rar")).is_err());
+    }
+}
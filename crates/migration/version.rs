@@ -0,0 +1,142 @@
+use crate::error::VersionParseError;
+use core::fmt;
+use core::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Version {
+    parts: Vec<u64>,
+}
+
+impl Version {
+    pub fn new(parts: impl Into<Vec<u64>>) -> Self {
+        let mut parts = parts.into();
+        while parts.len() > 1 && parts.last() == Some(&0) {
+            parts.pop();
+        }
+        if parts.is_empty() {
+            parts.push(0);
+        }
+
+        Self { parts }
+    }
+
+    pub fn parts(&self) -> &[u64] {
+        &self.parts
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.parts.iter().all(|p| *p == 0)
+    }
+
+    pub fn next(&self) -> Self {
+        let mut parts = self.parts.clone();
+        if let Some(last) = parts.last_mut() {
+            *last = last.saturating_add(1);
+        }
+        Self::new(parts)
+    }
+}
+
+impl From<u64> for Version {
+    fn from(value: u64) -> Self {
+        Self::new(vec![value])
+    }
+}
+
+impl FromStr for Version {
+    type Err = VersionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_parts(s, &['.'])
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{part}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Version {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Version {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+pub(crate) fn parse_parts(input: &str, separators: &[char]) -> Result<Version, VersionParseError> {
+    if input.is_empty() {
+        return Err(VersionParseError {
+            input: input.to_string(),
+            reason: "version must not be empty".to_string(),
+        });
+    }
+
+    let mut parts = Vec::new();
+    for part in input.split(|c| separators.contains(&c)) {
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+            return Err(VersionParseError {
+                input: input.to_string(),
+                reason: "each version segment must be a non-negative integer".to_string(),
+            });
+        }
+
+        let value = part.parse::<u64>().map_err(|_| VersionParseError {
+            input: input.to_string(),
+            reason: format!("version segment '{part}' is too large"),
+        })?;
+        parts.push(value);
+    }
+
+    Ok(Version::new(parts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Version;
+
+    fn v(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn orders_segments_numerically() {
+        let mut versions = vec![v("1.10"), v("1.2.3"), v("2"), v("1.2"), v("1.9")];
+        versions.sort();
+        assert_eq!(versions, vec![v("1.2"), v("1.2.3"), v("1.9"), v("1.10"), v("2")]);
+    }
+
+    #[test]
+    fn trailing_zero_segments_are_equal() {
+        assert_eq!(v("1.0.0"), v("1"));
+        assert_eq!(v("0007").to_string(), "7");
+        assert_eq!(v("1.2.0").to_string(), "1.2");
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for input in ["", "1..2", "v1", "1.a", "-1"] {
+            assert!(input.parse::<Version>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn next_increments_last_segment() {
+        assert_eq!(v("41").next(), v("42"));
+        assert_eq!(v("1.2.3").next(), v("1.2.4"));
+    }
+}
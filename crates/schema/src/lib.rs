@@ -9,6 +9,12 @@ impl DefaultSchemaIntrospector {
     }
 }
 
+impl Default for DefaultSchemaIntrospector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl vellum_contracts::schema::SchemaIntrospector for DefaultSchemaIntrospector {
     type Error = vellum_contracts::Error;
 
@@ -0,0 +1,609 @@
+use crate::error::{sql_snippet, SqlStatementParseError};
+use core::fmt;
+use pg_query::protobuf::{AlterTableType, ConstrType, ObjectType, RangeVar, ReindexObjectType};
+use pg_query::{Node, NodeEnum};
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LockLevel {
+    AccessShare,
+    RowShare,
+    RowExclusive,
+    ShareUpdateExclusive,
+    Share,
+    ShareRowExclusive,
+    Exclusive,
+    AccessExclusive,
+}
+
+impl LockLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LockLevel::AccessShare => "ACCESS SHARE",
+            LockLevel::RowShare => "ROW SHARE",
+            LockLevel::RowExclusive => "ROW EXCLUSIVE",
+            LockLevel::ShareUpdateExclusive => "SHARE UPDATE EXCLUSIVE",
+            LockLevel::Share => "SHARE",
+            LockLevel::ShareRowExclusive => "SHARE ROW EXCLUSIVE",
+            LockLevel::Exclusive => "EXCLUSIVE",
+            LockLevel::AccessExclusive => "ACCESS EXCLUSIVE",
+        }
+    }
+
+    pub fn blocks_writes(&self) -> bool {
+        *self >= LockLevel::Share
+    }
+
+    pub fn blocks_reads(&self) -> bool {
+        *self == LockLevel::AccessExclusive
+    }
+
+    pub fn from_pg_mode(mode: &str) -> Option<Self> {
+        Some(match mode {
+            "AccessShareLock" => LockLevel::AccessShare,
+            "RowShareLock" => LockLevel::RowShare,
+            "RowExclusiveLock" => LockLevel::RowExclusive,
+            "ShareUpdateExclusiveLock" => LockLevel::ShareUpdateExclusive,
+            "ShareLock" => LockLevel::Share,
+            "ShareRowExclusiveLock" => LockLevel::ShareRowExclusive,
+            "ExclusiveLock" => LockLevel::Exclusive,
+            "AccessExclusiveLock" => LockLevel::AccessExclusive,
+            _ => return None,
+        })
+    }
+
+    fn from_lock_mode(mode: i32) -> Option<Self> {
+        Some(match mode {
+            1 => LockLevel::AccessShare,
+            2 => LockLevel::RowShare,
+            3 => LockLevel::RowExclusive,
+            4 => LockLevel::ShareUpdateExclusive,
+            5 => LockLevel::Share,
+            6 => LockLevel::ShareRowExclusive,
+            7 => LockLevel::Exclusive,
+            8 => LockLevel::AccessExclusive,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for LockLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelationLock {
+    pub relation: String,
+    pub level: LockLevel,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementAnalysis {
+    pub kind: String,
+    pub transactional: bool,
+    pub transaction_control: bool,
+    pub explainable: bool,
+    pub locks: Vec<RelationLock>,
+}
+
+impl StatementAnalysis {
+    pub fn strongest_lock(&self) -> Option<&RelationLock> {
+        self.locks.iter().max_by_key(|l| l.level)
+    }
+}
+
+pub fn analyze_statement(
+    sql: &str,
+    source_name: Option<&str>,
+) -> Result<StatementAnalysis, SqlStatementParseError> {
+    let parsed = pg_query::parse(sql).map_err(|e| SqlStatementParseError::SqlParseFailed {
+        source_name: source_name.map(|s| s.to_string()),
+        message: e.to_string(),
+        sql_snippet: sql_snippet(sql),
+    })?;
+
+    let node = parsed
+        .protobuf
+        .stmts
+        .first()
+        .and_then(|raw| raw.stmt.as_ref())
+        .and_then(|stmt| stmt.node.as_ref())
+        .ok_or_else(|| SqlStatementParseError::EmptyStatement {
+            source_name: source_name.map(|s| s.to_string()),
+            statement_index: 0,
+        })?;
+
+    let mut analysis = StatementAnalysis {
+        kind: statement_kind(node),
+        transactional: true,
+        transaction_control: false,
+        explainable: is_explainable(node),
+        locks: Vec::new(),
+    };
+
+    match node {
+        NodeEnum::SelectStmt(_) | NodeEnum::CreateTableAsStmt(_) | NodeEnum::ExplainStmt(_) => {
+            for table in sorted(parsed.select_tables()) {
+                add_lock(&mut analysis.locks, table, LockLevel::AccessShare);
+            }
+        }
+        NodeEnum::InsertStmt(_)
+        | NodeEnum::UpdateStmt(_)
+        | NodeEnum::DeleteStmt(_)
+        | NodeEnum::MergeStmt(_) => {
+            for table in sorted(parsed.dml_tables()) {
+                add_lock(&mut analysis.locks, table, LockLevel::RowExclusive);
+            }
+            for table in sorted(parsed.select_tables()) {
+                add_lock(&mut analysis.locks, table, LockLevel::AccessShare);
+            }
+        }
+        NodeEnum::CreateStmt(stmt) => {
+            for element in &stmt.table_elts {
+                for constraint in column_or_table_constraints(element) {
+                    foreign_key_lock(&mut analysis.locks, constraint);
+                }
+            }
+            for parent in stmt.inh_relations.iter().filter_map(range_var) {
+                let level = if stmt.partbound.is_some() {
+                    LockLevel::AccessExclusive
+                } else {
+                    LockLevel::ShareUpdateExclusive
+                };
+                add_lock(&mut analysis.locks, relation_name(parent), level);
+            }
+        }
+        NodeEnum::AlterTableStmt(stmt) => {
+            let relation = stmt.relation.as_ref().map(relation_name).unwrap_or_default();
+            for cmd in &stmt.cmds {
+                let Some(NodeEnum::AlterTableCmd(cmd)) = cmd.node.as_ref() else {
+                    continue;
+                };
+
+                let subtype = AlterTableType::try_from(cmd.subtype).ok();
+                let constraint = cmd.def.as_deref().and_then(|def| match def.node.as_ref() {
+                    Some(NodeEnum::Constraint(c)) => Some(c.as_ref()),
+                    _ => None,
+                });
+
+                let concurrent = matches!(
+                    cmd.def.as_deref().and_then(|d| d.node.as_ref()),
+                    Some(NodeEnum::PartitionCmd(p)) if p.concurrent
+                );
+                if concurrent {
+                    analysis.transactional = false;
+                }
+
+                let level = match (subtype, constraint) {
+                    (Some(AlterTableType::AtAddConstraint), Some(c))
+                        if c.contype == ConstrType::ConstrForeign as i32 =>
+                    {
+                        foreign_key_lock(&mut analysis.locks, c);
+                        LockLevel::ShareRowExclusive
+                    }
+                    (Some(subtype), _) => alter_table_lock(subtype, concurrent),
+                    (None, _) => LockLevel::AccessExclusive,
+                };
+                add_lock(&mut analysis.locks, relation.clone(), level);
+            }
+        }
+        NodeEnum::IndexStmt(stmt) => {
+            if stmt.concurrent {
+                analysis.transactional = false;
+            }
+            if let Some(relation) = &stmt.relation {
+                let level = if stmt.concurrent {
+                    LockLevel::ShareUpdateExclusive
+                } else {
+                    LockLevel::Share
+                };
+                add_lock(&mut analysis.locks, relation_name(relation), level);
+            }
+        }
+        NodeEnum::DropStmt(stmt) => {
+            if stmt.concurrent {
+                analysis.transactional = false;
+            }
+            if is_relation_object(stmt.remove_type) {
+                let level = if stmt.concurrent {
+                    LockLevel::ShareUpdateExclusive
+                } else {
+                    LockLevel::AccessExclusive
+                };
+                for object in &stmt.objects {
+                    if let Some(name) = object_name(object) {
+                        add_lock(&mut analysis.locks, name, level);
+                    }
+                }
+            }
+        }
+        NodeEnum::TruncateStmt(stmt) => {
+            for relation in stmt.relations.iter().filter_map(range_var) {
+                add_lock(&mut analysis.locks, relation_name(relation), LockLevel::AccessExclusive);
+            }
+        }
+        NodeEnum::RenameStmt(stmt) => {
+            if let Some(relation) = &stmt.relation {
+                add_lock(&mut analysis.locks, relation_name(relation), LockLevel::AccessExclusive);
+            }
+        }
+        NodeEnum::ViewStmt(stmt) => {
+            if let (true, Some(view)) = (stmt.replace, &stmt.view) {
+                add_lock(&mut analysis.locks, relation_name(view), LockLevel::AccessExclusive);
+            }
+        }
+        NodeEnum::CreateTrigStmt(stmt) => {
+            if let Some(relation) = &stmt.relation {
+                add_lock(&mut analysis.locks, relation_name(relation), LockLevel::ShareRowExclusive);
+            }
+        }
+        NodeEnum::RefreshMatViewStmt(stmt) => {
+            if let Some(relation) = &stmt.relation {
+                let level = if stmt.concurrent {
+                    LockLevel::Exclusive
+                } else {
+                    LockLevel::AccessExclusive
+                };
+                add_lock(&mut analysis.locks, relation_name(relation), level);
+            }
+        }
+        NodeEnum::LockStmt(stmt) => {
+            if let Some(level) = LockLevel::from_lock_mode(stmt.mode) {
+                for relation in stmt.relations.iter().filter_map(range_var) {
+                    add_lock(&mut analysis.locks, relation_name(relation), level);
+                }
+            }
+        }
+        NodeEnum::ClusterStmt(stmt) => {
+            if let Some(relation) = &stmt.relation {
+                add_lock(&mut analysis.locks, relation_name(relation), LockLevel::AccessExclusive);
+            }
+        }
+        NodeEnum::ReindexStmt(stmt) => {
+            let concurrent = has_option(&stmt.params, "concurrently");
+            if concurrent {
+                analysis.transactional = false;
+            }
+            if let Some(relation) = &stmt.relation {
+                let level = match (concurrent, ReindexObjectType::try_from(stmt.kind)) {
+                    (true, _) => LockLevel::ShareUpdateExclusive,
+                    (false, Ok(ReindexObjectType::ReindexObjectIndex)) => {
+                        LockLevel::AccessExclusive
+                    }
+                    (false, _) => LockLevel::Share,
+                };
+                add_lock(&mut analysis.locks, relation_name(relation), level);
+            } else {
+                analysis.transactional = false;
+            }
+        }
+        NodeEnum::VacuumStmt(stmt) => {
+            if stmt.is_vacuumcmd {
+                analysis.transactional = false;
+            }
+            let level = if stmt.is_vacuumcmd && has_option(&stmt.options, "full") {
+                LockLevel::AccessExclusive
+            } else {
+                LockLevel::ShareUpdateExclusive
+            };
+            for rel in &stmt.rels {
+                if let Some(NodeEnum::VacuumRelation(rel)) = rel.node.as_ref() {
+                    if let Some(relation) = &rel.relation {
+                        add_lock(&mut analysis.locks, relation_name(relation), level);
+                    }
+                }
+            }
+        }
+        NodeEnum::TransactionStmt(_) => {
+            analysis.transactional = false;
+            analysis.transaction_control = true;
+        }
+        NodeEnum::CreatedbStmt(_)
+        | NodeEnum::DropdbStmt(_)
+        | NodeEnum::AlterSystemStmt(_)
+        | NodeEnum::CreateTableSpaceStmt(_)
+        | NodeEnum::DropTableSpaceStmt(_)
+        | NodeEnum::CreateSubscriptionStmt(_)
+        | NodeEnum::DropSubscriptionStmt(_) => {
+            analysis.transactional = false;
+        }
+        _ => {}
+    }
+
+    Ok(analysis)
+}
+
+fn is_explainable(node: &NodeEnum) -> bool {
+    match node {
+        NodeEnum::UpdateStmt(_) | NodeEnum::DeleteStmt(_) | NodeEnum::MergeStmt(_) => true,
+        NodeEnum::InsertStmt(stmt) => matches!(
+            stmt.select_stmt.as_ref().and_then(|s| s.node.as_ref()),
+            Some(NodeEnum::SelectStmt(select)) if select.values_lists.is_empty()
+        ),
+        _ => false,
+    }
+}
+
+fn statement_kind(node: &NodeEnum) -> String {
+    let kind = match node {
+        NodeEnum::SelectStmt(_) => "SELECT",
+        NodeEnum::InsertStmt(_) => "INSERT",
+        NodeEnum::UpdateStmt(_) => "UPDATE",
+        NodeEnum::DeleteStmt(_) => "DELETE",
+        NodeEnum::MergeStmt(_) => "MERGE",
+        NodeEnum::CreateStmt(_) => "CREATE TABLE",
+        NodeEnum::CreateTableAsStmt(_) => "CREATE TABLE AS",
+        NodeEnum::AlterTableStmt(stmt) => match ObjectType::try_from(stmt.objtype) {
+            Ok(ObjectType::ObjectIndex) => "ALTER INDEX",
+            Ok(ObjectType::ObjectView) => "ALTER VIEW",
+            Ok(ObjectType::ObjectMatview) => "ALTER MATERIALIZED VIEW",
+            Ok(ObjectType::ObjectSequence) => "ALTER SEQUENCE",
+            Ok(ObjectType::ObjectForeignTable) => "ALTER FOREIGN TABLE",
+            _ => "ALTER TABLE",
+        },
+        NodeEnum::IndexStmt(_) => "CREATE INDEX",
+        NodeEnum::DropStmt(stmt) => {
+            return format!("DROP {}", object_type_name(stmt.remove_type));
+        }
+        NodeEnum::TruncateStmt(_) => "TRUNCATE",
+        NodeEnum::RenameStmt(_) => "RENAME",
+        NodeEnum::ViewStmt(_) => "CREATE VIEW",
+        NodeEnum::CreateFunctionStmt(stmt) if stmt.is_procedure => "CREATE PROCEDURE",
+        NodeEnum::CreateFunctionStmt(_) => "CREATE FUNCTION",
+        NodeEnum::DoStmt(_) => "DO",
+        NodeEnum::CreateTrigStmt(_) => "CREATE TRIGGER",
+        NodeEnum::RefreshMatViewStmt(_) => "REFRESH MATERIALIZED VIEW",
+        NodeEnum::LockStmt(_) => "LOCK",
+        NodeEnum::ClusterStmt(_) => "CLUSTER",
+        NodeEnum::ReindexStmt(_) => "REINDEX",
+        NodeEnum::VacuumStmt(stmt) if stmt.is_vacuumcmd => "VACUUM",
+        NodeEnum::VacuumStmt(_) => "ANALYZE",
+        NodeEnum::TransactionStmt(_) => "TRANSACTION CONTROL",
+        NodeEnum::CreateSeqStmt(_) => "CREATE SEQUENCE",
+        NodeEnum::AlterSeqStmt(_) => "ALTER SEQUENCE",
+        NodeEnum::CreateEnumStmt(_) | NodeEnum::CompositeTypeStmt(_) => "CREATE TYPE",
+        NodeEnum::AlterEnumStmt(_) => "ALTER TYPE",
+        NodeEnum::CreateSchemaStmt(_) => "CREATE SCHEMA",
+        NodeEnum::CreateExtensionStmt(_) => "CREATE EXTENSION",
+        NodeEnum::GrantStmt(stmt) if stmt.is_grant => "GRANT",
+        NodeEnum::GrantStmt(_) => "REVOKE",
+        NodeEnum::CommentStmt(_) => "COMMENT",
+        NodeEnum::CopyStmt(_) => "COPY",
+        NodeEnum::VariableSetStmt(_) => "SET",
+        NodeEnum::CreatedbStmt(_) => "CREATE DATABASE",
+        NodeEnum::DropdbStmt(_) => "DROP DATABASE",
+        NodeEnum::AlterSystemStmt(_) => "ALTER SYSTEM",
+        NodeEnum::ExplainStmt(_) => "EXPLAIN",
+        other => node_name(other),
+    };
+    kind.to_string()
+}
+
+// Statements that need no inspection beyond their node type.
+fn node_name(node: &NodeEnum) -> &'static str {
+    match node {
+        NodeEnum::AlterCollationStmt(_) => "ALTER COLLATION",
+        NodeEnum::AlterDatabaseRefreshCollStmt(_)
+        | NodeEnum::AlterDatabaseSetStmt(_)
+        | NodeEnum::AlterDatabaseStmt(_) => "ALTER DATABASE",
+        NodeEnum::AlterDefaultPrivilegesStmt(_) => "ALTER DEFAULT PRIVILEGES",
+        NodeEnum::AlterDomainStmt(_) => "ALTER DOMAIN",
+        NodeEnum::AlterEventTrigStmt(_) => "ALTER EVENT TRIGGER",
+        NodeEnum::AlterExtensionContentsStmt(_)
+        | NodeEnum::AlterExtensionStmt(_) => "ALTER EXTENSION",
+        NodeEnum::AlterFdwStmt(_) => "ALTER FOREIGN DATA WRAPPER",
+        NodeEnum::AlterForeignServerStmt(_) => "ALTER SERVER",
+        NodeEnum::AlterFunctionStmt(_) => "ALTER FUNCTION",
+        NodeEnum::AlterObjectDependsStmt(_) => "ALTER DEPENDS ON EXTENSION",
+        NodeEnum::AlterObjectSchemaStmt(_) => "ALTER SET SCHEMA",
+        NodeEnum::AlterOpFamilyStmt(_) => "ALTER OPERATOR FAMILY",
+        NodeEnum::AlterOperatorStmt(_) => "ALTER OPERATOR",
+        NodeEnum::AlterOwnerStmt(_) => "ALTER OWNER",
+        NodeEnum::AlterPolicyStmt(_) => "ALTER POLICY",
+        NodeEnum::AlterPublicationStmt(_) => "ALTER PUBLICATION",
+        NodeEnum::AlterRoleSetStmt(_) | NodeEnum::AlterRoleStmt(_) => "ALTER ROLE",
+        NodeEnum::AlterStatsStmt(_) => "ALTER STATISTICS",
+        NodeEnum::AlterSubscriptionStmt(_) => "ALTER SUBSCRIPTION",
+        NodeEnum::AlterTableMoveAllStmt(_) => "ALTER TABLE ALL IN TABLESPACE",
+        NodeEnum::AlterTableSpaceOptionsStmt(_) => "ALTER TABLESPACE",
+        NodeEnum::AlterTsconfigurationStmt(_) => "ALTER TEXT SEARCH CONFIGURATION",
+        NodeEnum::AlterTsdictionaryStmt(_) => "ALTER TEXT SEARCH DICTIONARY",
+        NodeEnum::AlterTypeStmt(_) => "ALTER TYPE",
+        NodeEnum::AlterUserMappingStmt(_) => "ALTER USER MAPPING",
+        NodeEnum::CallStmt(_) => "CALL",
+        NodeEnum::CheckPointStmt(_) => "CHECKPOINT",
+        NodeEnum::ClosePortalStmt(_) => "CLOSE",
+        NodeEnum::ConstraintsSetStmt(_) => "SET CONSTRAINTS",
+        NodeEnum::CreateAmStmt(_) => "CREATE ACCESS METHOD",
+        NodeEnum::CreateCastStmt(_) => "CREATE CAST",
+        NodeEnum::CreateConversionStmt(_) => "CREATE CONVERSION",
+        NodeEnum::CreateDomainStmt(_) => "CREATE DOMAIN",
+        NodeEnum::CreateEventTrigStmt(_) => "CREATE EVENT TRIGGER",
+        NodeEnum::CreateFdwStmt(_) => "CREATE FOREIGN DATA WRAPPER",
+        NodeEnum::CreateForeignServerStmt(_) => "CREATE SERVER",
+        NodeEnum::CreateForeignTableStmt(_) => "CREATE FOREIGN TABLE",
+        NodeEnum::CreateOpClassStmt(_) => "CREATE OPERATOR CLASS",
+        NodeEnum::CreateOpFamilyStmt(_) => "CREATE OPERATOR FAMILY",
+        NodeEnum::CreatePlangStmt(_) => "CREATE LANGUAGE",
+        NodeEnum::CreatePolicyStmt(_) => "CREATE POLICY",
+        NodeEnum::CreatePublicationStmt(_) => "CREATE PUBLICATION",
+        NodeEnum::CreateRangeStmt(_) => "CREATE TYPE",
+        NodeEnum::CreateRoleStmt(_) => "CREATE ROLE",
+        NodeEnum::CreateStatsStmt(_) => "CREATE STATISTICS",
+        NodeEnum::CreateSubscriptionStmt(_) => "CREATE SUBSCRIPTION",
+        NodeEnum::CreateTableSpaceStmt(_) => "CREATE TABLESPACE",
+        NodeEnum::CreateTransformStmt(_) => "CREATE TRANSFORM",
+        NodeEnum::CreateUserMappingStmt(_) => "CREATE USER MAPPING",
+        NodeEnum::DeallocateStmt(_) => "DEALLOCATE",
+        NodeEnum::DeclareCursorStmt(_) => "DECLARE CURSOR",
+        NodeEnum::DefineStmt(stmt) => match ObjectType::try_from(stmt.kind) {
+            Ok(ObjectType::ObjectAggregate) => "CREATE AGGREGATE",
+            Ok(ObjectType::ObjectOperator) => "CREATE OPERATOR",
+            Ok(ObjectType::ObjectCollation) => "CREATE COLLATION",
+            Ok(ObjectType::ObjectTsparser) => "CREATE TEXT SEARCH PARSER",
+            Ok(ObjectType::ObjectTsdictionary) => "CREATE TEXT SEARCH DICTIONARY",
+            Ok(ObjectType::ObjectTstemplate) => "CREATE TEXT SEARCH TEMPLATE",
+            Ok(ObjectType::ObjectTsconfiguration) => "CREATE TEXT SEARCH CONFIGURATION",
+            _ => "CREATE TYPE",
+        },
+        NodeEnum::DiscardStmt(_) => "DISCARD",
+        NodeEnum::DropOwnedStmt(_) => "DROP OWNED",
+        NodeEnum::DropRoleStmt(_) => "DROP ROLE",
+        NodeEnum::DropSubscriptionStmt(_) => "DROP SUBSCRIPTION",
+        NodeEnum::DropTableSpaceStmt(_) => "DROP TABLESPACE",
+        NodeEnum::DropUserMappingStmt(_) => "DROP USER MAPPING",
+        NodeEnum::ExecuteStmt(_) => "EXECUTE",
+        NodeEnum::FetchStmt(_) => "FETCH",
+        NodeEnum::GrantRoleStmt(stmt) if stmt.is_grant => "GRANT ROLE",
+        NodeEnum::GrantRoleStmt(_) => "REVOKE ROLE",
+        NodeEnum::ImportForeignSchemaStmt(_) => "IMPORT FOREIGN SCHEMA",
+        NodeEnum::ListenStmt(_) => "LISTEN",
+        NodeEnum::LoadStmt(_) => "LOAD",
+        NodeEnum::NotifyStmt(_) => "NOTIFY",
+        NodeEnum::PrepareStmt(_) => "PREPARE",
+        NodeEnum::ReassignOwnedStmt(_) => "REASSIGN OWNED",
+        NodeEnum::ReplicaIdentityStmt(_) => "ALTER TABLE",
+        NodeEnum::RuleStmt(_) => "CREATE RULE",
+        NodeEnum::SecLabelStmt(_) => "SECURITY LABEL",
+        NodeEnum::UnlistenStmt(_) => "UNLISTEN",
+        NodeEnum::VariableShowStmt(_) => "SHOW",
+        _ => "OTHER",
+    }
+}
+
+fn object_type_name(object_type: i32) -> &'static str {
+    match ObjectType::try_from(object_type) {
+        Ok(ObjectType::ObjectTable) => "TABLE",
+        Ok(ObjectType::ObjectIndex) => "INDEX",
+        Ok(ObjectType::ObjectView) => "VIEW",
+        Ok(ObjectType::ObjectMatview) => "MATERIALIZED VIEW",
+        Ok(ObjectType::ObjectSequence) => "SEQUENCE",
+        Ok(ObjectType::ObjectForeignTable) => "FOREIGN TABLE",
+        Ok(ObjectType::ObjectSchema) => "SCHEMA",
+        Ok(ObjectType::ObjectType) => "TYPE",
+        Ok(ObjectType::ObjectDomain) => "DOMAIN",
+        Ok(ObjectType::ObjectFunction) => "FUNCTION",
+        Ok(ObjectType::ObjectProcedure) => "PROCEDURE",
+        Ok(ObjectType::ObjectTrigger) => "TRIGGER",
+        Ok(ObjectType::ObjectExtension) => "EXTENSION",
+        Ok(ObjectType::ObjectPolicy) => "POLICY",
+        Ok(ObjectType::ObjectRule) => "RULE",
+        _ => "OBJECT",
+    }
+}
+
+fn is_relation_object(object_type: i32) -> bool {
+    matches!(
+        ObjectType::try_from(object_type),
+        Ok(ObjectType::ObjectTable
+            | ObjectType::ObjectIndex
+            | ObjectType::ObjectView
+            | ObjectType::ObjectMatview
+            | ObjectType::ObjectSequence
+            | ObjectType::ObjectForeignTable)
+    )
+}
+
+fn alter_table_lock(subtype: AlterTableType, concurrent: bool) -> LockLevel {
+    use AlterTableType::*;
+
+    match subtype {
+        AtSetStatistics | AtClusterOn | AtDropCluster | AtSetOptions | AtResetOptions
+        | AtValidateConstraint | AtAttachPartition | AtSetRelOptions | AtResetRelOptions => {
+            LockLevel::ShareUpdateExclusive
+        }
+        AtDetachPartition | AtDetachPartitionFinalize if concurrent => {
+            LockLevel::ShareUpdateExclusive
+        }
+        AtEnableTrig | AtEnableAlwaysTrig | AtEnableReplicaTrig | AtDisableTrig
+        | AtEnableTrigAll | AtDisableTrigAll | AtEnableTrigUser | AtDisableTrigUser => {
+            LockLevel::ShareRowExclusive
+        }
+        _ => LockLevel::AccessExclusive,
+    }
+}
+
+fn column_or_table_constraints(element: &Node) -> Vec<&pg_query::protobuf::Constraint> {
+    match element.node.as_ref() {
+        Some(NodeEnum::Constraint(c)) => vec![c.as_ref()],
+        Some(NodeEnum::ColumnDef(col)) => col
+            .constraints
+            .iter()
+            .filter_map(|n| match n.node.as_ref() {
+                Some(NodeEnum::Constraint(c)) => Some(c.as_ref()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn foreign_key_lock(locks: &mut Vec<RelationLock>, constraint: &pg_query::protobuf::Constraint) {
+    if constraint.contype != ConstrType::ConstrForeign as i32 {
+        return;
+    }
+    if let Some(pktable) = &constraint.pktable {
+        add_lock(locks, relation_name(pktable), LockLevel::ShareRowExclusive);
+    }
+}
+
+fn has_option(options: &[Node], name: &str) -> bool {
+    options.iter().any(|o| match o.node.as_ref() {
+        Some(NodeEnum::DefElem(d)) => d.defname.eq_ignore_ascii_case(name),
+        _ => false,
+    })
+}
+
+fn range_var(node: &Node) -> Option<&RangeVar> {
+    match node.node.as_ref() {
+        Some(NodeEnum::RangeVar(r)) => Some(r),
+        _ => None,
+    }
+}
+
+fn relation_name(relation: &RangeVar) -> String {
+    if relation.schemaname.is_empty() {
+        relation.relname.clone()
+    } else {
+        format!("{}.{}", relation.schemaname, relation.relname)
+    }
+}
+
+fn object_name(node: &Node) -> Option<String> {
+    match node.node.as_ref() {
+        Some(NodeEnum::List(list)) => {
+            let parts: Vec<&str> = list
+                .items
+                .iter()
+                .filter_map(|item| match item.node.as_ref() {
+                    Some(NodeEnum::String(s)) => Some(s.sval.as_str()),
+                    _ => None,
+                })
+                .collect();
+            (!parts.is_empty()).then(|| parts.join("."))
+        }
+        Some(NodeEnum::String(s)) => Some(s.sval.clone()),
+        _ => None,
+    }
+}
+
+fn sorted(mut tables: Vec<String>) -> Vec<String> {
+    tables.sort();
+    tables
+}
+
+fn add_lock(locks: &mut Vec<RelationLock>, relation: String, level: LockLevel) {
+    if relation.is_empty() {
+        return;
+    }
+
+    match locks.iter_mut().find(|l| l.relation == relation) {
+        Some(existing) => existing.level = existing.level.max(level),
+        None => locks.push(RelationLock { relation, level }),
+    }
+}
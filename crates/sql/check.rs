@@ -0,0 +1,346 @@
+use pg_query::protobuf::{DefElem, Token, TransactionStmtKind};
+use pg_query::{Node, NodeEnum, ParseResult};
+
+const BODY_QUOTE: &str = "$vellum$";
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    SyntaxError,
+    TransactionControl,
+    PlpgsqlError,
+    EmptyMigration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub message: String,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub line: usize,
+    pub column: usize,
+    pub line_text: String,
+}
+
+impl Diagnostic {
+    pub fn location(&self, sql: &str) -> SourceLocation {
+        locate(sql, self.offset)
+    }
+}
+
+pub fn check_sql(sql: &str) -> Vec<Diagnostic> {
+    let parsed = match vellum_sql::parse_sql(sql) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            let offset = err
+                .position()
+                .map(|position| byte_offset(sql, position))
+                .unwrap_or(0);
+
+            return vec![Diagnostic {
+                kind: DiagnosticKind::SyntaxError,
+                message: parse_message(err.message()),
+                offset: skip_trivia(sql, offset),
+            }];
+        }
+    };
+    let parsed = parsed.ast();
+
+    if parsed.protobuf.stmts.is_empty() {
+        return vec![Diagnostic {
+            kind: DiagnosticKind::EmptyMigration,
+            message: "migration contains no SQL statements".to_string(),
+            offset: 0,
+        }];
+    }
+
+    let mut diagnostics = Vec::new();
+    for raw in &parsed.protobuf.stmts {
+        let Some(NodeEnum::TransactionStmt(stmt)) =
+            raw.stmt.as_ref().and_then(|stmt| stmt.node.as_ref())
+        else {
+            continue;
+        };
+
+        let Some(command) = forbidden_transaction_command(stmt.kind) else {
+            continue;
+        };
+
+        let location = usize::try_from(raw.stmt_location).unwrap_or(0);
+        diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::TransactionControl,
+            message: format!(
+                "{command} is not allowed in a migration file; vellum runs each migration in its own transaction"
+            ),
+            offset: skip_trivia(sql, location),
+        });
+    }
+
+    diagnostics.extend(plpgsql_diagnostics(sql, parsed));
+    diagnostics.sort_by_key(|d| d.offset);
+    diagnostics
+}
+
+pub fn check_plpgsql(sql: &str) -> Vec<Diagnostic> {
+    match pg_query::parse(sql) {
+        Ok(parsed) => plpgsql_diagnostics(sql, &parsed),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub fn locate(sql: &str, offset: usize) -> SourceLocation {
+    let offset = offset.min(sql.len());
+    let offset = (0..=offset)
+        .rev()
+        .find(|i| sql.is_char_boundary(*i))
+        .unwrap_or(0);
+
+    let line_start = sql[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = sql[offset..]
+        .find('\n')
+        .map(|i| offset + i)
+        .unwrap_or(sql.len());
+
+    SourceLocation {
+        line: sql[..line_start].matches('\n').count() + 1,
+        column: sql[line_start..offset].chars().count() + 1,
+        line_text: sql[line_start..line_end].trim_end_matches('\r').to_string(),
+    }
+}
+
+struct PlpgsqlBlock {
+    label: String,
+    body: String,
+    as_location: usize,
+}
+
+fn plpgsql_diagnostics(sql: &str, parsed: &ParseResult) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for raw in &parsed.protobuf.stmts {
+        let Some(block) = raw
+            .stmt
+            .as_ref()
+            .and_then(|stmt| stmt.node.as_ref())
+            .and_then(plpgsql_block)
+        else {
+            continue;
+        };
+
+        let start = usize::try_from(raw.stmt_location).unwrap_or(0).min(sql.len());
+        let end = match usize::try_from(raw.stmt_len) {
+            Ok(len) if len > 0 => (start + len).min(sql.len()),
+            _ => sql.len(),
+        };
+
+        let Err(err) = pg_query::parse_plpgsql(&sql[start..end]) else {
+            continue;
+        };
+
+        let message = parse_message(&err.to_string());
+        let offset = locate_plpgsql_error(sql, start..end, &block, &message)
+            .unwrap_or_else(|| skip_trivia(sql, start));
+
+        diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::PlpgsqlError,
+            message: format!("{message} (in PL/pgSQL {})", block.label),
+            offset,
+        });
+    }
+
+    diagnostics
+}
+
+fn plpgsql_block(node: &NodeEnum) -> Option<PlpgsqlBlock> {
+    match node {
+        NodeEnum::CreateFunctionStmt(stmt) => {
+            let language = def_elem(&stmt.options, "language").and_then(def_string)?;
+            if !language.eq_ignore_ascii_case("plpgsql") {
+                return None;
+            }
+
+            let body = def_elem(&stmt.options, "as")?;
+            let name = stmt
+                .funcname
+                .iter()
+                .filter_map(|n| match n.node.as_ref() {
+                    Some(NodeEnum::String(s)) => Some(s.sval.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(".");
+            let kind = if stmt.is_procedure { "procedure" } else { "function" };
+
+            Some(PlpgsqlBlock {
+                label: format!("{kind} {name}"),
+                body: def_string(body)?.to_string(),
+                as_location: usize::try_from(body.location).ok()?,
+            })
+        }
+        NodeEnum::DoStmt(stmt) => {
+            let language = def_elem(&stmt.args, "language")
+                .and_then(def_string)
+                .unwrap_or("plpgsql");
+            if !language.eq_ignore_ascii_case("plpgsql") {
+                return None;
+            }
+
+            let body = def_elem(&stmt.args, "as")?;
+            Some(PlpgsqlBlock {
+                label: "DO block".to_string(),
+                body: def_string(body)?.to_string(),
+                as_location: usize::try_from(body.location).ok()?,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn def_elem<'a>(options: &'a [Node], name: &str) -> Option<&'a DefElem> {
+    options.iter().find_map(|option| match option.node.as_ref() {
+        Some(NodeEnum::DefElem(elem)) if elem.defname == name => Some(elem.as_ref()),
+        _ => None,
+    })
+}
+
+fn def_string(elem: &DefElem) -> Option<&str> {
+    match elem.arg.as_ref()?.node.as_ref()? {
+        NodeEnum::String(s) => Some(&s.sval),
+        NodeEnum::List(list) => match list.items.first()?.node.as_ref()? {
+            NodeEnum::String(s) => Some(&s.sval),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// The PL/pgSQL compiler reports no position through pg_query, so binary-search
+// for the shortest body prefix that reproduces the error and map it back to
+// the file.
+fn locate_plpgsql_error(
+    sql: &str,
+    statement: std::ops::Range<usize>,
+    block: &PlpgsqlBlock,
+    message: &str,
+) -> Option<usize> {
+    let scanned = pg_query::scan(&sql[statement.clone()]).ok()?;
+    let as_location = block.as_location.checked_sub(statement.start)?;
+    let literal = scanned
+        .tokens
+        .iter()
+        .find(|t| {
+            t.token == Token::Sconst as i32
+                && usize::try_from(t.start).is_ok_and(|start| start >= as_location)
+        })?;
+
+    let literal_start = statement.start + usize::try_from(literal.start).ok()?;
+    let literal_end = statement.start + usize::try_from(literal.end).ok()?;
+    let offsets = body_offsets(&sql[literal_start..literal_end], literal_start)?;
+    if offsets.len() != block.body.len() || block.body.contains(BODY_QUOTE) {
+        return None;
+    }
+
+    let reproduces = |end: usize| {
+        let candidate = format!(
+            "{}{BODY_QUOTE}{}{BODY_QUOTE}{}",
+            &sql[statement.start..literal_start],
+            &block.body[..end],
+            &sql[literal_end..statement.end]
+        );
+        matches!(pg_query::parse_plpgsql(&candidate), Err(e) if parse_message(&e.to_string()) == message)
+    };
+
+    let near = match message.split_once(" at or near \"") {
+        Some((_, near)) => Some(near.strip_suffix('"').unwrap_or(near)),
+        None => message
+            .strip_prefix('"')
+            .and_then(|rest| rest.split_once('"'))
+            .map(|(name, _)| name),
+    };
+
+    let body_offset = match pg_query::scan(&block.body) {
+        Ok(tokens) => {
+            let spans: Vec<(usize, usize)> = tokens
+                .tokens
+                .iter()
+                .filter_map(|t| Some((usize::try_from(t.start).ok()?, usize::try_from(t.end).ok()?)))
+                .collect();
+
+            match spans.get(spans.partition_point(|(_, end)| !reproduces(*end))) {
+                Some((start, end)) => near
+                    .and_then(|near| block.body[..*end].rfind(near))
+                    .unwrap_or(*start),
+                None => spans.last().map(|(start, _)| *start).unwrap_or(0),
+            }
+        }
+        Err(_) => near.and_then(|near| block.body.find(near)).unwrap_or(0),
+    };
+
+    offsets.get(body_offset).copied()
+}
+
+fn body_offsets(literal: &str, literal_start: usize) -> Option<Vec<usize>> {
+    if let Some(rest) = literal.strip_prefix('$') {
+        let tag_len = rest.find('$')? + 2;
+        let content_len = literal.len().checked_sub(2 * tag_len)?;
+        return Some((0..content_len).map(|i| literal_start + tag_len + i).collect());
+    }
+
+    let inner = literal.strip_prefix('\'')?.strip_suffix('\'')?;
+    let bytes = inner.as_bytes();
+    let mut offsets = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        offsets.push(literal_start + 1 + i);
+        i += if bytes[i] == b'\'' { 2 } else { 1 };
+    }
+    Some(offsets)
+}
+
+fn forbidden_transaction_command(kind: i32) -> Option<&'static str> {
+    match TransactionStmtKind::try_from(kind).ok()? {
+        TransactionStmtKind::TransStmtBegin => Some("BEGIN"),
+        TransactionStmtKind::TransStmtStart => Some("START TRANSACTION"),
+        TransactionStmtKind::TransStmtCommit => Some("COMMIT"),
+        TransactionStmtKind::TransStmtRollback => Some("ROLLBACK"),
+        TransactionStmtKind::TransStmtPrepare => Some("PREPARE TRANSACTION"),
+        TransactionStmtKind::TransStmtCommitPrepared => Some("COMMIT PREPARED"),
+        TransactionStmtKind::TransStmtRollbackPrepared => Some("ROLLBACK PREPARED"),
+        _ => None,
+    }
+}
+
+fn parse_message(message: &str) -> String {
+    message
+        .strip_prefix("Invalid statement: ")
+        .unwrap_or(message)
+        .to_string()
+}
+
+fn byte_offset(sql: &str, position: usize) -> usize {
+    sql.char_indices()
+        .nth(position.saturating_sub(1))
+        .map(|(i, _)| i)
+        .unwrap_or(sql.len())
+}
+
+fn skip_trivia(sql: &str, mut offset: usize) -> usize {
+    loop {
+        let rest = &sql[offset..];
+        let trimmed = rest.trim_start();
+        offset += rest.len() - trimmed.len();
+
+        if trimmed.starts_with("--") {
+            offset += trimmed.find('\n').unwrap_or(trimmed.len());
+        } else if let Some(comment) = trimmed.strip_prefix("/*") {
+            offset += comment.find("*/").map(|i| i + 4).unwrap_or(trimmed.len());
+        } else {
+            return offset;
+        }
+    }
+}
@@ -31,6 +31,12 @@ impl DefaultSqlEngine {
     }
 }
 
+impl Default for DefaultSqlEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl vellum_contracts::sql::SqlEngine for DefaultSqlEngine {
     type Error = vellum_contracts::Error;
 
@@ -39,11 +45,15 @@ impl vellum_contracts::sql::SqlEngine for DefaultSqlEngine {
     }
 }
 
+pub mod analysis;
+pub mod check;
 pub mod error;
 pub mod model;
 pub mod parser;
 pub mod pg_query;
 
+pub use analysis::{analyze_statement, LockLevel, RelationLock, StatementAnalysis};
+pub use check::{check_plpgsql, check_sql, locate, Diagnostic, DiagnosticKind, SourceLocation};
 pub use error::SqlStatementParseError;
 pub use model::SqlStatement;
 pub use parser::StatementParser;
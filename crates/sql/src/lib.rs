@@ -31,6 +31,12 @@ impl DefaultSqlEngine {
     }
 }
 
+impl Default for DefaultSqlEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl vellum_contracts::sql::SqlEngine for DefaultSqlEngine {
     type Error = vellum_contracts::Error;
 
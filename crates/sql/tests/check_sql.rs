@@ -0,0 +1,71 @@
+use vellum_sql_engine::{check_plpgsql, check_sql, DiagnosticKind};
+
+#[test]
+fn syntax_error_is_located_at_offending_token() {
+    let sql = "-- create users\nCREATE TABLE users (id int);\nALTER TABLE users ADD COLUMN name text NOT NUL;\n";
+    let diagnostics = check_sql(sql);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::SyntaxError);
+    assert_eq!(diagnostics[0].message, "syntax error at or near \"NUL\"");
+
+    let location = diagnostics[0].location(sql);
+    assert_eq!((location.line, location.column), (3, 44));
+    assert_eq!(location.line_text, "ALTER TABLE users ADD COLUMN name text NOT NUL;");
+}
+
+#[test]
+fn transaction_control_statements_are_flagged() {
+    let sql = "BEGIN;\nCREATE TABLE t (id int);\nSAVEPOINT s;\n  -- done\n  COMMIT;\n";
+    let diagnostics = check_sql(sql);
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![DiagnosticKind::TransactionControl, DiagnosticKind::TransactionControl]
+    );
+
+    let location = diagnostics[1].location(sql);
+    assert_eq!((location.line, location.column), (5, 3));
+}
+
+#[test]
+fn valid_and_empty_migrations() {
+    assert!(check_sql("CREATE INDEX CONCURRENTLY i ON t (c);").is_empty());
+    assert_eq!(check_sql("-- nothing here\n")[0].kind, DiagnosticKind::EmptyMigration);
+}
+
+#[test]
+fn plpgsql_function_body_errors_use_file_lines() {
+    let sql = "CREATE TABLE t (id int);\n\nCREATE FUNCTION bump(n int) RETURNS int\nLANGUAGE plpgsql AS $$\nDECLARE\n  x int;\nBEGIN\n  x := n + 1\n  RETURN x;\nEND;\n$$;\n";
+    let diagnostics = check_sql(sql);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::PlpgsqlError);
+    assert!(diagnostics[0].message.contains("function bump"));
+
+    let location = diagnostics[0].location(sql);
+    assert_eq!((location.line, location.column), (9, 10));
+}
+
+#[test]
+fn plpgsql_do_block_and_unknown_variable() {
+    let sql = "DO '\nBEGIN\n  PERFORM ''a'';\n  y := 1;\nEND';\n";
+    let diagnostics = check_plpgsql(sql);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.starts_with("\"y\" is not a known variable"));
+
+    let location = diagnostics[0].location(sql);
+    assert_eq!((location.line, location.column), (4, 3));
+
+    assert!(check_plpgsql("CREATE FUNCTION f() RETURNS int LANGUAGE sql AS $$ select 1 $$;").is_empty());
+    assert!(check_plpgsql("DO $$ BEGIN PERFORM 1; END $$;").is_empty());
+}
+
+#[test]
+fn plpgsql_errors_in_long_bodies_are_located() {
+    let body = "  PERFORM 1;\n".repeat(2000);
+    let sql = format!("DO $$\nBEGIN\n{body}  retrun 1;\n{body}END $$;\n");
+    let diagnostics = check_plpgsql(&sql);
+    assert_eq!(diagnostics.len(), 1);
+
+    let location = diagnostics[0].location(&sql);
+    assert_eq!((location.line, location.column), (2003, 3));
+}
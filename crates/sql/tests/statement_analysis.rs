@@ -0,0 +1,96 @@
+use vellum_sql_engine::{analyze_statement, LockLevel, RelationLock};
+
+fn lock(relation: &str, level: LockLevel) -> RelationLock {
+    RelationLock {
+        relation: relation.to_string(),
+        level,
+    }
+}
+
+#[test]
+fn alter_table_add_column_takes_access_exclusive() {
+    let a = analyze_statement("ALTER TABLE public.orders ADD COLUMN note text", None).unwrap();
+    assert_eq!(a.kind, "ALTER TABLE");
+    assert!(a.transactional);
+    assert_eq!(a.locks, vec![lock("public.orders", LockLevel::AccessExclusive)]);
+}
+
+#[test]
+fn foreign_key_and_validate_take_weaker_locks() {
+    let a = analyze_statement(
+        "ALTER TABLE orders ADD CONSTRAINT fk FOREIGN KEY (user_id) REFERENCES users (id) NOT VALID",
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        a.locks,
+        vec![
+            lock("users", LockLevel::ShareRowExclusive),
+            lock("orders", LockLevel::ShareRowExclusive),
+        ]
+    );
+
+    let a = analyze_statement("ALTER TABLE orders VALIDATE CONSTRAINT fk", None).unwrap();
+    assert_eq!(a.locks, vec![lock("orders", LockLevel::ShareUpdateExclusive)]);
+}
+
+#[test]
+fn concurrent_index_is_not_transactional() {
+    let a = analyze_statement("CREATE INDEX idx ON orders (user_id)", None).unwrap();
+    assert_eq!(a.kind, "CREATE INDEX");
+    assert!(a.transactional);
+    assert_eq!(a.locks, vec![lock("orders", LockLevel::Share)]);
+
+    let a = analyze_statement("CREATE INDEX CONCURRENTLY idx ON orders (user_id)", None).unwrap();
+    assert!(!a.transactional);
+    assert_eq!(a.locks, vec![lock("orders", LockLevel::ShareUpdateExclusive)]);
+}
+
+#[test]
+fn dml_and_transaction_control_are_classified() {
+    let a = analyze_statement("UPDATE orders SET total = 0 FROM users WHERE users.id = orders.user_id", None)
+        .unwrap();
+    assert_eq!(a.kind, "UPDATE");
+    assert_eq!(
+        a.locks,
+        vec![
+            lock("orders", LockLevel::RowExclusive),
+            lock("users", LockLevel::AccessShare),
+        ]
+    );
+
+    let a = analyze_statement("COMMIT", None).unwrap();
+    assert!(a.transaction_control);
+    assert!(!a.transactional);
+
+    let a = analyze_statement("DROP TABLE a, s.b", None).unwrap();
+    assert_eq!(a.kind, "DROP TABLE");
+    assert_eq!(
+        a.locks,
+        vec![lock("a", LockLevel::AccessExclusive), lock("s.b", LockLevel::AccessExclusive)]
+    );
+}
+
+#[test]
+fn only_data_dependent_dml_is_explainable() {
+    let explainable = |sql: &str| analyze_statement(sql, None).unwrap().explainable;
+    assert!(explainable("UPDATE users SET active = true"));
+    assert!(explainable("DELETE FROM events WHERE created_at < now()"));
+    assert!(explainable("INSERT INTO archive SELECT * FROM events"));
+    assert!(!explainable("INSERT INTO users (id) VALUES (1), (2)"));
+    assert!(!explainable("ALTER TABLE users ADD COLUMN note text"));
+}
+
+#[test]
+fn less_common_statements_get_readable_kinds() {
+    for (sql, kind) in [
+        ("CREATE ROLE app_reader", "CREATE ROLE"),
+        ("REVOKE app_reader FROM app", "REVOKE ROLE"),
+        ("CREATE AGGREGATE total (int) (sfunc = int4pl, stype = int)", "CREATE AGGREGATE"),
+        ("ALTER DEFAULT PRIVILEGES GRANT SELECT ON TABLES TO app_reader", "ALTER DEFAULT PRIVILEGES"),
+        ("CALL refresh_totals()", "CALL"),
+        ("SELECT 1 UNION SELECT 2", "SELECT"),
+    ] {
+        assert_eq!(analyze_statement(sql, None).unwrap().kind, kind, "{sql}");
+    }
+}
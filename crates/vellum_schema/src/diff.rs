@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+
+use crate::normalize::normalize_schema;
+use crate::{Column, Constraint, ConstraintKind, EnumType, Index, Schema, Table};
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifferenceKind {
+    Missing,
+    Unexpected,
+    Changed,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaDifference {
+    pub kind: DifferenceKind,
+    pub object: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+pub fn diff_schemas(
+    expected: &BTreeMap<String, Schema>,
+    actual: &BTreeMap<String, Schema>,
+) -> Vec<SchemaDifference> {
+    let mut differences = Vec::new();
+
+    for (name, schema) in expected {
+        match actual.get(name) {
+            Some(other) => differences.extend(diff_schema(schema, other)),
+            None => differences.push(missing(format!("schema {name}"), describe_schema(schema))),
+        }
+    }
+
+    for (name, schema) in actual {
+        if !expected.contains_key(name) {
+            differences.push(unexpected(format!("schema {name}"), describe_schema(schema)));
+        }
+    }
+
+    differences
+}
+
+pub fn diff_schema(expected: &Schema, actual: &Schema) -> Vec<SchemaDifference> {
+    let expected = normalize_schema(expected);
+    let actual = normalize_schema(actual);
+    let mut differences = Vec::new();
+
+    diff_map(
+        &mut differences,
+        &format!("enum {}.", expected.name),
+        &expected.enum_types,
+        &actual.enum_types,
+        describe_enum,
+    );
+
+    for (name, table) in &expected.tables {
+        let object = format!("table {}.{name}", expected.name);
+        match actual.tables.get(name) {
+            Some(other) => diff_table(&mut differences, &expected.name, table, other),
+            None => differences.push(missing(object, describe_table(table))),
+        }
+    }
+
+    for (name, table) in &actual.tables {
+        if !expected.tables.contains_key(name) {
+            let object = format!("table {}.{name}", expected.name);
+            differences.push(unexpected(object, describe_table(table)));
+        }
+    }
+
+    differences
+}
+
+fn diff_table(out: &mut Vec<SchemaDifference>, schema: &str, expected: &Table, actual: &Table) {
+    let table = format!("{schema}.{}", expected.name);
+    diff_map(
+        out,
+        &format!("column {table}."),
+        &expected.columns,
+        &actual.columns,
+        describe_column,
+    );
+    diff_map(
+        out,
+        &format!("index {table}."),
+        &expected.indexes,
+        &actual.indexes,
+        describe_index,
+    );
+    diff_map(
+        out,
+        &format!("constraint {table}."),
+        &expected.constraints,
+        &actual.constraints,
+        describe_constraint,
+    );
+}
+
+fn diff_map<T>(
+    out: &mut Vec<SchemaDifference>,
+    prefix: &str,
+    expected: &BTreeMap<String, T>,
+    actual: &BTreeMap<String, T>,
+    describe: impl Fn(&T) -> String,
+) {
+    for (name, item) in expected {
+        let object = format!("{prefix}{name}");
+        match actual.get(name) {
+            Some(other) => {
+                let (expected, actual) = (describe(item), describe(other));
+                if expected != actual {
+                    out.push(SchemaDifference {
+                        kind: DifferenceKind::Changed,
+                        object,
+                        expected: Some(expected),
+                        actual: Some(actual),
+                    });
+                }
+            }
+            None => out.push(missing(object, describe(item))),
+        }
+    }
+
+    for (name, item) in actual {
+        if !expected.contains_key(name) {
+            out.push(unexpected(format!("{prefix}{name}"), describe(item)));
+        }
+    }
+}
+
+fn missing(object: String, description: String) -> SchemaDifference {
+    SchemaDifference {
+        kind: DifferenceKind::Missing,
+        object,
+        expected: Some(description),
+        actual: None,
+    }
+}
+
+fn unexpected(object: String, description: String) -> SchemaDifference {
+    SchemaDifference {
+        kind: DifferenceKind::Unexpected,
+        object,
+        expected: None,
+        actual: Some(description),
+    }
+}
+
+fn describe_schema(schema: &Schema) -> String {
+    format!("{} table(s)", schema.tables.len())
+}
+
+fn describe_table(table: &Table) -> String {
+    format!("{} column(s)", table.columns.len())
+}
+
+fn describe_column(column: &Column) -> String {
+    let mut description = column.data_type.clone();
+    if !column.nullable {
+        description.push_str(" not null");
+    }
+    if let Some(default) = &column.default {
+        description.push_str(" default ");
+        description.push_str(default);
+    }
+    description
+}
+
+fn describe_index(index: &Index) -> String {
+    let unique = if index.unique { "unique " } else { "" };
+    format!("{unique}{} ({})", index.method, index.columns.join(", "))
+}
+
+fn describe_constraint(constraint: &Constraint) -> String {
+    match &constraint.kind {
+        ConstraintKind::PrimaryKey { columns } => format!("primary key ({})", columns.join(", ")),
+        ConstraintKind::Unique { columns } => format!("unique ({})", columns.join(", ")),
+        ConstraintKind::ForeignKey {
+            columns,
+            referenced_table,
+            referenced_columns,
+        } => format!(
+            "foreign key ({}) references {referenced_table} ({})",
+            columns.join(", "),
+            referenced_columns.join(", ")
+        ),
+        ConstraintKind::Check { expression } => format!("check {expression}"),
+    }
+}
+
+fn describe_enum(enum_type: &EnumType) -> String {
+    format!("({})", enum_type.values.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{diff_schema, diff_schemas, DifferenceKind};
+    use crate::{Column, EnumType, Schema, Table};
+
+    fn column(name: &str, data_type: &str, nullable: bool) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default: None,
+        }
+    }
+
+    fn schema(tables: Vec<(&str, Vec<Column>)>) -> Schema {
+        Schema {
+            name: "public".to_string(),
+            tables: tables
+                .into_iter()
+                .map(|(name, columns)| {
+                    let table = Table {
+                        name: name.to_string(),
+                        columns: columns.into_iter().map(|c| (c.name.clone(), c)).collect(),
+                        indexes: BTreeMap::new(),
+                        constraints: BTreeMap::new(),
+                    };
+                    (name.to_string(), table)
+                })
+                .collect(),
+            enum_types: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn equivalent_schemas_have_no_differences() {
+        let expected = schema(vec![("users", vec![column("id", "int8", false)])]);
+        let actual = schema(vec![("USERS", vec![column("ID", "bigint", false)])]);
+
+        assert!(diff_schema(&expected, &actual).is_empty());
+    }
+
+    #[test]
+    fn reports_missing_unexpected_and_changed_objects() {
+        let expected = schema(vec![
+            (
+                "users",
+                vec![column("id", "bigint", false), column("email", "text", false)],
+            ),
+            ("orders", vec![column("id", "bigint", false)]),
+        ]);
+        let mut actual = schema(vec![
+            (
+                "users",
+                vec![column("id", "bigint", false), column("email", "text", true)],
+            ),
+            ("audit", vec![column("id", "bigint", false)]),
+        ]);
+        actual.enum_types.insert(
+            "mood".to_string(),
+            EnumType {
+                name: "mood".to_string(),
+                values: vec!["happy".to_string()],
+            },
+        );
+
+        let differences = diff_schema(&expected, &actual);
+        let summary: Vec<(DifferenceKind, &str)> = differences
+            .iter()
+            .map(|d| (d.kind, d.object.as_str()))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (DifferenceKind::Unexpected, "enum public.mood"),
+                (DifferenceKind::Missing, "table public.orders"),
+                (DifferenceKind::Changed, "column public.users.email"),
+                (DifferenceKind::Unexpected, "table public.audit"),
+            ]
+        );
+        assert_eq!(differences[2].expected.as_deref(), Some("text not null"));
+        assert_eq!(differences[2].actual.as_deref(), Some("text"));
+
+        let mut expected_schemas = BTreeMap::new();
+        expected_schemas.insert("public".to_string(), expected);
+        let differences = diff_schemas(&expected_schemas, &BTreeMap::new());
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].object, "schema public");
+    }
+}
@@ -1,5 +1,6 @@
 mod column;
 mod constraint;
+mod diff;
 mod enum_type;
 mod index;
 pub mod normalize;
@@ -8,6 +9,7 @@ mod table;
 
 pub use column::Column;
 pub use constraint::{Constraint, ConstraintKind};
+pub use diff::{diff_schema, diff_schemas, DifferenceKind, SchemaDifference};
 pub use enum_type::EnumType;
 pub use index::Index;
 pub use schema::Schema;
@@ -9,8 +9,8 @@ pub fn normalize_schema(schema: &Schema) -> Schema {
 
     let tables = schema
         .tables
-        .iter()
-        .map(|(_, t)| {
+        .values()
+        .map(|t| {
             let t = normalize_table(t);
             (t.name.clone(), t)
         })
@@ -18,8 +18,8 @@ pub fn normalize_schema(schema: &Schema) -> Schema {
 
     let enum_types = schema
         .enum_types
-        .iter()
-        .map(|(_, e)| {
+        .values()
+        .map(|e| {
             let e = EnumType {
                 name: super::normalize_name(&e.name),
                 values: e.values.clone(),
@@ -9,8 +9,8 @@ pub fn normalize_table(table: &Table) -> Table {
 
     let columns = table
         .columns
-        .iter()
-        .map(|(_, c)| {
+        .values()
+        .map(|c| {
             let c = normalize_column(c);
             (c.name.clone(), c)
         })
@@ -18,8 +18,8 @@ pub fn normalize_table(table: &Table) -> Table {
 
     let constraints = table
         .constraints
-        .iter()
-        .map(|(_, c)| {
+        .values()
+        .map(|c| {
             let c = normalize_constraint(&name, c);
             (c.name.clone(), c)
         })
@@ -32,8 +32,8 @@ pub fn normalize_table(table: &Table) -> Table {
 
     let indexes = table
         .indexes
-        .iter()
-        .map(|(_, i)| {
+        .values()
+        .map(|i| {
             let i = normalize_index(i);
             (i.name.clone(), i)
         })
@@ -7,18 +7,22 @@ pub enum SqlParseError {
         message: String,
         position: Option<usize>,
     },
+    #[error("sql deparse failed: {message}")]
+    DeparseFailed { message: String },
 }
 
 impl SqlParseError {
     pub fn message(&self) -> &str {
         match self {
             SqlParseError::ParseFailed { message, .. } => message,
+            SqlParseError::DeparseFailed { message } => message,
         }
     }
 
     pub fn position(&self) -> Option<usize> {
         match self {
             SqlParseError::ParseFailed { position, .. } => *position,
+            SqlParseError::DeparseFailed { .. } => None,
         }
     }
 }
@@ -11,15 +11,28 @@ impl ParsedSql {
         &self.sql
     }
 
-    pub(crate) fn ast(&self) -> &pg_query::ParseResult {
+    pub fn ast(&self) -> &pg_query::ParseResult {
         &self.ast
     }
+
+    // PostgreSQL major version of the bundled parser, e.g. 17.
+    pub fn parser_major_version(&self) -> i32 {
+        self.ast.protobuf.version / 10000
+    }
+
+    pub fn normalized(&self) -> Result<String, SqlParseError> {
+        self.ast
+            .deparse()
+            .map_err(|e| SqlParseError::DeparseFailed {
+                message: e.to_string(),
+            })
+    }
 }
 
 pub fn parse_sql(sql: &str) -> Result<ParsedSql, SqlParseError> {
     let parsed = pg_query::parse(sql).map_err(|e| SqlParseError::ParseFailed {
         message: e.to_string(),
-        position: extract_position(&e),
+        position: extract_position(&e).or_else(|| locate_error(sql)),
     })?;
 
     Ok(ParsedSql {
@@ -51,3 +64,51 @@ fn extract_position(err: &pg_query::Error) -> Option<usize> {
 
     digits[..end].parse::<usize>().ok()
 }
+
+// pg_query does not expose the error cursor, so find the first token whose
+// inclusion turns the error into something other than "at end of input".
+fn locate_error(sql: &str) -> Option<usize> {
+    let tokens = match pg_query::scan(sql) {
+        Ok(scanned) => scanned.tokens,
+        Err(_) => return locate_lexer_error(sql),
+    };
+
+    let fails_at_token = |end: i32| {
+        let end = usize::try_from(end).unwrap_or(sql.len()).min(sql.len());
+        match pg_query::parse(&sql[..end]) {
+            Ok(_) => false,
+            Err(e) => !e.to_string().contains("at end of input"),
+        }
+    };
+
+    let (mut lo, mut hi) = (0usize, tokens.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if fails_at_token(tokens[mid].end) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+
+    let offset = match tokens.get(lo) {
+        Some(token) => usize::try_from(token.start).ok()?,
+        None => sql.trim_end().len(),
+    };
+    Some(char_position(sql, offset))
+}
+
+fn locate_lexer_error(sql: &str) -> Option<usize> {
+    let err = pg_query::parse(sql).err()?.to_string();
+    let near = err.split(" at or near \"").nth(1)?;
+    let near = near.strip_suffix('"').unwrap_or(near);
+    let offset = sql.find(near)?;
+    Some(char_position(sql, offset))
+}
+
+fn char_position(sql: &str, byte_offset: usize) -> usize {
+    sql.get(..byte_offset)
+        .map(|prefix| prefix.chars().count())
+        .unwrap_or(0)
+        + 1
+}
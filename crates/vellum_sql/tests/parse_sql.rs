@@ -23,3 +23,26 @@ fn error_message_is_not_empty() {
     let err = parse_sql("SELECT FROM;").unwrap_err();
     assert!(!err.message().is_empty());
 }
+
+#[test]
+fn normalized_ignores_comments_and_formatting() {
+    let a = parse_sql("-- add users\nCREATE TABLE users (id int);\r\n").unwrap();
+    let b = parse_sql("create   table users(\n  id int   -- pk\n);").unwrap();
+    assert_eq!(a.normalized().unwrap(), b.normalized().unwrap());
+}
+
+#[test]
+fn normalized_keeps_literals() {
+    let a = parse_sql("ALTER TABLE t ALTER COLUMN c SET DEFAULT 1;").unwrap();
+    let b = parse_sql("ALTER TABLE t ALTER COLUMN c SET DEFAULT 2;").unwrap();
+    assert_ne!(a.normalized().unwrap(), b.normalized().unwrap());
+}
+
+#[test]
+fn error_position_points_at_offending_token() {
+    let err = parse_sql("SELECT 1;\nCREATE TABL users (id int);").unwrap_err();
+    assert_eq!(err.position(), Some(18));
+
+    let err = parse_sql("SELECT 1;\nCREATE TABLE users (id int").unwrap_err();
+    assert_eq!(err.position(), Some(37));
+}
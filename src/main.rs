@@ -9,13 +9,51 @@ async fn main() {
     let result = match cli.command {
         vellum_cli::Command::Migrate(args) => vellum_cli::migrate::run(
             &args,
-            cli.database_url.as_deref(),
+            &cli.connection,
             env!("CARGO_PKG_VERSION"),
         )
         .await,
         vellum_cli::Command::Status(args) => {
-            vellum_cli::status::run(&args, cli.database_url.as_deref()).await
+            vellum_cli::status::run(&args, &cli.connection).await
         }
+        vellum_cli::Command::Plan(args) => vellum_cli::plan::run(
+            &args,
+            &cli.connection,
+            env!("CARGO_PKG_VERSION"),
+        )
+        .await,
+        vellum_cli::Command::Apply(args) => vellum_cli::apply::run(
+            &args,
+            &cli.connection,
+            env!("CARGO_PKG_VERSION"),
+        )
+        .await,
+        vellum_cli::Command::Repair(args) => vellum_cli::repair::run(
+            &args,
+            &cli.connection,
+            env!("CARGO_PKG_VERSION"),
+        )
+        .await,
+        vellum_cli::Command::Check(args) => vellum_cli::check::run(&args),
+        vellum_cli::Command::Verify(args) => vellum_cli::verify::run(
+            &args,
+            &cli.connection,
+            env!("CARGO_PKG_VERSION"),
+        )
+        .await,
+        vellum_cli::Command::Lock(args) => {
+            vellum_cli::lock::run(&args, &cli.connection).await
+        }
+        vellum_cli::Command::History(args) => {
+            vellum_cli::history::run(&args, &cli.connection, env!("CARGO_PKG_VERSION")).await
+        }
+        vellum_cli::Command::New(args) => vellum_cli::new::run(&args),
+        vellum_cli::Command::Baseline(args) => vellum_cli::baseline::run(
+            &args,
+            &cli.connection,
+            env!("CARGO_PKG_VERSION"),
+        )
+        .await,
     };
 
     if let Err(err) = result {